@@ -0,0 +1,144 @@
+//! Textures baked from the surfaces of objects
+//!
+//! Every texel is mapped back onto the surfaces by
+//! `TextureCoordinates::surface_point` and shaded there, turning procedural
+//! materials into images which `ImageTexture` can load.
+use crate::color::Color;
+use crate::img::Image;
+use crate::light::LightTrait;
+use crate::object::ObjectTrait;
+use crate::texture::ShadingContext;
+use crate::uv::{Footprint, Uv};
+
+/// Texture of `surfaces` over the `[0, 1]` uv square, `v` going up from the
+/// bottom row like in `ImageTexture`
+///
+/// Texels are shaded on the front of the first surface covering them and left
+/// black where none does. With `lights`, the colors are lit by their direct
+/// diffuse and ambient lighting, which does not depend on the viewer.
+pub fn bake(
+    surfaces: &[&dyn ObjectTrait],
+    lights: Option<&[Box<dyn LightTrait>]>,
+    height: usize,
+    width: usize,
+) -> Image {
+    let mut img = Image::new(height, width);
+    // Texture coordinate differences of one texel, for filtered textures
+    let footprint = Footprint {
+        dx: Uv::new(1f64 / width as f64, 0f64),
+        dy: Uv::new(0f64, -1f64 / height as f64),
+    };
+
+    for i in 0..height {
+        for j in 0..width {
+            let uv = Uv::new(
+                (j as f64 + 0.5f64) / width as f64,
+                1f64 - (i as f64 + 0.5f64) / height as f64,
+            );
+            let hit = surfaces
+                .iter()
+                .find_map(|obj| obj.surface_point(uv).map(|p| (*obj, p)));
+
+            let color = match hit {
+                Some((obj, p)) => {
+                    let ctx = ShadingContext {
+                        normal: obj.normal(p),
+                        uv,
+                        footprint,
+                        id: obj.id(),
+                        level: obj.level(),
+                        ..ShadingContext::at(p)
+                    };
+                    match lights {
+                        Some(lights) => direct(obj, &ctx, lights),
+                        None => obj.material().texture.color(&ctx).to_linear(),
+                    }
+                }
+                None => Color::BLACK,
+            };
+            img.push(color);
+        }
+    }
+    img
+}
+
+/// Linear color of the point of `ctx` on `obj` lit by `lights`, following the
+/// diffuse and ambient terms of the scene
+fn direct(obj: &dyn ObjectTrait, ctx: &ShadingContext, lights: &[Box<dyn LightTrait>]) -> Color {
+    let material = obj.material();
+    let shading = material.shading_normal(ctx, obj.tangents(ctx.point));
+    let (kd, _, ka) = material.texture.coefficients(ctx);
+    let albedo = material.texture.color(ctx).to_linear().to_vec();
+
+    let mut color = material.emission.to_linear();
+    for light in lights {
+        let l = (light.point() - ctx.point).normalize();
+        let diffuse = albedo.mul(light.intensity().normalize()) * kd * (shading * l).max(0f64);
+        color = color + Color::from(diffuse + albedo * ka);
+    }
+    color
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bake::bake;
+    use crate::color::Color;
+    use crate::light::point::PointLight;
+    use crate::light::LightTrait;
+    use crate::material::Material;
+    use crate::object::sphere::Sphere;
+    use crate::object::triangle::Triangle;
+    use crate::object::ObjectTrait;
+    use crate::texture::uniform::UniformTexture;
+    use crate::{Point, Vector};
+    use std::rc::Rc;
+
+    fn material(color: Color) -> Rc<Material> {
+        Rc::new(Material::new(Box::new(UniformTexture {
+            kd: 1f64,
+            ks: 0f64,
+            ka: 0f64,
+            color,
+        })))
+    }
+
+    #[test]
+    fn coverage() {
+        // Default texture coordinates cover the bottom left half
+        let triangle = Triangle::new(
+            Point::ZERO,
+            Point::new(1f64, 0f64, 0f64),
+            Point::new(0f64, 1f64, 0f64),
+            material(Color::RED),
+            String::from("triangle"),
+        );
+        let img = bake(&[&triangle], None, 4, 4);
+
+        assert_eq!(img.pixels().len(), 16);
+        assert_eq!(img.pixels()[12].values(), (255, 0, 0));
+        assert_eq!(img.pixels()[3], Color::BLACK);
+    }
+
+    #[test]
+    fn lit() {
+        let sphere = Sphere {
+            p: Point::ZERO,
+            r: 1f64,
+            material: material(Color::WHITE),
+            id: String::from("sphere"),
+        };
+        let lights: Vec<Box<dyn LightTrait>> = vec![Box::new(PointLight {
+            point: Point::new(10f64, 0f64, 0f64),
+            intensity: Vector::new(255f64, 255f64, 255f64),
+        })];
+        let surfaces: [&dyn ObjectTrait; 1] = [&sphere];
+        let img = bake(&surfaces, Some(&lights), 2, 4);
+
+        // Longitudes of the columns centered on -135, -45, 45 and 135 degrees
+        let row = &img.pixels()[0..4];
+        assert_eq!(row[0], Color::BLACK);
+        assert_eq!(row[3], Color::BLACK);
+        assert!(row[1].luminance() > 0f64);
+        assert_eq!(row[1], row[2]);
+    }
+}
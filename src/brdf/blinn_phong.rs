@@ -0,0 +1,120 @@
+//! Energy normalized Blinn-Phong
+use crate::brdf::{cosine_hemisphere, reflect, spherical, weighted, Brdf, Sample};
+use crate::vector::Vector;
+use std::f64::consts::PI;
+
+/// Lambertian `diffuse` plus a highlight around the half vector, normalized
+/// so that the highlight keeps its energy whatever the shininess
+pub struct BlinnPhong {
+    pub diffuse: Vector,
+    pub specular: Vector,
+    /// Exponent of the cosine between the normal and the half vector
+    pub shininess: f64,
+}
+
+impl BlinnPhong {
+    /// Chance of sampling the highlight rather than the diffuse lobe
+    #[allow(dead_code)]
+    fn specular_probability(&self) -> f64 {
+        let (diffuse, specular) = (self.diffuse.sum(), self.specular.sum());
+        if diffuse + specular <= 0f64 {
+            return 0f64;
+        }
+        specular / (diffuse + specular)
+    }
+
+    /// Density of half vectors `(s + 1) / 2PI * cos^s`
+    #[allow(dead_code)]
+    fn half_pdf(&self, n_h: f64) -> f64 {
+        (self.shininess + 1f64) / (2f64 * PI) * n_h.powf(self.shininess)
+    }
+}
+
+impl Brdf for BlinnPhong {
+    fn eval(&self, normal: Vector, wo: Vector, wi: Vector) -> Vector {
+        if wo * normal <= 0f64 || wi * normal <= 0f64 {
+            return Vector::ZERO;
+        }
+
+        let h = (wo + wi).normalize();
+        let lobe = (self.shininess + 8f64) / (8f64 * PI) * (normal * h).powf(self.shininess);
+        self.diffuse / PI + self.specular * lobe
+    }
+
+    fn pdf(&self, normal: Vector, wo: Vector, wi: Vector) -> f64 {
+        if wi * normal <= 0f64 {
+            return 0f64;
+        }
+
+        let h = (wo + wi).normalize();
+        let specular = self.half_pdf((normal * h).max(0f64)) / (4f64 * (wo * h).abs());
+        let p = self.specular_probability();
+        p * specular + (1f64 - p) * (wi * normal) / PI
+    }
+
+    fn sample(&self, normal: Vector, wo: Vector, (u1, u2): (f64, f64)) -> Option<Sample> {
+        let p = self.specular_probability();
+
+        let wi = if u1 < p {
+            let u1 = u1 / p;
+            let h = spherical(
+                normal,
+                u1.powf(1f64 / (self.shininess + 1f64)),
+                2f64 * PI * u2,
+            );
+            reflect(wo, h)
+        } else {
+            cosine_hemisphere(normal, ((u1 - p) / (1f64 - p), u2))
+        };
+
+        weighted(self, normal, wo, wi)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::brdf::blinn_phong::BlinnPhong;
+    use crate::brdf::tests::{albedo, direction, NORMAL};
+    use crate::brdf::Brdf;
+    use crate::vector::Vector;
+
+    fn brdf(shininess: f64) -> BlinnPhong {
+        BlinnPhong {
+            diffuse: Vector::ONE * 0.5f64,
+            specular: Vector::ONE * 0.5f64,
+            shininess,
+        }
+    }
+
+    #[test]
+    fn reciprocity() {
+        let brdf = brdf(20f64);
+        let (a, b) = (direction(0.3f64, 0f64), direction(0.6f64, 2f64));
+        assert_eq!(brdf.eval(NORMAL, a, b), brdf.eval(NORMAL, b, a));
+    }
+
+    #[test]
+    fn highlight_narrows_with_shininess() {
+        let wo = direction(0.5f64, 0f64);
+        let mirror = direction(0.5f64, std::f64::consts::PI);
+        let off = direction(0.8f64, std::f64::consts::PI);
+
+        let (dull, sharp) = (brdf(10f64), brdf(500f64));
+        assert!(sharp.eval(NORMAL, wo, mirror).x > dull.eval(NORMAL, wo, mirror).x);
+        assert!(sharp.eval(NORMAL, wo, off).x < dull.eval(NORMAL, wo, off).x);
+    }
+
+    #[test]
+    fn sampling() {
+        for shininess in [1f64, 50f64] {
+            let (estimate, pdf_error) = albedo(&brdf(shininess), NORMAL);
+
+            assert!(pdf_error < 1e-6);
+            assert!(
+                estimate.x > 0.5f64 && estimate.x < 1.05f64,
+                "{:?}",
+                estimate
+            );
+        }
+    }
+}
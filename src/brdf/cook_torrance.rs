@@ -0,0 +1,184 @@
+//! Cook-Torrance microfacets with a GGX distribution
+//!
+//! From Walter et al., "Microfacet Models for Refraction through Rough
+//! Surfaces": GGX normal distribution, separable Smith shadowing and Schlick
+//! Fresnel.
+use crate::brdf::{cosine_hemisphere, reflect, spherical, weighted, Brdf, Sample};
+use crate::vector::Vector;
+use std::f64::consts::PI;
+
+/// Reflectance of dielectrics at normal incidence
+const DIELECTRIC_F0: f64 = 0.04f64;
+
+/// Rough metal or plastic
+///
+/// Metals reflect `albedo` tinted light only, dielectrics add a diffuse
+/// `albedo` layer under a white coat. `metallic` blends the two.
+pub struct CookTorrance {
+    pub albedo: Vector,
+    /// Perceptual roughness in `[0, 1]`, the GGX width being its square
+    pub roughness: f64,
+    pub metallic: f64,
+}
+
+/// Density of microfacets of normal `h`, `n_h` being `normal * h`
+pub fn ggx(n_h: f64, alpha: f64) -> f64 {
+    let a2 = alpha * alpha;
+    let d = n_h * n_h * (a2 - 1f64) + 1f64;
+    a2 / (PI * d * d)
+}
+
+/// Share of microfacets seen from a direction at `n_v` from the normal
+pub fn smith_g1(n_v: f64, alpha: f64) -> f64 {
+    let a2 = alpha * alpha;
+    2f64 * n_v / (n_v + (a2 + (1f64 - a2) * n_v * n_v).sqrt())
+}
+
+/// Fresnel reflectance at `cos` from the normal, `f0` at normal incidence
+pub fn schlick(f0: Vector, cos: f64) -> Vector {
+    f0 + (Vector::ONE - f0) * (1f64 - cos).clamp(0f64, 1f64).powf(5f64)
+}
+
+impl CookTorrance {
+    /// GGX width, kept above zero so that mirrors still have a density
+    fn alpha(&self) -> f64 {
+        (self.roughness * self.roughness).max(1e-3)
+    }
+
+    fn f0(&self) -> Vector {
+        Vector::ONE * DIELECTRIC_F0 * (1f64 - self.metallic) + self.albedo * self.metallic
+    }
+
+    /// Chance of sampling the microfacets rather than the diffuse layer
+    #[allow(dead_code)]
+    fn specular_probability(&self) -> f64 {
+        0.5f64 + 0.5f64 * self.metallic
+    }
+}
+
+impl Brdf for CookTorrance {
+    fn eval(&self, normal: Vector, wo: Vector, wi: Vector) -> Vector {
+        let (n_o, n_i) = (wo * normal, wi * normal);
+        if n_o <= 0f64 || n_i <= 0f64 {
+            return Vector::ZERO;
+        }
+
+        let alpha = self.alpha();
+        let h = (wo + wi).normalize();
+        let fresnel = schlick(self.f0(), wi * h);
+        let specular = fresnel
+            * (ggx(normal * h, alpha) * smith_g1(n_o, alpha) * smith_g1(n_i, alpha)
+                / (4f64 * n_o * n_i));
+        let diffuse = (Vector::ONE - fresnel).mul(self.albedo) * (1f64 - self.metallic) / PI;
+
+        specular + diffuse
+    }
+
+    fn pdf(&self, normal: Vector, wo: Vector, wi: Vector) -> f64 {
+        if wi * normal <= 0f64 {
+            return 0f64;
+        }
+
+        let h = (wo + wi).normalize();
+        let n_h = (normal * h).max(0f64);
+        let specular = ggx(n_h, self.alpha()) * n_h / (4f64 * (wo * h).abs());
+        let p = self.specular_probability();
+        p * specular + (1f64 - p) * (wi * normal) / PI
+    }
+
+    fn sample(&self, normal: Vector, wo: Vector, (u1, u2): (f64, f64)) -> Option<Sample> {
+        let p = self.specular_probability();
+
+        let wi = if u1 < p {
+            // Half vectors distributed as `D(h) * cos`
+            let u1 = u1 / p;
+            let a2 = self.alpha() * self.alpha();
+            let cos_theta = ((1f64 - u1) / (1f64 + (a2 - 1f64) * u1)).sqrt();
+            reflect(wo, spherical(normal, cos_theta, 2f64 * PI * u2))
+        } else {
+            cosine_hemisphere(normal, ((u1 - p) / (1f64 - p), u2))
+        };
+
+        weighted(self, normal, wo, wi)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::brdf::cook_torrance::{ggx, schlick, CookTorrance};
+    use crate::brdf::tests::{albedo, direction, NORMAL};
+    use crate::brdf::Brdf;
+    use crate::vector::Vector;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn ggx_is_normalized() {
+        // Projected microfacet area adds up to the macro surface
+        for alpha in [0.1f64, 0.5f64, 1f64] {
+            let steps = 100000;
+            let dtheta = PI / 2f64 / steps as f64;
+            let area = (0..steps)
+                .map(|i| (i as f64 + 0.5f64) * dtheta)
+                .map(|theta| ggx(theta.cos(), alpha) * theta.cos() * theta.sin())
+                .sum::<f64>()
+                * 2f64
+                * PI
+                * dtheta;
+
+            assert!((area - 1f64).abs() < 1e-3, "{} {}", alpha, area);
+        }
+    }
+
+    #[test]
+    fn schlick_limits() {
+        let f0 = Vector::ONE * 0.04f64;
+        assert_eq!(schlick(f0, 1f64), f0);
+        assert_eq!(schlick(f0, 0f64), Vector::ONE);
+    }
+
+    #[test]
+    fn reciprocity() {
+        let brdf = CookTorrance {
+            albedo: Vector::new(0.9f64, 0.6f64, 0.2f64),
+            roughness: 0.4f64,
+            metallic: 0.5f64,
+        };
+        let (a, b) = (direction(0.3f64, 0f64), direction(1.1f64, 2f64));
+        assert!((brdf.eval(NORMAL, a, b) - brdf.eval(NORMAL, b, a)).mag() < 1e-12);
+    }
+
+    #[test]
+    fn metal_is_tinted() {
+        let gold = CookTorrance {
+            albedo: Vector::new(1f64, 0.8f64, 0.3f64),
+            roughness: 0.3f64,
+            metallic: 1f64,
+        };
+        let wo = direction(0.4f64, 0f64);
+        let f = gold.eval(NORMAL, wo, direction(0.4f64, PI));
+
+        assert!(f.x > f.y && f.y > f.z);
+    }
+
+    #[test]
+    fn sampling() {
+        for (roughness, metallic) in [(0.2f64, 1f64), (0.5f64, 1f64), (0.5f64, 0f64)] {
+            let brdf = CookTorrance {
+                albedo: Vector::ONE,
+                roughness,
+                metallic,
+            };
+            let (estimate, pdf_error) = albedo(&brdf, direction(0.5f64, 0f64));
+
+            assert!(pdf_error < 1e-6);
+            // Smith shadowing loses some energy, nothing is created
+            assert!(
+                estimate.x > 0.8f64 && estimate.x < 1.01f64,
+                "{} {} {:?}",
+                roughness,
+                metallic,
+                estimate
+            );
+        }
+    }
+}
@@ -0,0 +1,58 @@
+//! Ideal diffuse reflection
+use crate::brdf::{cosine_hemisphere, weighted, Brdf, Sample};
+use crate::vector::Vector;
+use std::f64::consts::PI;
+
+/// Reflects `albedo` of the light evenly in every direction
+#[allow(dead_code)]
+pub struct Lambert {
+    pub albedo: Vector,
+}
+
+impl Brdf for Lambert {
+    fn eval(&self, normal: Vector, wo: Vector, wi: Vector) -> Vector {
+        if wo * normal <= 0f64 || wi * normal <= 0f64 {
+            return Vector::ZERO;
+        }
+        self.albedo / PI
+    }
+
+    fn pdf(&self, normal: Vector, _wo: Vector, wi: Vector) -> f64 {
+        (wi * normal).max(0f64) / PI
+    }
+
+    fn sample(&self, normal: Vector, wo: Vector, u: (f64, f64)) -> Option<Sample> {
+        weighted(self, normal, wo, cosine_hemisphere(normal, u))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::brdf::lambert::Lambert;
+    use crate::brdf::tests::{albedo, direction, NORMAL};
+    use crate::brdf::Brdf;
+    use crate::vector::Vector;
+
+    #[test]
+    fn white_furnace() {
+        let lambert = Lambert {
+            albedo: Vector::new(1f64, 0.5f64, 0f64),
+        };
+        let (estimate, pdf_error) = albedo(&lambert, direction(1f64, 0f64));
+
+        // Cosine sampling is exact: every weight is the albedo
+        assert!((estimate - lambert.albedo).mag() < 1e-9);
+        assert!(pdf_error < 1e-9);
+    }
+
+    #[test]
+    fn below_surface() {
+        let lambert = Lambert {
+            albedo: Vector::ONE,
+        };
+        assert_eq!(
+            lambert.eval(NORMAL, direction(0.5f64, 0f64), -NORMAL),
+            Vector::ZERO
+        );
+    }
+}
@@ -0,0 +1,155 @@
+//! Reflectance models
+//!
+//! Directions point away from the surface and the normal faces the viewer.
+//! Values are per channel, a white surface having an albedo of `1`.
+use crate::vector::Vector;
+use std::f64::consts::PI;
+
+pub mod blinn_phong;
+pub mod cook_torrance;
+pub mod lambert;
+
+/// Direction drawn by `Brdf::sample`
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[allow(dead_code)]
+pub struct Sample {
+    pub wi: Vector,
+    /// Density of `wi` per solid angle
+    pub pdf: f64,
+    /// `eval(wo, wi) * cos / pdf`, what a path is multiplied by
+    pub weight: Vector,
+}
+
+pub trait Brdf {
+    /// Radiance reflected toward `wo` per unit of irradiance from `wi`
+    fn eval(&self, normal: Vector, wo: Vector, wi: Vector) -> Vector;
+
+    /// Density `sample` draws `wi` with for `wo`
+    #[allow(dead_code)]
+    fn pdf(&self, normal: Vector, wo: Vector, wi: Vector) -> f64;
+
+    /// Draws an incoming direction for `wo` from `u` in `[0, 1)^2`, `None`
+    /// when it falls below the surface
+    #[allow(dead_code)]
+    fn sample(&self, normal: Vector, wo: Vector, u: (f64, f64)) -> Option<Sample>;
+}
+
+/// Tangent and bitangent completing `normal` to an orthonormal basis
+///
+/// From Duff et al., "Building an Orthonormal Basis, Revisited".
+pub fn frame(normal: Vector) -> (Vector, Vector) {
+    let sign = 1f64.copysign(normal.z);
+    let a = -1f64 / (sign + normal.z);
+    let b = normal.x * normal.y * a;
+
+    (
+        Vector::new(
+            1f64 + sign * normal.x * normal.x * a,
+            sign * b,
+            -sign * normal.x,
+        ),
+        Vector::new(b, sign + normal.y * normal.y * a, -normal.y),
+    )
+}
+
+/// Direction of polar angle `acos(cos_theta)` and azimuth `phi` around
+/// `normal`
+#[allow(dead_code)]
+fn spherical(normal: Vector, cos_theta: f64, phi: f64) -> Vector {
+    let (t, b) = frame(normal);
+    let sin_theta = (1f64 - cos_theta * cos_theta).max(0f64).sqrt();
+    (t * phi.cos() + b * phi.sin()) * sin_theta + normal * cos_theta
+}
+
+/// Cosine weighted direction of the hemisphere of `normal`, of density
+/// `cos / PI`
+#[allow(dead_code)]
+pub fn cosine_hemisphere(normal: Vector, (u1, u2): (f64, f64)) -> Vector {
+    spherical(normal, (1f64 - u1).sqrt(), 2f64 * PI * u2)
+}
+
+/// `v` mirrored around `normal`, both pointing away from the surface
+#[allow(dead_code)]
+pub fn reflect(v: Vector, normal: Vector) -> Vector {
+    normal * (v * normal) * 2f64 - v
+}
+
+/// Sample of `brdf` drawn toward `wi`, `None` below the surface
+#[allow(dead_code)]
+fn weighted(brdf: &dyn Brdf, normal: Vector, wo: Vector, wi: Vector) -> Option<Sample> {
+    let cos = wi * normal;
+    let pdf = brdf.pdf(normal, wo, wi);
+    if cos <= 0f64 || pdf <= 0f64 {
+        return None;
+    }
+
+    Some(Sample {
+        wi,
+        pdf,
+        weight: brdf.eval(normal, wo, wi) * cos / pdf,
+    })
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use crate::brdf::{cosine_hemisphere, frame, Brdf};
+    use crate::vector::Vector;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    pub const NORMAL: Vector = Vector::new(0f64, 0f64, 1f64);
+
+    /// Unit direction above `NORMAL` at `theta` radians from it
+    pub fn direction(theta: f64, phi: f64) -> Vector {
+        Vector::new(
+            theta.sin() * phi.cos(),
+            theta.sin() * phi.sin(),
+            theta.cos(),
+        )
+    }
+
+    /// Monte Carlo estimate of the albedo of `brdf` seen from `wo`, with the
+    /// largest difference between sampled and evaluated densities
+    pub fn albedo(brdf: &dyn Brdf, wo: Vector) -> (Vector, f64) {
+        let mut rng = StdRng::seed_from_u64(7);
+        let (mut sum, mut error) = (Vector::ZERO, 0f64);
+        let count = 20000;
+
+        for _ in 0..count {
+            let u = (rng.gen::<f64>(), rng.gen::<f64>());
+            if let Some(sample) = brdf.sample(NORMAL, wo, u) {
+                sum = sum + sample.weight;
+                let pdf = brdf.pdf(NORMAL, wo, sample.wi);
+                error = error.max((pdf - sample.pdf).abs() / pdf);
+            }
+        }
+
+        (sum / count as f64, error)
+    }
+
+    #[test]
+    fn orthonormal_frame() {
+        for n in [NORMAL, -NORMAL, Vector::new(1f64, 2f64, -3f64).normalize()] {
+            let (t, b) = frame(n);
+            for (v, w) in [(t, b), (t, n), (b, n)] {
+                assert!((v * w).abs() < 1e-12);
+            }
+            assert!((t.mag() - 1f64).abs() < 1e-12);
+            assert!((b.mag() - 1f64).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn cosine_hemisphere_stays_above() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let normal = Vector::new(-1f64, 1f64, 0f64).normalize();
+
+        let mean = (0..1000)
+            .map(|_| cosine_hemisphere(normal, (rng.gen(), rng.gen())))
+            .inspect(|wi| assert!(*wi * normal >= 0f64))
+            .fold(Vector::ZERO, |acc, wi| acc + wi)
+            / 1000f64;
+        // The mean cosine of the distribution is 2 / 3
+        assert!((mean * normal - 2f64 / 3f64).abs() < 0.02f64);
+    }
+}
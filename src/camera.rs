@@ -5,6 +5,7 @@ use crate::vector::Vector;
 /// Struct representing the camera
 pub struct Camera {
     pub center: Point,
+    #[allow(dead_code)]
     pub spotted_point: Point,
     pub up: Vector,
     pub right: Vector,
@@ -1,30 +1,108 @@
 //! Color representation
 
 use crate::Vector;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::ops::{Add, Div, Mul, Sub};
 
 /// Color representation in rgb
-#[derive(Debug, PartialOrd, PartialEq, Copy, Clone)]
+///
+/// Channels are stored as unbounded floats where 255 is white,
+/// `r`, `g` and `b` clamp and round them to 8 bits.
+/// Colors written with `Color::new` are sRGB encoded, `to_linear` must be
+/// called before using them in lighting math.
+/// Scene files write them as `[r, g, b]` arrays or `#rrggbb` strings.
+#[derive(Debug, PartialOrd, PartialEq, Copy, Clone, Serialize, Deserialize)]
+#[serde(try_from = "ColorDescription", into = "ColorDescription")]
 pub struct Color {
-    pub(crate) v: Vector,
+    v: Vector,
+}
+
+/// Forms of colors in scene files
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+enum ColorDescription {
+    Rgb([u8; 3]),
+    Hex(String),
+}
+
+impl TryFrom<ColorDescription> for Color {
+    type Error = String;
+
+    fn try_from(description: ColorDescription) -> Result<Self, Self::Error> {
+        match description {
+            ColorDescription::Rgb([r, g, b]) => Ok(Color::new(r, g, b)),
+            ColorDescription::Hex(hex) => Color::from_hex(&hex).map_err(|e| e.to_string()),
+        }
+    }
+}
+
+impl From<Color> for ColorDescription {
+    fn from(c: Color) -> Self {
+        let (r, g, b) = c.values();
+        ColorDescription::Rgb([r, g, b])
+    }
 }
 
 type ColorTuple = (u8, u8, u8);
 
+/// Transfer function applied to linear values when writing 8-bit output
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Encoding {
+    Srgb,
+    Linear,
+}
+
+impl Encoding {
+    pub fn encode(&self, c: Color) -> Color {
+        match self {
+            Encoding::Srgb => c.to_srgb(),
+            Encoding::Linear => c,
+        }
+    }
+
+    pub fn decode(&self, c: Color) -> Color {
+        match self {
+            Encoding::Srgb => c.to_linear(),
+            Encoding::Linear => c,
+        }
+    }
+}
+
+/// sRGB electro-optical transfer function, `c` normalized to `[0, 1]`
+pub fn srgb_to_linear(c: f64) -> f64 {
+    if c <= 0.04045f64 {
+        c / 12.92f64
+    } else {
+        ((c + 0.055f64) / 1.055f64).powf(2.4f64)
+    }
+}
+
+/// Inverse of `srgb_to_linear`, `c` normalized to `[0, 1]`
+pub fn linear_to_srgb(c: f64) -> f64 {
+    if c <= 0.0031308f64 {
+        c * 12.92f64
+    } else {
+        1.055f64 * c.powf(1f64 / 2.4f64) - 0.055f64
+    }
+}
+
 #[allow(dead_code)]
 impl Color {
     pub fn r(&self) -> u8 {
         let v = self.v.clamp(0f64, 255f64);
-        v.x as u8
+        v.x.round() as u8
     }
 
     pub fn g(&self) -> u8 {
         let v = self.v.clamp(0f64, 255f64);
-        v.y as u8
+        v.y.round() as u8
     }
 
     pub fn b(&self) -> u8 {
         let v = self.v.clamp(0f64, 255f64);
-        v.z as u8
+        v.z.round() as u8
     }
 
     pub const fn new(r: u8, g: u8, b: u8) -> Color {
@@ -43,6 +121,25 @@ impl Color {
         self.v
     }
 
+    /// Decodes an sRGB color to linear values
+    pub fn to_linear(self) -> Color {
+        Color {
+            v: (self.v / 255f64).map(|c| srgb_to_linear(c.max(0f64))) * 255f64,
+        }
+    }
+
+    /// Encodes a linear color to sRGB, values above white are kept as is
+    pub fn to_srgb(self) -> Color {
+        Color {
+            v: (self.v / 255f64).map(|c| linear_to_srgb(c.max(0f64))) * 255f64,
+        }
+    }
+
+    /// Rec. 709 weighted sum of the channels, white being `1`
+    pub fn luminance(&self) -> f64 {
+        (0.2126f64 * self.v.x + 0.7152f64 * self.v.y + 0.0722f64 * self.v.z) / 255f64
+    }
+
     pub fn values(&self) -> ColorTuple {
         (self.r(), self.g(), self.b())
     }
@@ -51,13 +148,145 @@ impl Color {
         let (r, g, b) = self.values();
         (r as u32) << 24 | (g as u32) << 16 | (b as u32) << 8
     }
+
+    /// Parses `#rrggbb`, the `#` being optional
+    pub fn from_hex(hex: &str) -> Result<Color, Box<dyn Error>> {
+        let digits = hex.strip_prefix('#').unwrap_or(hex);
+        if digits.len() != 6 || !digits.is_ascii() {
+            return Err(format!("`{}`: colors are written #rrggbb", hex).into());
+        }
+
+        let channel = |i: usize| u8::from_str_radix(&digits[i..i + 2], 16);
+        Ok(Color::new(channel(0)?, channel(2)?, channel(4)?))
+    }
+
+    /// `#rrggbb` form of the 8-bit channels
+    pub fn to_hex(self) -> String {
+        let (r, g, b) = self.values();
+        format!("#{:02x}{:02x}{:02x}", r, g, b)
+    }
+
+    /// Color of `hue` in degrees, `saturation` and `value` in `[0, 1]`
+    pub fn from_hsv(hue: f64, saturation: f64, value: f64) -> Color {
+        let chroma = value * saturation;
+        Color::from_chroma(hue, chroma, value - chroma)
+    }
+
+    /// Color of `hue` in degrees, `saturation` and `lightness` in `[0, 1]`
+    pub fn from_hsl(hue: f64, saturation: f64, lightness: f64) -> Color {
+        let chroma = (1f64 - (2f64 * lightness - 1f64).abs()) * saturation;
+        Color::from_chroma(hue, chroma, lightness - chroma / 2f64)
+    }
+
+    /// Hue in degrees, saturation and value, gray having a hue of `0`
+    pub fn to_hsv(self) -> (f64, f64, f64) {
+        let (max, chroma) = (self.max_channel(), self.chroma());
+        let saturation = if max > 0f64 { chroma / max } else { 0f64 };
+        (self.hue(), saturation, max)
+    }
+
+    /// Hue in degrees, saturation and lightness, gray having a hue of `0`
+    pub fn to_hsl(self) -> (f64, f64, f64) {
+        let chroma = self.chroma();
+        let lightness = self.max_channel() - chroma / 2f64;
+        let divisor = 1f64 - (2f64 * lightness - 1f64).abs();
+        let saturation = if divisor > 0f64 {
+            chroma / divisor
+        } else {
+            0f64
+        };
+        (self.hue(), saturation, lightness)
+    }
+
+    /// Color of `hue` whose channels span `chroma` above `min`, all in
+    /// `[0, 1]`
+    fn from_chroma(hue: f64, chroma: f64, min: f64) -> Color {
+        let h = hue.rem_euclid(360f64) / 60f64;
+        let x = chroma * (1f64 - (h % 2f64 - 1f64).abs());
+        let (r, g, b) = match h as u32 {
+            0 => (chroma, x, 0f64),
+            1 => (x, chroma, 0f64),
+            2 => (0f64, chroma, x),
+            3 => (0f64, x, chroma),
+            4 => (x, 0f64, chroma),
+            _ => (chroma, 0f64, x),
+        };
+        Color::from((Vector::new(r, g, b) + min) * 255f64)
+    }
+
+    fn max_channel(&self) -> f64 {
+        self.v.max() / 255f64
+    }
+
+    fn chroma(&self) -> f64 {
+        (self.v.max() - self.v.min()) / 255f64
+    }
+
+    fn hue(&self) -> f64 {
+        let chroma = self.chroma() * 255f64;
+        if chroma <= 0f64 {
+            return 0f64;
+        }
+
+        let Vector { x: r, y: g, z: b } = self.v;
+        let sector = if r >= g && r >= b {
+            ((g - b) / chroma).rem_euclid(6f64)
+        } else if g >= b {
+            (b - r) / chroma + 2f64
+        } else {
+            (r - g) / chroma + 4f64
+        };
+        sector * 60f64
+    }
+}
+
+impl Add for Color {
+    type Output = Color;
+
+    fn add(self, rhs: Color) -> Color {
+        Color::from(self.v + rhs.v)
+    }
+}
+
+impl Sub for Color {
+    type Output = Color;
+
+    fn sub(self, rhs: Color) -> Color {
+        Color::from(self.v - rhs.v)
+    }
+}
+
+impl Mul<f64> for Color {
+    type Output = Color;
+
+    fn mul(self, rhs: f64) -> Color {
+        Color::from(self.v * rhs)
+    }
+}
+
+/// Filters `self` by `rhs`, white leaving it unchanged
+impl Mul for Color {
+    type Output = Color;
+
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn mul(self, rhs: Color) -> Color {
+        // Elementwise, `Vector * Vector` being the dot product
+        Color::from(Vector::mul(&self.v, rhs.v) / 255f64)
+    }
+}
+
+impl Div<f64> for Color {
+    type Output = Color;
+
+    fn div(self, rhs: f64) -> Color {
+        Color::from(self.v / rhs)
+    }
 }
 
 impl From<Vector> for Color {
+    /// Keeps `v` unclamped, values above 255 are highlights
     fn from(v: Vector) -> Self {
-        Color {
-            v: v.clamp(0f64, 255f64),
-        }
+        Color { v }
     }
 }
 
@@ -72,8 +301,85 @@ mod tests {
         assert_eq!(Color::BLUE.values(), (0, 0, 255));
     }
 
+    #[test]
+    fn srgb_round_trip() {
+        for i in 0..=255 {
+            let c = Color::new(i, i, i);
+            assert_eq!(c.to_linear().to_srgb().values(), (i, i, i));
+        }
+    }
+
+    #[test]
+    fn srgb_mid_gray() {
+        let c = Color::new(188, 128, 0).to_linear();
+        assert_eq!(c.values(), (128, 55, 0));
+    }
+
     #[test]
     fn to_value() {
         assert_eq!(0xFF000000, Color::RED.value())
     }
+
+    #[test]
+    fn hex() {
+        assert_eq!(Color::from_hex("#ff8000").unwrap(), Color::new(255, 128, 0));
+        assert_eq!(Color::from_hex("0A0b0C").unwrap(), Color::new(10, 11, 12));
+        assert_eq!(Color::new(3, 128, 250).to_hex(), "#0380fa");
+        for bad in ["#fff", "#gg0000", "#ff00001", "#ff00é"] {
+            assert!(Color::from_hex(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn serde() {
+        let colors: Vec<Color> = serde_json::from_str(r##"[[255, 0, 0], "#00ff00"]"##).unwrap();
+        assert_eq!(colors, vec![Color::RED, Color::GREEN]);
+        assert_eq!(serde_json::to_string(&Color::BLUE).unwrap(), "[0,0,255]");
+        assert!(serde_json::from_str::<Color>(r#""red""#).is_err());
+    }
+
+    #[test]
+    fn hsv() {
+        assert_eq!(Color::from_hsv(0f64, 1f64, 1f64), Color::RED);
+        assert_eq!(Color::from_hsv(240f64, 1f64, 1f64), Color::BLUE);
+        assert_eq!(
+            Color::from_hsv(-240f64, 0.5f64, 1f64).values(),
+            (128, 255, 128)
+        );
+        assert_eq!(Color::from_hsv(77f64, 0f64, 0.2f64).values(), (51, 51, 51));
+
+        let c = Color::new(30, 200, 120);
+        let (h, s, v) = c.to_hsv();
+        assert!((v - 200f64 / 255f64).abs() < 1e-12);
+        assert_eq!(Color::from_hsv(h, s, v).values(), c.values());
+        assert_eq!(Color::WHITE.to_hsv(), (0f64, 0f64, 1f64));
+    }
+
+    #[test]
+    fn hsl() {
+        assert_eq!(Color::from_hsl(120f64, 1f64, 0.5f64), Color::GREEN);
+        assert_eq!(Color::from_hsl(0f64, 1f64, 1f64), Color::WHITE);
+        assert_eq!(
+            Color::from_hsl(0f64, 1f64, 0.75f64).values(),
+            (255, 128, 128)
+        );
+
+        let c = Color::new(250, 20, 90);
+        let (h, s, l) = c.to_hsl();
+        assert_eq!(Color::from_hsl(h, s, l).values(), c.values());
+        assert_eq!(Color::BLACK.to_hsl(), (0f64, 0f64, 0f64));
+    }
+
+    #[test]
+    fn operators() {
+        let c = Color::new(100, 50, 10);
+
+        assert_eq!(c + c, Color::new(200, 100, 20));
+        assert_eq!((c - c), Color::BLACK);
+        assert_eq!(c * 2f64, c + c);
+        assert_eq!(c / 2f64, Color::new(50, 25, 5));
+        // White filters nothing out, red keeps the red channel
+        assert_eq!(c * Color::WHITE, c);
+        assert_eq!(c * Color::RED, Color::new(100, 0, 0));
+    }
 }
@@ -0,0 +1,193 @@
+//! Materials of scene files
+use crate::color::Color;
+use crate::description::texture::TextureDescription;
+use crate::material::{Bump, Material, Model, Parameter};
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+
+fn white() -> Color {
+    Color::WHITE
+}
+
+fn black() -> Color {
+    Color::BLACK
+}
+
+fn shininess() -> f64 {
+    50f64
+}
+
+fn half() -> ParameterDescription {
+    ParameterDescription::Value(0.5f64)
+}
+
+/// Constant or textured scalar
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ParameterDescription {
+    Value(f64),
+    Texture(Box<TextureDescription>),
+}
+
+impl Default for ParameterDescription {
+    fn default() -> Self {
+        ParameterDescription::Value(0f64)
+    }
+}
+
+impl ParameterDescription {
+    fn build(&self) -> Result<Parameter, Box<dyn Error>> {
+        Ok(match self {
+            ParameterDescription::Value(v) => Parameter::Value(*v),
+            ParameterDescription::Texture(t) => Parameter::Texture(t.build()?),
+        })
+    }
+}
+
+/// Reflectance model tagged by `type` in JSON
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ModelDescription {
+    #[default]
+    Phong,
+    BlinnPhong,
+    CookTorrance {
+        #[serde(default = "half")]
+        roughness: ParameterDescription,
+        #[serde(default)]
+        metallic: ParameterDescription,
+    },
+}
+
+impl ModelDescription {
+    fn build(&self) -> Result<Model, Box<dyn Error>> {
+        Ok(match self {
+            ModelDescription::Phong => Model::Phong,
+            ModelDescription::BlinnPhong => Model::BlinnPhong,
+            ModelDescription::CookTorrance {
+                roughness,
+                metallic,
+            } => Model::CookTorrance {
+                roughness: roughness.build()?,
+                metallic: metallic.build()?,
+            },
+        })
+    }
+}
+
+fn one() -> f64 {
+    1f64
+}
+
+/// Normal perturbation tagged by `type` in JSON
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BumpDescription {
+    Height {
+        texture: TextureDescription,
+        #[serde(default = "one")]
+        strength: f64,
+    },
+    NormalMap {
+        texture: TextureDescription,
+    },
+}
+
+impl BumpDescription {
+    pub fn build(&self) -> Result<Bump, Box<dyn Error>> {
+        Ok(match self {
+            BumpDescription::Height { texture, strength } => Bump::Height {
+                texture: texture.build()?,
+                strength: *strength,
+            },
+            BumpDescription::NormalMap { texture } => Bump::NormalMap(texture.build()?),
+        })
+    }
+}
+
+/// Material of the library of a scene file, missing fields take the values
+/// of `Material::new`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MaterialDescription {
+    pub texture: TextureDescription,
+    #[serde(default = "white")]
+    pub specular: Color,
+    #[serde(default = "shininess")]
+    pub shininess: f64,
+    #[serde(default)]
+    pub reflectivity: f64,
+    #[serde(default)]
+    pub transparency: f64,
+    #[serde(default = "black")]
+    pub emission: Color,
+    #[serde(default)]
+    pub model: ModelDescription,
+    #[serde(default)]
+    pub bump: Option<BumpDescription>,
+}
+
+impl MaterialDescription {
+    pub fn build(&self) -> Result<Material, Box<dyn Error>> {
+        Ok(Material {
+            texture: self.texture.build()?,
+            specular: self.specular,
+            shininess: self.shininess,
+            reflectivity: self.reflectivity,
+            transparency: self.transparency,
+            emission: self.emission,
+            model: self.model.build()?,
+            bump: self.bump.as_ref().map(|b| b.build()).transpose()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::color::Color;
+    use crate::description::material::{
+        MaterialDescription, ModelDescription, ParameterDescription,
+    };
+    use crate::description::texture::TextureDescription;
+
+    #[test]
+    fn defaults() {
+        let json = r#"{ "texture": { "type": "uniform", "color": [255, 0, 0] } }"#;
+        let material = serde_json::from_str::<MaterialDescription>(json)
+            .unwrap()
+            .build()
+            .unwrap();
+
+        assert_eq!(material.specular, Color::WHITE);
+        assert_eq!(material.shininess, 50f64);
+        assert_eq!(material.reflectivity, 0f64);
+        assert_eq!(material.transparency, 0f64);
+        assert_eq!(material.emission, Color::BLACK);
+    }
+
+    #[test]
+    fn model() {
+        let json = r#"{
+            "texture": { "type": "uniform", "color": [255, 200, 50] },
+            "model": {
+                "type": "cook_torrance",
+                "roughness": { "type": "uniform", "color": [128, 128, 128] },
+                "metallic": 1
+            }
+        }"#;
+        let material = serde_json::from_str::<MaterialDescription>(json).unwrap();
+
+        let ModelDescription::CookTorrance {
+            roughness,
+            metallic,
+        } = &material.model
+        else {
+            panic!("not Cook-Torrance: {:?}", material.model);
+        };
+        let ParameterDescription::Texture(roughness) = roughness else {
+            panic!("not textured: {:?}", roughness);
+        };
+        assert!(matches!(**roughness, TextureDescription::Uniform { .. }));
+        assert_eq!(*metallic, ParameterDescription::Value(1f64));
+        assert!(material.build().is_ok());
+    }
+}
@@ -0,0 +1,444 @@
+//! Scenes read from JSON files
+//!
+//! Materials are declared once in a named library, objects refer to them by
+//! name and share them. Points and vectors are `[x, y, z]` arrays, colors are
+//! sRGB `[r, g, b]` arrays.
+pub mod material;
+pub mod texture;
+
+use crate::camera::Camera;
+use crate::description::material::{BumpDescription, MaterialDescription};
+use crate::light::point::PointLight;
+use crate::light::LightTrait;
+use crate::material::Material;
+use crate::object::csg::{Csg, Operation};
+use crate::object::culled::Culled;
+use crate::object::menger::Menger;
+use crate::object::plane::Plane;
+use crate::object::rect::Rectangle;
+use crate::object::sphere::Sphere;
+use crate::object::triangle::Triangle;
+use crate::object::turtle::Turtle;
+use crate::object::{ObjectTrait, Solid};
+use crate::scene::Scene;
+use crate::uv::Uv;
+use crate::vector::Vector;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::fs::File;
+use std::io::BufReader;
+use std::rc::Rc;
+
+pub(crate) fn vector([x, y, z]: [f64; 3]) -> Vector {
+    Vector::new(x, y, z)
+}
+
+fn y_axis() -> [f64; 3] {
+    [0f64, 1f64, 0f64]
+}
+
+fn fov() -> f64 {
+    90f64
+}
+
+fn white_light() -> [f64; 3] {
+    [255f64, 255f64, 255f64]
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CameraDescription {
+    pub center: [f64; 3],
+    /// Point looked at
+    pub target: [f64; 3],
+    #[serde(default = "y_axis")]
+    pub up: [f64; 3],
+    /// Horizontal field of view in degrees
+    #[serde(default = "fov")]
+    pub fov: f64,
+}
+
+/// Light tagged by `type` in JSON
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LightDescription {
+    Point {
+        point: [f64; 3],
+        #[serde(default = "white_light")]
+        intensity: [f64; 3],
+    },
+}
+
+/// Shape of an object tagged by `type` in JSON
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ShapeDescription {
+    Sphere {
+        center: [f64; 3],
+        radius: f64,
+    },
+    Plane {
+        point: [f64; 3],
+        normal: [f64; 3],
+    },
+    Triangle {
+        points: [[f64; 3]; 3],
+        /// Texture coordinates of the points, defaulting to `Triangle::new`
+        #[serde(default)]
+        uvs: Option<[[f64; 2]; 3]>,
+    },
+    Rectangle {
+        min: [f64; 3],
+        max: [f64; 3],
+    },
+    Menger {
+        depth: usize,
+        min: [f64; 3],
+        max: [f64; 3],
+    },
+    Turtle {
+        /// Path of the grammar file
+        grammar: String,
+        /// Material of the polygons, plain green when missing
+        #[serde(default)]
+        leaf: Option<String>,
+        /// Normal perturbation of the branches, smooth when missing
+        #[serde(default)]
+        bump: Option<BumpDescription>,
+    },
+    /// Spheres, rectangles, menger sponges or csg nodes combined by
+    /// `operation`, inheriting the material of the node when they have none
+    Csg {
+        operation: Operation,
+        left: Box<ObjectDescription>,
+        right: Box<ObjectDescription>,
+    },
+}
+
+/// Object of a scene file, materials naming entries of the material library
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ObjectDescription {
+    #[serde(flatten)]
+    pub shape: ShapeDescription,
+    /// Required but for csg nodes
+    #[serde(default)]
+    pub material: Option<String>,
+    /// Material of the back of thin surfaces: planes, triangles and the
+    /// leaves of turtles
+    #[serde(default)]
+    pub back: Option<String>,
+    /// Lets rays hitting the back of the surface through
+    #[serde(default)]
+    pub cull: bool,
+    #[serde(default)]
+    pub id: String,
+}
+
+fn find(materials: &HashMap<String, Rc<Material>>, name: &str) -> Result<Rc<Material>, String> {
+    materials
+        .get(name)
+        .cloned()
+        .ok_or_else(|| format!("unknown material `{}`", name))
+}
+
+impl ObjectDescription {
+    /// Material named by the object, or else by the csg node holding it
+    fn material(
+        &self,
+        materials: &HashMap<String, Rc<Material>>,
+        inherited: Option<&str>,
+    ) -> Result<Rc<Material>, Box<dyn Error>> {
+        let name = self
+            .material
+            .as_deref()
+            .or(inherited)
+            .ok_or_else(|| format!("`{}`: missing material", self.id))?;
+        Ok(find(materials, name)?)
+    }
+
+    /// Builds the object, sharing its materials from `materials`
+    pub fn build(
+        &self,
+        materials: &HashMap<String, Rc<Material>>,
+    ) -> Result<Box<dyn ObjectTrait>, Box<dyn Error>> {
+        let back = self
+            .back
+            .as_deref()
+            .map(|name| find(materials, name))
+            .transpose()?;
+        let id = self.id.clone();
+
+        let object: Box<dyn ObjectTrait> = match &self.shape {
+            ShapeDescription::Plane { point, normal } => Box::new(Plane {
+                p: vector(*point),
+                normal: vector(*normal),
+                material: self.material(materials, None)?,
+                back,
+                id,
+            }),
+            ShapeDescription::Triangle {
+                points: [p1, p2, p3],
+                uvs,
+            } => {
+                let material = self.material(materials, None)?;
+                let triangle = Triangle::new(vector(*p1), vector(*p2), vector(*p3), material, id)
+                    .with_back(back);
+                match uvs {
+                    Some(uvs) => Box::new(triangle.with_uvs(uvs.map(|[u, v]| Uv::new(u, v)))),
+                    None => Box::new(triangle),
+                }
+            }
+            ShapeDescription::Turtle {
+                grammar,
+                leaf,
+                bump,
+            } => Box::new(Turtle::new(
+                grammar.clone(),
+                self.material(materials, None)?,
+                leaf.as_deref()
+                    .map(|name| find(materials, name))
+                    .transpose()?,
+                back,
+                bump.as_ref().map(|b| b.build()).transpose()?,
+                id,
+            )?),
+            _ if back.is_some() => {
+                return Err(format!("`{}`: only thin surfaces have a back material", id).into())
+            }
+            _ => self.solid(materials, None)?,
+        };
+
+        if self.cull {
+            return Ok(Box::new(Culled { object }));
+        }
+        Ok(object)
+    }
+
+    /// Builds a closed object, for csg nodes to combine
+    fn solid(
+        &self,
+        materials: &HashMap<String, Rc<Material>>,
+        inherited: Option<&str>,
+    ) -> Result<Box<dyn Solid>, Box<dyn Error>> {
+        let id = self.id.clone();
+
+        Ok(match &self.shape {
+            ShapeDescription::Sphere { center, radius } => Box::new(Sphere {
+                p: vector(*center),
+                r: *radius,
+                material: self.material(materials, inherited)?,
+                id,
+            }),
+            ShapeDescription::Rectangle { min, max } => Box::new(Rectangle::new(
+                vector(*min),
+                vector(*max),
+                self.material(materials, inherited)?,
+                id,
+            )),
+            ShapeDescription::Menger { depth, min, max } => Box::new(Menger::new(
+                *depth,
+                vector(*min),
+                vector(*max),
+                self.material(materials, inherited)?,
+                id,
+            )),
+            ShapeDescription::Csg {
+                operation,
+                left,
+                right,
+            } => {
+                if [left, right].iter().any(|s| s.back.is_some() || s.cull) {
+                    return Err(
+                        format!("`{}`: solids of csg nodes have no back nor culling", id).into(),
+                    );
+                }
+
+                let inherited = self.material.as_deref().or(inherited);
+                Box::new(Csg::new(
+                    *operation,
+                    left.solid(materials, inherited)?,
+                    right.solid(materials, inherited)?,
+                    id,
+                ))
+            }
+            _ => return Err(format!("`{}`: csg nodes only combine solids", id).into()),
+        })
+    }
+}
+
+/// Camera, lights, material library and objects of a scene
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SceneDescription {
+    pub camera: CameraDescription,
+    #[serde(default)]
+    pub lights: Vec<LightDescription>,
+    #[serde(default)]
+    pub materials: BTreeMap<String, MaterialDescription>,
+    #[serde(default)]
+    pub objects: Vec<ObjectDescription>,
+}
+
+impl SceneDescription {
+    pub fn from_file(path: &str) -> Result<SceneDescription, Box<dyn Error>> {
+        let reader = BufReader::new(File::open(path)?);
+        Ok(serde_json::from_reader(reader)?)
+    }
+
+    /// Builds the scene, every material of the library being built once
+    pub fn build(&self) -> Result<Scene, Box<dyn Error>> {
+        let mut materials = HashMap::new();
+        for (name, material) in &self.materials {
+            materials.insert(name.clone(), Rc::new(material.build()?));
+        }
+
+        let objects = self
+            .objects
+            .iter()
+            .map(|o| o.build(&materials))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let lights = self
+            .lights
+            .iter()
+            .map(|l| match l {
+                LightDescription::Point { point, intensity } => Box::new(PointLight {
+                    point: vector(*point),
+                    intensity: vector(*intensity),
+                })
+                    as Box<dyn LightTrait>,
+            })
+            .collect();
+
+        let camera = &self.camera;
+        Ok(Scene {
+            cam: Camera::new(
+                vector(camera.center),
+                vector(camera.target),
+                vector(camera.up),
+                camera.fov.to_radians(),
+            ),
+            lights,
+            objects,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::description::SceneDescription;
+    use crate::{Point, Vector};
+
+    const SCENE: &str = r#"{
+        "camera": { "center": [0, 0, 0], "target": [0, 0, 1] },
+        "lights": [{ "type": "point", "point": [0, 5, 0] }],
+        "materials": {
+            "mirror": {
+                "texture": { "type": "uniform", "color": [255, 255, 255] },
+                "shininess": 200,
+                "reflectivity": 0.8
+            }
+        },
+        "objects": [
+            { "type": "sphere", "center": [0, 0, 3], "radius": 1, "material": "mirror" },
+            { "type": "plane", "point": [0, -1, 0], "normal": [0, 1, 0], "material": "mirror" }
+        ]
+    }"#;
+
+    #[test]
+    fn build() {
+        let description: SceneDescription = serde_json::from_str(SCENE).unwrap();
+        let scene = description.build().unwrap();
+
+        assert_eq!(scene.lights.len(), 1);
+        assert_eq!(scene.objects.len(), 2);
+        assert_eq!(scene.cam.alpha, std::f64::consts::FRAC_PI_2);
+
+        // Both objects share the material of the library
+        let (sphere, plane) = (scene.objects[0].material(), scene.objects[1].material());
+        assert!(std::ptr::eq(sphere, plane));
+        assert_eq!(sphere.shininess, 200f64);
+        assert_eq!(sphere.reflectivity, 0.8f64);
+    }
+
+    #[test]
+    fn sides() {
+        let json = SCENE.replace(
+            r#""normal": [0, 1, 0], "material": "mirror" }"#,
+            r#""normal": [0, 1, 0], "material": "mirror", "back": "mirror", "cull": true }"#,
+        );
+        let description: SceneDescription = serde_json::from_str(&json).unwrap();
+        assert!(description.objects[1].cull);
+
+        let scene = description.build().unwrap();
+        let plane = &scene.objects[1];
+        // Culled from below
+        assert!(plane
+            .intersect_points(Point::ZERO, Vector::new(0f64, 1f64, 0f64))
+            .is_empty());
+        assert!(std::ptr::eq(plane.material(), plane.back_material()));
+
+        // Spheres have no back
+        let json = SCENE.replace(
+            r#""radius": 1, "material": "mirror" }"#,
+            r#""radius": 1, "material": "mirror", "back": "mirror" }"#,
+        );
+        let description: SceneDescription = serde_json::from_str(&json).unwrap();
+        assert!(description.build().is_err());
+    }
+
+    #[test]
+    fn csg() {
+        let json = SCENE.replace(
+            r#"{ "type": "sphere", "center": [0, 0, 3], "radius": 1, "material": "mirror" }"#,
+            r#"{
+                "type": "csg",
+                "operation": "difference",
+                "material": "mirror",
+                "left": { "type": "menger", "depth": 1, "min": [-1, -1, 2], "max": [1, 1, 4] },
+                "right": {
+                    "type": "sphere", "center": [1, 1, 2], "radius": 1, "material": "mirror"
+                },
+                "id": "carved"
+            }"#,
+        );
+        let description: SceneDescription = serde_json::from_str(&json).unwrap();
+        let scene = description.build().unwrap();
+        let csg = &scene.objects[0];
+        assert_eq!(csg.id(), "carved");
+
+        // Straight into the front face, next to the carved corner
+        let hits = csg.intersect_points(
+            Point::new(-0.5f64, -0.5f64, 0f64),
+            Vector::new(0f64, 0f64, 1f64),
+        );
+        assert_eq!(hits[0], Point::new(-0.5f64, -0.5f64, 2f64));
+
+        // Only solids can be combined
+        let json = json.replace(
+            r#"{ "type": "menger", "depth": 1, "min": [-1, -1, 2], "max": [1, 1, 4] }"#,
+            r#"{ "type": "plane", "point": [0, 0, 0], "normal": [0, 1, 0] }"#,
+        );
+        let description: SceneDescription = serde_json::from_str(&json).unwrap();
+        assert!(description.build().is_err());
+    }
+
+    #[test]
+    fn unknown_material() {
+        let json = SCENE.replace(r#""material": "mirror" }"#, r#""material": "glass" }"#);
+        let description: SceneDescription = serde_json::from_str(&json).unwrap();
+
+        assert!(description.build().is_err());
+    }
+
+    #[test]
+    fn missing_grammar() {
+        let json = SCENE.replace(
+            r#"{ "type": "sphere", "center": [0, 0, 3], "radius": 1, "material": "mirror" }"#,
+            r#"{ "type": "turtle", "grammar": "missing.json", "material": "mirror" }"#,
+        );
+        let description: SceneDescription = serde_json::from_str(&json).unwrap();
+
+        let error = description.build().err().unwrap().to_string();
+        assert!(error.starts_with("`missing.json`: "), "{}", error);
+    }
+}
@@ -0,0 +1,650 @@
+//! Textures of scene files
+use crate::color::Color;
+use crate::description::vector;
+use crate::noise::perlin::Perlin;
+use crate::noise::worley::{DistanceMode, Metric, Worley};
+use crate::noise::Octaves;
+use crate::palette::Palette;
+use crate::texture::brick::BrickTexture;
+use crate::texture::checker::CheckerTexture;
+use crate::texture::cloud::CloudTexture;
+use crate::texture::expression::ExpressionTexture;
+use crate::texture::gradient::{GradientKind, GradientTexture};
+use crate::texture::image::{Filtering, ImageTexture};
+use crate::texture::level::LevelTexture;
+use crate::texture::marble::MarbleTexture;
+use crate::texture::mask::MaskTexture;
+use crate::texture::mix::MixTexture;
+use crate::texture::remap::RemapTexture;
+use crate::texture::stripes::StripesTexture;
+use crate::texture::transform::TransformTexture;
+use crate::texture::triplanar::TriplanarTexture;
+use crate::texture::uniform::UniformTexture;
+use crate::texture::wood::WoodTexture;
+use crate::texture::worley::WorleyTexture;
+use crate::texture::{Mapping, TextureTrait};
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+
+/// Diffuse, specular and ambient weights of a texture
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Coefficients {
+    pub kd: f64,
+    pub ks: f64,
+    pub ka: f64,
+}
+
+impl Default for Coefficients {
+    fn default() -> Self {
+        Coefficients {
+            kd: 1f64,
+            ks: 0.1f64,
+            ka: 0f64,
+        }
+    }
+}
+
+impl Coefficients {
+    fn values(&self) -> (f64, f64, f64) {
+        (self.kd, self.ks, self.ka)
+    }
+}
+
+fn one() -> f64 {
+    1f64
+}
+
+fn sharpness() -> f64 {
+    4f64
+}
+
+fn half() -> f64 {
+    0.5f64
+}
+
+fn x_axis() -> [f64; 3] {
+    [1f64, 0f64, 0f64]
+}
+
+fn z_axis() -> [f64; 3] {
+    [0f64, 0f64, 1f64]
+}
+
+fn ones() -> [f64; 3] {
+    [1f64, 1f64, 1f64]
+}
+
+/// Texture tagged by `type` in JSON, colors being declared in sRGB
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TextureDescription {
+    Uniform {
+        color: Color,
+        #[serde(flatten)]
+        coefficients: Coefficients,
+    },
+    Image {
+        path: String,
+        #[serde(default)]
+        filtering: Filtering,
+        #[serde(flatten)]
+        coefficients: Coefficients,
+    },
+    Checker {
+        even: Box<TextureDescription>,
+        odd: Box<TextureDescription>,
+        #[serde(default = "one")]
+        scale: f64,
+        #[serde(default)]
+        mapping: Mapping,
+    },
+    Stripes {
+        first: Box<TextureDescription>,
+        second: Box<TextureDescription>,
+        #[serde(default = "x_axis")]
+        axis: [f64; 3],
+        #[serde(default = "one")]
+        scale: f64,
+        #[serde(default)]
+        mapping: Mapping,
+    },
+    Marble {
+        base: Color,
+        vein: Color,
+        #[serde(default)]
+        seed: u64,
+        #[serde(default = "one")]
+        scale: f64,
+        #[serde(default = "one")]
+        distortion: f64,
+        #[serde(default)]
+        octaves: Octaves,
+        #[serde(default)]
+        mapping: Mapping,
+        #[serde(flatten)]
+        coefficients: Coefficients,
+    },
+    Wood {
+        light: Color,
+        dark: Color,
+        #[serde(default)]
+        seed: u64,
+        #[serde(default = "one")]
+        scale: f64,
+        #[serde(default = "one")]
+        distortion: f64,
+        #[serde(default)]
+        octaves: Octaves,
+        #[serde(default)]
+        mapping: Mapping,
+        #[serde(flatten)]
+        coefficients: Coefficients,
+    },
+    Cloud {
+        sky: Color,
+        cloud: Color,
+        #[serde(default)]
+        seed: u64,
+        #[serde(default = "one")]
+        scale: f64,
+        cover: f64,
+        #[serde(default)]
+        octaves: Octaves,
+        #[serde(default)]
+        mapping: Mapping,
+        #[serde(flatten)]
+        coefficients: Coefficients,
+    },
+    Triplanar {
+        texture: Box<TextureDescription>,
+        #[serde(default = "one")]
+        scale: f64,
+        #[serde(default = "sharpness")]
+        sharpness: f64,
+    },
+    /// Value of a math expression of the point, uv and normal, see
+    /// `crate::expression`
+    Expression {
+        expression: String,
+        /// `[position, color]` stops, grays from black at `0` to white at `1`
+        /// when missing
+        #[serde(default)]
+        ramp: Option<Vec<(f64, Color)>>,
+        /// Seed of the noise functions
+        #[serde(default)]
+        seed: u64,
+        #[serde(flatten)]
+        coefficients: Coefficients,
+    },
+    Gradient {
+        start: Box<TextureDescription>,
+        end: Box<TextureDescription>,
+        #[serde(default)]
+        kind: GradientKind,
+        #[serde(default)]
+        origin: [f64; 3],
+        /// Direction of the gradient, its length being the distance `end` is
+        /// reached at
+        #[serde(default = "x_axis")]
+        axis: [f64; 3],
+        #[serde(default)]
+        mapping: Mapping,
+    },
+    Brick {
+        brick: Box<TextureDescription>,
+        mortar: Box<TextureDescription>,
+        /// Size of a brick with its mortar
+        size: [f64; 3],
+        mortar_width: f64,
+        /// Shift of every other row in brick lengths, `0` laying tiles
+        #[serde(default = "half")]
+        offset: f64,
+        #[serde(default)]
+        mapping: Mapping,
+    },
+    /// Cells colored from `colors`, or from 360 hues of `palette` when
+    /// missing
+    Worley {
+        #[serde(default)]
+        seed: u64,
+        #[serde(default)]
+        metric: Metric,
+        #[serde(default)]
+        mode: DistanceMode,
+        /// Cells per unit
+        #[serde(default = "one")]
+        density: f64,
+        #[serde(default)]
+        contrast: f64,
+        #[serde(default)]
+        palette: Palette,
+        #[serde(default)]
+        colors: Option<Vec<Color>>,
+        #[serde(default)]
+        mapping: Mapping,
+        #[serde(flatten)]
+        coefficients: Coefficients,
+    },
+    /// Colors picked by the subdivision level of menger sponges and turtles
+    Level {
+        colors: Vec<Color>,
+        #[serde(flatten)]
+        coefficients: Coefficients,
+    },
+    Mix {
+        a: Box<TextureDescription>,
+        b: Box<TextureDescription>,
+        factor: Box<TextureDescription>,
+    },
+    Mask {
+        inside: Box<TextureDescription>,
+        outside: Box<TextureDescription>,
+        mask: Box<TextureDescription>,
+        #[serde(default = "half")]
+        threshold: f64,
+    },
+    Remap {
+        texture: Box<TextureDescription>,
+        /// `[position, color]` stops
+        ramp: Vec<(f64, Color)>,
+    },
+    Transform {
+        texture: Box<TextureDescription>,
+        #[serde(default = "ones")]
+        scale: [f64; 3],
+        #[serde(default = "z_axis")]
+        axis: [f64; 3],
+        /// Rotation around `axis` in degrees
+        #[serde(default)]
+        angle: f64,
+        #[serde(default)]
+        translation: [f64; 3],
+    },
+}
+
+impl TextureDescription {
+    /// Builds the texture, loading image files
+    pub fn build(&self) -> Result<Box<dyn TextureTrait>, Box<dyn Error>> {
+        Ok(match self {
+            TextureDescription::Uniform {
+                color: c,
+                coefficients,
+            } => Box::new(UniformTexture {
+                kd: coefficients.kd,
+                ks: coefficients.ks,
+                ka: coefficients.ka,
+                color: *c,
+            }),
+            TextureDescription::Image {
+                path,
+                filtering,
+                coefficients,
+            } => Box::new(ImageTexture::load(path, *filtering, coefficients.values())?),
+            TextureDescription::Checker {
+                even,
+                odd,
+                scale,
+                mapping,
+            } => Box::new(CheckerTexture {
+                even: even.build()?,
+                odd: odd.build()?,
+                scale: *scale,
+                mapping: *mapping,
+            }),
+            TextureDescription::Stripes {
+                first,
+                second,
+                axis,
+                scale,
+                mapping,
+            } => Box::new(StripesTexture {
+                first: first.build()?,
+                second: second.build()?,
+                axis: vector(*axis),
+                scale: *scale,
+                mapping: *mapping,
+            }),
+            TextureDescription::Marble {
+                base,
+                vein,
+                seed,
+                scale,
+                distortion,
+                octaves,
+                mapping,
+                coefficients,
+            } => Box::new(MarbleTexture {
+                kd: coefficients.kd,
+                ks: coefficients.ks,
+                ka: coefficients.ka,
+                base: *base,
+                vein: *vein,
+                noise: Perlin::new(*seed),
+                scale: *scale,
+                distortion: *distortion,
+                octaves: *octaves,
+                mapping: *mapping,
+            }),
+            TextureDescription::Wood {
+                light,
+                dark,
+                seed,
+                scale,
+                distortion,
+                octaves,
+                mapping,
+                coefficients,
+            } => Box::new(WoodTexture {
+                kd: coefficients.kd,
+                ks: coefficients.ks,
+                ka: coefficients.ka,
+                light: *light,
+                dark: *dark,
+                noise: Perlin::new(*seed),
+                scale: *scale,
+                distortion: *distortion,
+                octaves: *octaves,
+                mapping: *mapping,
+            }),
+            TextureDescription::Cloud {
+                sky,
+                cloud,
+                seed,
+                scale,
+                cover,
+                octaves,
+                mapping,
+                coefficients,
+            } => Box::new(CloudTexture {
+                kd: coefficients.kd,
+                ks: coefficients.ks,
+                ka: coefficients.ka,
+                sky: *sky,
+                cloud: *cloud,
+                noise: Perlin::new(*seed),
+                scale: *scale,
+                cover: *cover,
+                octaves: *octaves,
+                mapping: *mapping,
+            }),
+            TextureDescription::Triplanar {
+                texture,
+                scale,
+                sharpness,
+            } => Box::new(TriplanarTexture {
+                texture: texture.build()?,
+                scale: *scale,
+                sharpness: *sharpness,
+            }),
+            TextureDescription::Expression {
+                expression,
+                ramp,
+                seed,
+                coefficients,
+            } => Box::new(ExpressionTexture::new(
+                expression,
+                *seed,
+                ramp.clone(),
+                coefficients.values(),
+            )?),
+            TextureDescription::Gradient {
+                start,
+                end,
+                kind,
+                origin,
+                axis,
+                mapping,
+            } => Box::new(GradientTexture {
+                start: start.build()?,
+                end: end.build()?,
+                kind: *kind,
+                origin: vector(*origin),
+                axis: vector(*axis),
+                mapping: *mapping,
+            }),
+            TextureDescription::Brick {
+                brick,
+                mortar,
+                size,
+                mortar_width,
+                offset,
+                mapping,
+            } => Box::new(BrickTexture {
+                brick: brick.build()?,
+                mortar: mortar.build()?,
+                size: vector(*size),
+                mortar_width: *mortar_width,
+                offset: *offset,
+                mapping: *mapping,
+            }),
+            TextureDescription::Worley {
+                seed,
+                metric,
+                mode,
+                density,
+                contrast,
+                palette,
+                colors,
+                mapping,
+                coefficients,
+            } => {
+                let colors = colors.clone().unwrap_or_else(|| palette.colors(360));
+                let mut texture = WorleyTexture::new(
+                    Worley::new(*seed, *metric),
+                    *density,
+                    coefficients.values(),
+                )
+                .with_palette(colors)?;
+                texture.mode = *mode;
+                texture.contrast = *contrast;
+                texture.mapping = *mapping;
+                Box::new(texture)
+            }
+            TextureDescription::Level {
+                colors,
+                coefficients,
+            } => Box::new(LevelTexture::new(colors.clone(), coefficients.values())?),
+            TextureDescription::Mix { a, b, factor } => Box::new(MixTexture {
+                a: a.build()?,
+                b: b.build()?,
+                factor: factor.build()?,
+            }),
+            TextureDescription::Mask {
+                inside,
+                outside,
+                mask,
+                threshold,
+            } => Box::new(MaskTexture {
+                inside: inside.build()?,
+                outside: outside.build()?,
+                mask: mask.build()?,
+                threshold: *threshold,
+            }),
+            TextureDescription::Remap { texture, ramp } => {
+                Box::new(RemapTexture::new(texture.build()?, ramp.clone())?)
+            }
+            TextureDescription::Transform {
+                texture,
+                scale,
+                axis,
+                angle,
+                translation,
+            } => Box::new(TransformTexture {
+                scale: vector(*scale),
+                axis: vector(*axis),
+                angle: angle.to_radians(),
+                translation: vector(*translation),
+                ..TransformTexture::new(texture.build()?)
+            }),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::color::Color;
+    use crate::description::texture::{Coefficients, TextureDescription};
+    use crate::noise::worley::{DistanceMode, Metric};
+    use crate::palette::Palette;
+    use crate::point::Point;
+    use crate::texture::{Mapping, ShadingContext};
+
+    #[test]
+    fn parse() {
+        let json = r##"{
+            "type": "checker",
+            "even": { "type": "uniform", "color": [255, 0, 0], "ka": 0.5 },
+            "odd": { "type": "uniform", "color": "#0000ff" },
+            "mapping": "uv"
+        }"##;
+        let texture: TextureDescription = serde_json::from_str(json).unwrap();
+
+        let TextureDescription::Checker {
+            even,
+            odd,
+            scale,
+            mapping,
+            ..
+        } = &texture
+        else {
+            panic!("not a checker: {:?}", texture);
+        };
+        assert_eq!(
+            **even,
+            TextureDescription::Uniform {
+                color: Color::RED,
+                coefficients: Coefficients {
+                    ka: 0.5f64,
+                    ..Coefficients::default()
+                },
+            }
+        );
+        assert!(matches!(
+            **odd,
+            TextureDescription::Uniform {
+                color: Color::BLUE,
+                ..
+            }
+        ));
+        assert_eq!(*scale, 1f64);
+        assert_eq!(*mapping, Mapping::Uv);
+    }
+
+    #[test]
+    fn build() {
+        let texture = TextureDescription::Uniform {
+            color: Color::GREEN,
+            coefficients: Coefficients::default(),
+        }
+        .build()
+        .unwrap();
+        let ctx = ShadingContext::at(Point::ZERO);
+
+        assert_eq!(texture.color(&ctx), Color::GREEN);
+        assert_eq!(texture.coefficients(&ctx), (1f64, 0.1f64, 0f64));
+    }
+
+    #[test]
+    fn missing_image() {
+        let texture = TextureDescription::Image {
+            path: String::from("missing.png"),
+            filtering: Default::default(),
+            coefficients: Coefficients::default(),
+        };
+        assert!(texture.build().is_err());
+    }
+
+    #[test]
+    fn expression() {
+        let json = r##"{
+            "type": "expression",
+            "expression": "sin(x * 10) * 0.5 + 0.5",
+            "ramp": [[0, "#000000"], [1, [255, 0, 0]]]
+        }"##;
+        let texture: TextureDescription = serde_json::from_str(json).unwrap();
+        let ctx = ShadingContext::at(Point::ZERO);
+        assert_eq!(texture.build().unwrap().color(&ctx).values(), (188, 0, 0));
+
+        let texture = TextureDescription::Expression {
+            expression: String::from("sin(x"),
+            ramp: None,
+            seed: 0,
+            coefficients: Coefficients::default(),
+        };
+        let error = texture.build().err().unwrap().to_string();
+        assert_eq!(error, "`sin(x`: expected `)` at 5");
+    }
+
+    #[test]
+    fn layered() {
+        let json = r##"{
+            "type": "mix",
+            "a": {
+                "type": "worley",
+                "metric": "manhattan",
+                "mode": "f2_minus_f1",
+                "palette": "deep"
+            },
+            "b": {
+                "type": "transform",
+                "texture": {
+                    "type": "brick",
+                    "brick": { "type": "uniform", "color": "#ff0000" },
+                    "mortar": { "type": "uniform", "color": "#ffffff" },
+                    "size": [2, 1, 1],
+                    "mortar_width": 0.1
+                },
+                "angle": 90
+            },
+            "factor": {
+                "type": "remap",
+                "texture": {
+                    "type": "gradient",
+                    "start": { "type": "uniform", "color": "#000000" },
+                    "end": { "type": "uniform", "color": "#ffffff" },
+                    "kind": "radial"
+                },
+                "ramp": [[1, "#ffffff"], [0, "#000000"]]
+            }
+        }"##;
+        let texture: TextureDescription = serde_json::from_str(json).unwrap();
+        let texture = texture.build().unwrap();
+
+        // At the origin of the gradient, the factor is black
+        let ctx = ShadingContext::at(Point::ZERO);
+        let a = TextureDescription::Worley {
+            seed: 0,
+            metric: Metric::Manhattan,
+            mode: DistanceMode::F2MinusF1,
+            density: 1f64,
+            contrast: 0f64,
+            palette: Palette::Deep,
+            colors: None,
+            mapping: Mapping::Solid,
+            coefficients: Coefficients::default(),
+        };
+        assert_eq!(texture.color(&ctx), a.build().unwrap().color(&ctx));
+    }
+
+    #[test]
+    fn invalid_colors() {
+        let level = TextureDescription::Level {
+            colors: Vec::new(),
+            coefficients: Coefficients::default(),
+        };
+        assert!(level.build().is_err());
+
+        let json = r##"{ "type": "worley", "colors": [] }"##;
+        let worley: TextureDescription = serde_json::from_str(json).unwrap();
+        assert!(worley.build().is_err());
+
+        let json = r##"{
+            "type": "remap",
+            "texture": { "type": "uniform", "color": "#ffffff" },
+            "ramp": []
+        }"##;
+        let remap: TextureDescription = serde_json::from_str(json).unwrap();
+        assert!(remap.build().is_err());
+    }
+}
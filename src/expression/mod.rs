@@ -0,0 +1,295 @@
+//! Math expressions of scene files
+//!
+//! Expressions are parsed once into an `Expr` tree, then evaluated at every
+//! shaded point. They are made of numbers, the variables of `Variable`, the
+//! `+ - * / % ^` operators, parentheses and the functions of `Function`.
+use crate::noise::perlin::Perlin;
+use crate::noise::worley::{DistanceMode, Worley};
+use crate::noise::{fbm, smoothstep, turbulence, Noise, Octaves};
+use crate::point::Point;
+use crate::texture::ShadingContext;
+use std::error::Error;
+use std::f64::consts::PI;
+
+pub mod parser;
+
+/// Values an expression reads from the shading context
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Variable {
+    X,
+    Y,
+    Z,
+    U,
+    V,
+    /// Components of the normal
+    Nx,
+    Ny,
+    Nz,
+}
+
+impl Variable {
+    fn from_name(name: &str) -> Option<Variable> {
+        Some(match name {
+            "x" => Variable::X,
+            "y" => Variable::Y,
+            "z" => Variable::Z,
+            "u" => Variable::U,
+            "v" => Variable::V,
+            "nx" => Variable::Nx,
+            "ny" => Variable::Ny,
+            "nz" => Variable::Nz,
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Operator {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    /// Remainder of the floored division, positive for positive divisors
+    Rem,
+    Pow,
+}
+
+/// Built-in functions, noises taking a point as three arguments
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Function {
+    Sin,
+    Cos,
+    Tan,
+    Abs,
+    Floor,
+    Ceil,
+    /// Fractional part, `x - floor(x)`
+    Fract,
+    Sqrt,
+    Exp,
+    Ln,
+    Min,
+    Max,
+    Pow,
+    /// `clamp(x, min, max)`
+    Clamp,
+    /// `mix(a, b, t)` interpolates `a` and `b` linearly
+    Mix,
+    /// `step(edge, x)` is `0` below `edge` and `1` above
+    Step,
+    Smoothstep,
+    /// Perlin noise in `[-1, 1]`
+    Noise,
+    /// Fractional Brownian motion of the default octaves, in `[-1, 1]`
+    Fbm,
+    /// Sum of absolute octaves, in `[0, 1]`
+    Turbulence,
+    /// Distance to the closest Worley feature point
+    Worley,
+}
+
+impl Function {
+    fn from_name(name: &str) -> Option<Function> {
+        Some(match name {
+            "sin" => Function::Sin,
+            "cos" => Function::Cos,
+            "tan" => Function::Tan,
+            "abs" => Function::Abs,
+            "floor" => Function::Floor,
+            "ceil" => Function::Ceil,
+            "fract" => Function::Fract,
+            "sqrt" => Function::Sqrt,
+            "exp" => Function::Exp,
+            "ln" => Function::Ln,
+            "min" => Function::Min,
+            "max" => Function::Max,
+            "pow" => Function::Pow,
+            "clamp" => Function::Clamp,
+            "mix" => Function::Mix,
+            "step" => Function::Step,
+            "smoothstep" => Function::Smoothstep,
+            "noise" => Function::Noise,
+            "fbm" => Function::Fbm,
+            "turbulence" => Function::Turbulence,
+            "worley" => Function::Worley,
+            _ => return None,
+        })
+    }
+
+    fn arity(&self) -> usize {
+        match self {
+            Function::Min | Function::Max | Function::Pow | Function::Step => 2,
+            Function::Clamp | Function::Mix | Function::Smoothstep => 3,
+            Function::Noise | Function::Fbm | Function::Turbulence | Function::Worley => 3,
+            _ => 1,
+        }
+    }
+}
+
+/// Parsed expression
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Number(f64),
+    Variable(Variable),
+    Neg(Box<Expr>),
+    Binary(Operator, Box<Expr>, Box<Expr>),
+    Call(Function, Vec<Expr>),
+}
+
+/// Noises the noise functions of an expression sample
+pub struct Noises {
+    pub perlin: Perlin,
+    pub worley: Worley,
+}
+
+impl Noises {
+    pub fn new(seed: u64) -> Noises {
+        Noises {
+            perlin: Perlin::new(seed),
+            worley: Worley::new(seed, Default::default()),
+        }
+    }
+}
+
+impl Expr {
+    /// Parses `source`, reporting the position of syntax errors
+    pub fn parse(source: &str) -> Result<Expr, Box<dyn Error>> {
+        parser::parse(source)
+    }
+
+    pub fn eval(&self, ctx: &ShadingContext, noises: &Noises) -> f64 {
+        match self {
+            Expr::Number(n) => *n,
+            Expr::Variable(variable) => match variable {
+                Variable::X => ctx.point.x,
+                Variable::Y => ctx.point.y,
+                Variable::Z => ctx.point.z,
+                Variable::U => ctx.uv.u,
+                Variable::V => ctx.uv.v,
+                Variable::Nx => ctx.normal.x,
+                Variable::Ny => ctx.normal.y,
+                Variable::Nz => ctx.normal.z,
+            },
+            Expr::Neg(e) => -e.eval(ctx, noises),
+            Expr::Binary(operator, a, b) => {
+                let (a, b) = (a.eval(ctx, noises), b.eval(ctx, noises));
+                match operator {
+                    Operator::Add => a + b,
+                    Operator::Sub => a - b,
+                    Operator::Mul => a * b,
+                    Operator::Div => a / b,
+                    Operator::Rem => a - b * (a / b).floor(),
+                    Operator::Pow => a.powf(b),
+                }
+            }
+            Expr::Call(function, args) => {
+                let args = args
+                    .iter()
+                    .map(|arg| arg.eval(ctx, noises))
+                    .collect::<Vec<_>>();
+                call(*function, &args, noises)
+            }
+        }
+    }
+}
+
+/// Applies `function` to `args`, of its arity
+fn call(function: Function, args: &[f64], noises: &Noises) -> f64 {
+    let point = || Point::new(args[0], args[1], args[2]);
+
+    match function {
+        Function::Sin => args[0].sin(),
+        Function::Cos => args[0].cos(),
+        Function::Tan => args[0].tan(),
+        Function::Abs => args[0].abs(),
+        Function::Floor => args[0].floor(),
+        Function::Ceil => args[0].ceil(),
+        Function::Fract => args[0] - args[0].floor(),
+        Function::Sqrt => args[0].sqrt(),
+        Function::Exp => args[0].exp(),
+        Function::Ln => args[0].ln(),
+        Function::Min => args[0].min(args[1]),
+        Function::Max => args[0].max(args[1]),
+        Function::Pow => args[0].powf(args[1]),
+        // Not `f64::clamp`, which panics on reversed bounds
+        Function::Clamp => args[0].max(args[1]).min(args[2]),
+        Function::Mix => args[0] * (1f64 - args[2]) + args[1] * args[2],
+        Function::Step => {
+            if args[1] < args[0] {
+                0f64
+            } else {
+                1f64
+            }
+        }
+        Function::Smoothstep => smoothstep(args[0], args[1], args[2]),
+        Function::Noise => noises.perlin.noise(point()),
+        Function::Fbm => fbm(&noises.perlin, point(), Octaves::default()),
+        Function::Turbulence => turbulence(&noises.perlin, point(), Octaves::default()),
+        Function::Worley => noises.worley.cells(point()).value(DistanceMode::F1),
+    }
+}
+
+/// Named constants, replaced by numbers when parsing
+fn constant(name: &str) -> Option<f64> {
+    match name {
+        "pi" => Some(PI),
+        "e" => Some(std::f64::consts::E),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::expression::{Expr, Noises};
+    use crate::point::Point;
+    use crate::texture::ShadingContext;
+    use crate::uv::Uv;
+    use crate::vector::Vector;
+
+    fn eval(source: &str) -> f64 {
+        let ctx = ShadingContext {
+            normal: Vector::new(0f64, 1f64, 0f64),
+            uv: Uv::new(0.25f64, 0.75f64),
+            ..ShadingContext::at(Point::new(1f64, 2f64, 3f64))
+        };
+        Expr::parse(source).unwrap().eval(&ctx, &Noises::new(0))
+    }
+
+    #[test]
+    fn arithmetic() {
+        assert_eq!(eval("1 + 2 * 3"), 7f64);
+        assert_eq!(eval("(1 + 2) * 3"), 9f64);
+        assert_eq!(eval("2 ^ 3 ^ 2"), 512f64);
+        assert_eq!(eval("-2 ^ 2"), -4f64);
+        assert_eq!(eval("2 ^ -1"), 0.5f64);
+        assert_eq!(eval("-7 % 3"), 2f64);
+        assert_eq!(eval("8 / 4 / 2"), 1f64);
+        assert_eq!(eval("1.5e1 - .5"), 14.5f64);
+    }
+
+    #[test]
+    fn variables() {
+        assert_eq!(eval("x + y * 10 + z * 100"), 321f64);
+        assert_eq!(eval("u + v"), 1f64);
+        assert_eq!(eval("nx + ny + nz"), 1f64);
+        assert_eq!(eval("cos(pi)"), -1f64);
+    }
+
+    #[test]
+    fn functions() {
+        assert_eq!(
+            eval("sin(x * 10) * 0.5 + 0.5"),
+            10f64.sin() * 0.5f64 + 0.5f64
+        );
+        assert_eq!(eval("fract(-0.25)"), 0.75f64);
+        assert_eq!(eval("clamp(5, 0, 1) + min(x, y) + max(x, y)"), 4f64);
+        assert_eq!(eval("mix(0, 10, 0.25)"), 2.5f64);
+        assert_eq!(eval("step(0.5, u) + step(0.5, v)"), 1f64);
+        assert_eq!(eval("smoothstep(0, 1, 0.5)"), 0.5f64);
+
+        // Perlin noise is zero on integer coordinates
+        assert_eq!(eval("noise(x, y, z)"), 0f64);
+        assert!((0f64..=1f64).contains(&eval("turbulence(x * 1.3, y, z)")));
+        assert!(eval("worley(x, y, z)") >= 0f64);
+    }
+}
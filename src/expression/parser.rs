@@ -0,0 +1,269 @@
+//! Recursive descent parser of expressions
+//!
+//! ```text
+//! sum     = product (("+" | "-") product)*
+//! product = unary (("*" | "/" | "%") unary)*
+//! unary   = "-" unary | power
+//! power   = atom ("^" unary)?
+//! atom    = number | name | name "(" sum ("," sum)* ")" | "(" sum ")"
+//! ```
+//!
+//! `^` binds tighter than the unary minus and is right associative, like in
+//! mathematics: `-2^2` is `-4` and `2^3^2` is `512`.
+use crate::expression::{constant, Expr, Function, Operator, Variable};
+use std::error::Error;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Name(String),
+    Symbol(char),
+}
+
+/// Splits `source` into tokens with their byte offsets
+fn tokenize(source: &str) -> Result<Vec<(Token, usize)>, Box<dyn Error>> {
+    let mut tokens = Vec::new();
+    let mut chars = source.char_indices().peekable();
+
+    while let Some(&(start, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c.is_ascii_digit() || c == '.' {
+            let mut end = start;
+            let mut previous = ' ';
+            // Digits, a dot and an exponent which may be signed
+            while let Some(&(i, c)) = chars.peek() {
+                let sign = (c == '-' || c == '+') && (previous == 'e' || previous == 'E');
+                if !(c.is_ascii_digit() || c == '.' || c == 'e' || c == 'E' || sign) {
+                    break;
+                }
+                previous = c;
+                end = i + c.len_utf8();
+                chars.next();
+            }
+
+            let text = &source[start..end];
+            let number = text
+                .parse()
+                .map_err(|_| format!("invalid number `{}` at {}", text, start))?;
+            tokens.push((Token::Number(number), start));
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let mut end = start;
+            while let Some(&(i, c)) = chars.peek() {
+                if !(c.is_ascii_alphanumeric() || c == '_') {
+                    break;
+                }
+                end = i + c.len_utf8();
+                chars.next();
+            }
+            tokens.push((Token::Name(source[start..end].to_string()), start));
+        } else if "+-*/%^(),".contains(c) {
+            tokens.push((Token::Symbol(c), start));
+            chars.next();
+        } else {
+            return Err(format!("unexpected `{}` at {}", c, start).into());
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    position: usize,
+    /// Length of the source, the offset of errors at its end
+    end: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position).map(|(token, _)| token)
+    }
+
+    /// Byte offset of the next token
+    fn offset(&self) -> usize {
+        self.tokens
+            .get(self.position)
+            .map_or(self.end, |&(_, offset)| offset)
+    }
+
+    /// Consumes the next token if it is `symbol`
+    fn eat(&mut self, symbol: char) -> bool {
+        if self.peek() == Some(&Token::Symbol(symbol)) {
+            self.position += 1;
+            return true;
+        }
+        false
+    }
+
+    fn expect(&mut self, symbol: char) -> Result<(), Box<dyn Error>> {
+        if !self.eat(symbol) {
+            return Err(format!("expected `{}` at {}", symbol, self.offset()).into());
+        }
+        Ok(())
+    }
+
+    /// Left associative chain of `operand` separated by the operators of
+    /// `symbols`
+    fn chain(
+        &mut self,
+        symbols: &[(char, Operator)],
+        operand: fn(&mut Parser) -> Result<Expr, Box<dyn Error>>,
+    ) -> Result<Expr, Box<dyn Error>> {
+        let mut expr = operand(self)?;
+
+        'chain: loop {
+            for &(symbol, operator) in symbols {
+                if self.eat(symbol) {
+                    expr = Expr::Binary(operator, Box::new(expr), Box::new(operand(self)?));
+                    continue 'chain;
+                }
+            }
+            return Ok(expr);
+        }
+    }
+
+    fn sum(&mut self) -> Result<Expr, Box<dyn Error>> {
+        self.chain(
+            &[('+', Operator::Add), ('-', Operator::Sub)],
+            Parser::product,
+        )
+    }
+
+    fn product(&mut self) -> Result<Expr, Box<dyn Error>> {
+        self.chain(
+            &[
+                ('*', Operator::Mul),
+                ('/', Operator::Div),
+                ('%', Operator::Rem),
+            ],
+            Parser::unary,
+        )
+    }
+
+    fn unary(&mut self) -> Result<Expr, Box<dyn Error>> {
+        if self.eat('-') {
+            return Ok(Expr::Neg(Box::new(self.unary()?)));
+        }
+        self.power()
+    }
+
+    fn power(&mut self) -> Result<Expr, Box<dyn Error>> {
+        let base = self.atom()?;
+        if self.eat('^') {
+            let exponent = self.unary()?;
+            return Ok(Expr::Binary(
+                Operator::Pow,
+                Box::new(base),
+                Box::new(exponent),
+            ));
+        }
+        Ok(base)
+    }
+
+    fn atom(&mut self) -> Result<Expr, Box<dyn Error>> {
+        let offset = self.offset();
+        let token = self.peek().cloned();
+        self.position += 1;
+
+        match token {
+            Some(Token::Number(n)) => Ok(Expr::Number(n)),
+            Some(Token::Symbol('(')) => {
+                let expr = self.sum()?;
+                self.expect(')')?;
+                Ok(expr)
+            }
+            Some(Token::Name(name)) if self.eat('(') => self.call(&name, offset),
+            Some(Token::Name(name)) => {
+                if let Some(variable) = Variable::from_name(&name) {
+                    return Ok(Expr::Variable(variable));
+                }
+                constant(&name)
+                    .map(Expr::Number)
+                    .ok_or_else(|| format!("unknown variable `{}` at {}", name, offset).into())
+            }
+            Some(Token::Symbol(c)) => Err(format!("unexpected `{}` at {}", c, offset).into()),
+            None => Err(format!("unexpected end at {}", offset).into()),
+        }
+    }
+
+    /// Arguments of the call of `name` at `offset`, its `(` being consumed
+    fn call(&mut self, name: &str, offset: usize) -> Result<Expr, Box<dyn Error>> {
+        let function = Function::from_name(name)
+            .ok_or_else(|| format!("unknown function `{}` at {}", name, offset))?;
+
+        let mut args = vec![self.sum()?];
+        while self.eat(',') {
+            args.push(self.sum()?);
+        }
+        self.expect(')')?;
+
+        if args.len() != function.arity() {
+            return Err(format!(
+                "`{}` at {} takes {} arguments, not {}",
+                name,
+                offset,
+                function.arity(),
+                args.len()
+            )
+            .into());
+        }
+        Ok(Expr::Call(function, args))
+    }
+}
+
+/// Parses a whole expression
+pub fn parse(source: &str) -> Result<Expr, Box<dyn Error>> {
+    let mut parser = Parser {
+        tokens: tokenize(source)?,
+        position: 0,
+        end: source.len(),
+    };
+
+    let expr = parser.sum()?;
+    if parser.position < parser.tokens.len() {
+        return Err(format!("unexpected input at {}", parser.offset()).into());
+    }
+    Ok(expr)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::expression::parser::parse;
+    use crate::expression::{Expr, Function, Operator, Variable};
+
+    #[test]
+    fn tree() {
+        let expr = parse("sin(x*10)*0.5").unwrap();
+        assert_eq!(
+            expr,
+            Expr::Binary(
+                Operator::Mul,
+                Box::new(Expr::Call(
+                    Function::Sin,
+                    vec![Expr::Binary(
+                        Operator::Mul,
+                        Box::new(Expr::Variable(Variable::X)),
+                        Box::new(Expr::Number(10f64)),
+                    )],
+                )),
+                Box::new(Expr::Number(0.5f64)),
+            )
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let error = |source: &str| parse(source).unwrap_err().to_string();
+
+        assert_eq!(error("1 +"), "unexpected end at 3");
+        assert_eq!(error("(x"), "expected `)` at 2");
+        assert_eq!(error("x y"), "unexpected input at 2");
+        assert_eq!(error("w * 2"), "unknown variable `w` at 0");
+        assert_eq!(error("1 + sine(x)"), "unknown function `sine` at 4");
+        assert_eq!(error("min(x)"), "`min` at 0 takes 2 arguments, not 1");
+        assert_eq!(error("x # 2"), "unexpected `#` at 2");
+        assert_eq!(error("1..2"), "invalid number `1..2` at 0");
+        assert!(parse("").is_err());
+    }
+}
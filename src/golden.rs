@@ -0,0 +1,184 @@
+//! Tests rendering canonical scenes against reference images
+//!
+//! Flat scenes are checked against the `images/` renders of earlier versions,
+//! lit scenes against `images/golden/` renders of this one, which running
+//! the tests with `UPDATE_GOLDEN` set rewrites. On failure the render and a
+//! difference image are written to `target/golden/` next to the reference
+//! name.
+use crate::camera::Camera;
+use crate::color::{Color, Encoding};
+use crate::img::compare::{compare, diff_image, Tolerance};
+use crate::img::Image;
+use crate::light::point::PointLight;
+use crate::material::Material;
+use crate::object::plane::Plane;
+use crate::object::sphere::Sphere;
+use crate::point::Point;
+use crate::scene::Scene;
+use crate::texture::uniform::UniformTexture;
+use crate::vector::Vector;
+use std::fs;
+use std::path::Path;
+use std::rc::Rc;
+
+/// Edges of the `images/` references were rendered by an older camera, a
+/// few silhouette pixels are allowed to differ
+const REFERENCE: Tolerance = Tolerance {
+    max_error: 2,
+    max_outliers: 0.005f64,
+    min_psnr: 23f64,
+};
+
+/// `images/golden/` holds renders of this renderer, only rounding
+/// differences between platforms are allowed
+const REGRESSION: Tolerance = Tolerance {
+    max_error: 1,
+    max_outliers: 0f64,
+    min_psnr: 48f64,
+};
+
+/// Material showing its color as is: only the ambient term is lit
+fn flat(color: Color) -> Rc<Material> {
+    Rc::new(Material::new(Box::new(UniformTexture {
+        kd: 0f64,
+        ks: 0f64,
+        ka: 1f64,
+        color,
+    })))
+}
+
+/// Material lit by its diffuse, specular and ambient terms
+fn lit(color: Color) -> Rc<Material> {
+    Rc::new(Material::new(Box::new(UniformTexture {
+        kd: 0.7f64,
+        ks: 0.3f64,
+        ka: 0.1f64,
+        color,
+    })))
+}
+
+/// A large sphere in front of a smaller one on its left, lit from above and
+/// seen on a white background
+fn two_spheres(front: Rc<Material>, back: Rc<Material>) -> Scene {
+    let cam = Camera::new(
+        Point::ZERO,
+        Point::new(0f64, 0f64, 1f64),
+        Vector::new(0f64, 1f64, 0f64),
+        std::f64::consts::FRAC_PI_2,
+    );
+
+    let front = Sphere {
+        p: Point::new(0.027f64, -0.027f64, 2.14f64),
+        r: 1f64,
+        material: front,
+        id: String::from("front"),
+    };
+
+    let back = Sphere {
+        p: Point::new(-4.55f64, -0.09f64, 7.2f64),
+        r: 1.5f64,
+        material: back,
+        id: String::from("back"),
+    };
+
+    Scene {
+        cam,
+        lights: vec![Box::new(PointLight {
+            intensity: Vector::new(255f64, 255f64, 255f64),
+            point: Point::new(0f64, 5f64, 0f64),
+        })],
+        objects: vec![Box::new(front), Box::new(back)],
+    }
+}
+
+/// `two_spheres` of lit materials casting shadows on a gray floor
+fn lit_spheres(front: Color, back: Color) -> Scene {
+    let mut scene = two_spheres(lit(front), lit(back));
+    scene.objects.push(Box::new(Plane {
+        p: Point::new(0f64, -1.6f64, 0f64),
+        normal: Vector::new(0f64, 1f64, 0f64),
+        material: lit(Color::new(128, 128, 128)),
+        back: None,
+        id: String::from("floor"),
+    }));
+    scene
+}
+
+/// Compares `img` to the reference, saving the render and the difference
+/// to `target/golden/` when it is not accepted
+fn check(img: &Image, reference: &str, tolerance: Tolerance) {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join(reference);
+    let expected = Image::load_png(path.to_str().unwrap(), Encoding::Srgb).unwrap();
+    let comparison = compare(img, &expected).unwrap();
+
+    if tolerance.accepts(&comparison) {
+        return;
+    }
+
+    let out = Path::new(env!("CARGO_MANIFEST_DIR")).join("target/golden");
+    fs::create_dir_all(&out).unwrap();
+
+    let name = path.file_stem().unwrap().to_str().unwrap();
+    let actual = out.join(format!("{}_actual.png", name));
+    let diff = out.join(format!("{}_diff.png", name));
+
+    img.save_png(actual.to_str().unwrap()).unwrap();
+    diff_image(img, &expected)
+        .unwrap()
+        .save_png(diff.to_str().unwrap())
+        .unwrap();
+
+    panic!(
+        "{} differs: max error {}, {:.2}% pixels above {}, PSNR {:.2} dB, see {}",
+        reference,
+        comparison.max_error,
+        comparison.outliers(tolerance.max_error) * 100f64,
+        tolerance.max_error,
+        comparison.psnr,
+        diff.display()
+    );
+}
+
+/// Compares `img` to the `images/golden/` render of `name`, which is written
+/// instead when `UPDATE_GOLDEN` is set
+fn regression(img: &Image, name: &str) {
+    let reference = format!("images/golden/{}.png", name);
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join(&reference);
+        img.save_png(path.to_str().unwrap()).unwrap();
+        return;
+    }
+
+    check(img, &reference, REGRESSION);
+}
+
+#[test]
+fn v1_black_white() {
+    let scene = two_spheres(flat(Color::BLACK), flat(Color::BLACK));
+    check(
+        &scene.image(80, 80),
+        "images/80x80_v1_black_white.png",
+        REFERENCE,
+    );
+}
+
+#[test]
+fn v2_color() {
+    let scene = two_spheres(
+        flat(Color::new(66, 135, 245)),
+        flat(Color::new(227, 66, 245)),
+    );
+    check(&scene.image(80, 80), "images/80x80_v2_color.png", REFERENCE);
+}
+
+#[test]
+fn gray_spheres() {
+    let scene = lit_spheres(Color::WHITE, Color::new(64, 64, 64));
+    regression(&scene.image(80, 80), "80x80_gray_spheres");
+}
+
+#[test]
+fn color_spheres() {
+    let scene = lit_spheres(Color::new(66, 135, 245), Color::new(227, 66, 245));
+    regression(&scene.image(80, 80), "80x80_color_spheres");
+}
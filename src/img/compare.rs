@@ -0,0 +1,355 @@
+//! Comparison of the 8-bit output of two images
+use crate::color::{Color, Encoding};
+use crate::img::Image;
+use std::error::Error;
+
+/// Differences between two images of the same size
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comparison {
+    /// Largest absolute difference of a channel, per pixel
+    pub pixel_errors: Vec<u8>,
+    /// Largest value of `pixel_errors`
+    pub max_error: u8,
+    /// Largest absolute difference of the red, green and blue channels
+    pub channel_max_errors: [u8; 3],
+    /// Mean squared error of the red, green and blue channels
+    pub channel_mse: [f64; 3],
+    /// Mean squared error over every channel
+    pub mse: f64,
+    /// Peak signal to noise ratio in dB, infinite for identical images
+    pub psnr: f64,
+    /// Mean structural similarity of the luma, 1 for identical images
+    pub ssim: f64,
+}
+
+impl Comparison {
+    /// Fraction of pixels whose error is above `threshold`
+    #[allow(dead_code)]
+    pub fn outliers(&self, threshold: u8) -> f64 {
+        let count = self.pixel_errors.iter().filter(|&&e| e > threshold).count();
+        count as f64 / self.pixel_errors.len() as f64
+    }
+
+    /// PSNR of the red, green and blue channels
+    pub fn channel_psnr(&self) -> [f64; 3] {
+        self.channel_mse.map(psnr)
+    }
+}
+
+/// Acceptance criteria of a `Comparison`
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[allow(dead_code)]
+pub struct Tolerance {
+    /// Error a pixel may have without being counted as an outlier
+    pub max_error: u8,
+    /// Fraction of pixels allowed above `max_error`
+    pub max_outliers: f64,
+    /// Lowest accepted PSNR in dB
+    pub min_psnr: f64,
+}
+
+impl Tolerance {
+    #[allow(dead_code)]
+    pub fn accepts(&self, comparison: &Comparison) -> bool {
+        comparison.psnr >= self.min_psnr && comparison.outliers(self.max_error) <= self.max_outliers
+    }
+}
+
+/// Bytes written by `save_png` for every pixel of `img`
+fn rgb8_pixels(img: &Image) -> Vec<[u8; 3]> {
+    img.pixels
+        .iter()
+        .map(|&c| {
+            let (r, g, b) = img.rgb8(c);
+            [r, g, b]
+        })
+        .collect()
+}
+
+fn check_sizes(a: &Image, b: &Image) -> Result<(), Box<dyn Error>> {
+    a.check_complete()?;
+    b.check_complete()?;
+
+    if (a.width, a.height) != (b.width, b.height) {
+        return Err(format!(
+            "size mismatch: {}x{} against {}x{}",
+            a.width, a.height, b.width, b.height
+        )
+        .into());
+    }
+
+    Ok(())
+}
+
+fn psnr(mse: f64) -> f64 {
+    10f64 * (255f64.powf(2f64) / mse).log10()
+}
+
+/// Rec. 601 luma of 8-bit pixels
+fn luma(pixels: &[[u8; 3]]) -> Vec<f64> {
+    pixels
+        .iter()
+        .map(|p| 0.299f64 * p[0] as f64 + 0.587f64 * p[1] as f64 + 0.114f64 * p[2] as f64)
+        .collect()
+}
+
+/// Side of the square windows SSIM statistics are computed on
+const SSIM_WINDOW: usize = 8;
+
+/// Mean SSIM over every window of `SSIM_WINDOW` pixels, the whole image is a
+/// single window when smaller
+fn ssim(a: &[f64], b: &[f64], width: usize, height: usize) -> f64 {
+    const C1: f64 = (0.01f64 * 255f64) * (0.01f64 * 255f64);
+    const C2: f64 = (0.03f64 * 255f64) * (0.03f64 * 255f64);
+
+    let (w, h) = (SSIM_WINDOW.min(width), SSIM_WINDOW.min(height));
+    let n = (w * h) as f64;
+
+    let mut total = 0f64;
+    let mut windows = 0;
+
+    for y in 0..=(height - h) {
+        for x in 0..=(width - w) {
+            let (mut sa, mut sb, mut saa, mut sbb, mut sab) = (0f64, 0f64, 0f64, 0f64, 0f64);
+
+            for j in y..(y + h) {
+                for i in x..(x + w) {
+                    let (va, vb) = (a[j * width + i], b[j * width + i]);
+                    sa += va;
+                    sb += vb;
+                    saa += va * va;
+                    sbb += vb * vb;
+                    sab += va * vb;
+                }
+            }
+
+            let (ma, mb) = (sa / n, sb / n);
+            let var_a = saa / n - ma * ma;
+            let var_b = sbb / n - mb * mb;
+            let cov = sab / n - ma * mb;
+
+            total += ((2f64 * ma * mb + C1) * (2f64 * cov + C2))
+                / ((ma * ma + mb * mb + C1) * (var_a + var_b + C2));
+            windows += 1;
+        }
+    }
+
+    total / windows as f64
+}
+
+/// Compares the 8-bit output of `a` and `b`
+pub fn compare(a: &Image, b: &Image) -> Result<Comparison, Box<dyn Error>> {
+    check_sizes(a, b)?;
+
+    let (pixels_a, pixels_b) = (rgb8_pixels(a), rgb8_pixels(b));
+    let mut pixel_errors = Vec::with_capacity(a.pixels.len());
+    let mut channel_max_errors = [0u8; 3];
+    let mut channel_squared = [0f64; 3];
+
+    for (pa, pb) in pixels_a.iter().zip(&pixels_b) {
+        let errors = [0, 1, 2].map(|i| pa[i].abs_diff(pb[i]));
+
+        for i in 0..3 {
+            channel_max_errors[i] = channel_max_errors[i].max(errors[i]);
+            channel_squared[i] += (errors[i] as f64).powf(2f64);
+        }
+        pixel_errors.push(*errors.iter().max().unwrap());
+    }
+
+    let channel_mse = channel_squared.map(|s| s / a.pixels.len() as f64);
+    let mse = channel_mse.iter().sum::<f64>() / 3f64;
+
+    Ok(Comparison {
+        max_error: *pixel_errors.iter().max().unwrap(),
+        pixel_errors,
+        channel_max_errors,
+        channel_mse,
+        mse,
+        psnr: psnr(mse),
+        ssim: ssim(&luma(&pixels_a), &luma(&pixels_b), a.width, a.height),
+    })
+}
+
+/// Absolute difference of the 8-bit output of `a` and `b`, stored so that
+/// saving it writes the differences as is
+#[allow(dead_code)]
+pub fn diff_image(a: &Image, b: &Image) -> Result<Image, Box<dyn Error>> {
+    check_sizes(a, b)?;
+
+    let mut diff = Image::new(a.height, a.width);
+    diff.with_encoding(Encoding::Linear);
+
+    for (pa, pb) in rgb8_pixels(a).iter().zip(rgb8_pixels(b)) {
+        let [r, g, b] = [0, 1, 2].map(|i| pa[i].abs_diff(pb[i]));
+        diff.push(Color::new(r, g, b));
+    }
+
+    Ok(diff)
+}
+
+/// False color map from black (no error) through blue, cyan, green and yellow
+/// to red (error of `scale` or more)
+fn heat(error: f64, scale: f64) -> Color {
+    const STOPS: [(u8, u8, u8); 6] = [
+        (0, 0, 0),
+        (0, 0, 255),
+        (0, 255, 255),
+        (0, 255, 0),
+        (255, 255, 0),
+        (255, 0, 0),
+    ];
+
+    let t = (error / scale).clamp(0f64, 1f64) * (STOPS.len() - 1) as f64;
+    let index = (t.floor() as usize).min(STOPS.len() - 2);
+    let f = t - index as f64;
+
+    let (r1, g1, b1) = STOPS[index];
+    let (r2, g2, b2) = STOPS[index + 1];
+    let (c1, c2) = (
+        Color::new(r1, g1, b1).to_vec(),
+        Color::new(r2, g2, b2).to_vec(),
+    );
+
+    Color::from(c1 * (1f64 - f) + c2 * f)
+}
+
+/// Heatmap of the per-pixel max channel error between `a` and `b`
+///
+/// Colors are scaled so that the largest error is red, saving the result
+/// writes the colors as is.
+pub fn heatmap(a: &Image, b: &Image) -> Result<Image, Box<dyn Error>> {
+    let comparison = compare(a, b)?;
+    let scale = comparison.max_error.max(1) as f64;
+
+    let mut map = Image::new(a.height, a.width);
+    map.with_encoding(Encoding::Linear);
+
+    for &error in &comparison.pixel_errors {
+        map.push(heat(error as f64, scale));
+    }
+
+    Ok(map)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::color::{Color, Encoding};
+    use crate::img::compare::{compare, diff_image, heatmap, Tolerance};
+    use crate::img::Image;
+
+    fn image(colors: &[Color]) -> Image {
+        let mut img = Image::new(1, colors.len());
+        img.with_encoding(Encoding::Linear);
+        for &c in colors {
+            img.push(c);
+        }
+        img
+    }
+
+    #[test]
+    fn identical() {
+        let a = image(&[Color::RED, Color::BLUE]);
+        let cmp = compare(&a, &a).unwrap();
+
+        assert_eq!(cmp.max_error, 0);
+        assert_eq!(cmp.psnr, f64::INFINITY);
+        assert_eq!(cmp.ssim, 1f64);
+    }
+
+    #[test]
+    fn metrics() {
+        let a = image(&[Color::new(10, 10, 10), Color::BLACK]);
+        let b = image(&[Color::new(10, 10, 10), Color::new(0, 0, 30)]);
+        let cmp = compare(&a, &b).unwrap();
+
+        assert_eq!(cmp.pixel_errors, vec![0, 30]);
+        assert_eq!(cmp.max_error, 30);
+        assert_eq!(cmp.mse, 150f64);
+        assert_eq!(format!("{:.2}", cmp.psnr), "26.37");
+        assert_eq!(cmp.outliers(20), 0.5f64);
+        assert_eq!(cmp.channel_max_errors, [0, 0, 30]);
+        assert_eq!(cmp.channel_mse, [0f64, 0f64, 450f64]);
+        assert_eq!(cmp.channel_psnr()[0], f64::INFINITY);
+    }
+
+    fn checker(width: usize, height: usize, dark: Color) -> Image {
+        let mut img = Image::new(height, width);
+        img.with_encoding(Encoding::Linear);
+
+        for i in 0..(width * height) {
+            let (x, y) = (i % width, i / width);
+            img.push(if (x / 2 + y / 2) % 2 == 0 {
+                dark
+            } else {
+                Color::WHITE
+            });
+        }
+        img
+    }
+
+    #[test]
+    fn ssim_decreases_with_structure_loss() {
+        let reference = checker(16, 16, Color::BLACK);
+        let close = checker(16, 16, Color::new(20, 20, 20));
+        let far = checker(16, 16, Color::new(200, 200, 200));
+
+        let close = compare(&reference, &close).unwrap().ssim;
+        let far = compare(&reference, &far).unwrap().ssim;
+
+        assert!(close < 1f64);
+        assert!(far < close);
+    }
+
+    #[test]
+    fn heatmap_colors() {
+        let a = image(&[Color::BLACK, Color::BLACK, Color::BLACK]);
+        let b = image(&[
+            Color::BLACK,
+            Color::new(60, 60, 60),
+            Color::new(100, 100, 100),
+        ]);
+        let map = heatmap(&a, &b).unwrap();
+
+        assert_eq!(map.rgb8(map.pixels()[0]), (0, 0, 0));
+        assert_eq!(map.rgb8(map.pixels()[1]), (0, 255, 0));
+        assert_eq!(map.rgb8(map.pixels()[2]), (255, 0, 0));
+    }
+
+    #[test]
+    fn tolerance() {
+        let a = image(&[Color::WHITE, Color::WHITE, Color::WHITE, Color::WHITE]);
+        let b = image(&[Color::WHITE, Color::WHITE, Color::WHITE, Color::BLACK]);
+        let cmp = compare(&a, &b).unwrap();
+
+        let strict = Tolerance {
+            max_error: 0,
+            max_outliers: 0f64,
+            min_psnr: 0f64,
+        };
+        let loose = Tolerance {
+            max_outliers: 0.25f64,
+            ..strict
+        };
+
+        assert!(!strict.accepts(&cmp));
+        assert!(loose.accepts(&cmp));
+    }
+
+    #[test]
+    fn size_mismatch() {
+        let a = image(&[Color::RED]);
+        let b = image(&[Color::RED, Color::RED]);
+
+        assert!(compare(&a, &b).is_err());
+        assert!(diff_image(&a, &b).is_err());
+    }
+
+    #[test]
+    fn diff() {
+        let a = image(&[Color::new(200, 10, 0)]);
+        let b = image(&[Color::new(100, 30, 0)]);
+        let diff = diff_image(&a, &b).unwrap();
+
+        assert_eq!(diff.rgb8(diff.pixels()[0]), (100, 20, 0));
+    }
+}
@@ -0,0 +1,149 @@
+//! OpenEXR input and output
+use crate::img::Image;
+use ::exr::prelude::{
+    AnyChannel, AnyChannels, Encoding, FlatSamples, Image as ExrImage, Layer, LayerAttributes,
+    WritableImage,
+};
+use std::error::Error;
+
+impl Image {
+    /// Reads the `R`, `G` and `B` channels of an OpenEXR file as linear
+    /// values, `1.0` being white
+    pub fn load_exr(filename: &str) -> Result<Image, Box<dyn Error>> {
+        let buffer = image::open(filename)?.into_rgb32f();
+        let (width, height) = buffer.dimensions();
+
+        Ok(Image::from_linear(
+            height as usize,
+            width as usize,
+            buffer.pixels().map(|pixel| pixel.0),
+        ))
+    }
+
+    /// Writes the linear framebuffer as an OpenEXR file with 32-bit float
+    /// `R`, `G`, `B` channels, white being `1.0`.
+    ///
+    /// When the render filled them, depth is written to `Z`, normals to
+    /// `N.X`, `N.Y` and `N.Z`, and the linear albedo to `albedo.R`,
+    /// `albedo.G` and `albedo.B`.
+    pub fn save_exr(&self, filename: &str) -> Result<(), Box<dyn Error>> {
+        self.check_complete()?;
+
+        let linear = self.linear_pixels().collect::<Vec<_>>();
+        let channel =
+            |name: &str, values: Vec<f32>| AnyChannel::new(name, FlatSamples::F32(values));
+
+        let mut channels = vec![
+            channel("R", linear.iter().map(|c| c[0]).collect()),
+            channel("G", linear.iter().map(|c| c[1]).collect()),
+            channel("B", linear.iter().map(|c| c[2]).collect()),
+        ];
+
+        if self.has_aux() {
+            channels.push(channel("Z", self.depth.iter().map(|&d| d as f32).collect()));
+            channels.push(channel(
+                "N.X",
+                self.normals.iter().map(|n| n.x as f32).collect(),
+            ));
+            channels.push(channel(
+                "N.Y",
+                self.normals.iter().map(|n| n.y as f32).collect(),
+            ));
+            channels.push(channel(
+                "N.Z",
+                self.normals.iter().map(|n| n.z as f32).collect(),
+            ));
+
+            let albedo = self
+                .albedo
+                .iter()
+                .map(|c| c.to_vec() / 255f64)
+                .collect::<Vec<_>>();
+            channels.push(channel(
+                "albedo.R",
+                albedo.iter().map(|c| c.x as f32).collect(),
+            ));
+            channels.push(channel(
+                "albedo.G",
+                albedo.iter().map(|c| c.y as f32).collect(),
+            ));
+            channels.push(channel(
+                "albedo.B",
+                albedo.iter().map(|c| c.z as f32).collect(),
+            ));
+        }
+
+        let layer = Layer::new(
+            (self.width, self.height),
+            LayerAttributes::default(),
+            Encoding::FAST_LOSSLESS,
+            AnyChannels::sort(channels.into()),
+        );
+
+        ExrImage::from_layer(layer).write().to_file(filename)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::color::Color;
+    use crate::img::Image;
+    use crate::vector::Vector;
+    use ::exr::prelude::{read, FlatSamples, ReadChannels, ReadLayers};
+
+    fn channel(path: &str, name: &str) -> Option<Vec<f32>> {
+        let image = read()
+            .no_deep_data()
+            .largest_resolution_level()
+            .all_channels()
+            .first_valid_layer()
+            .all_attributes()
+            .from_file(path)
+            .unwrap();
+        let layer = &image.layer_data;
+
+        layer
+            .channel_data
+            .list
+            .iter()
+            .find(|c| c.name == *name)
+            .map(|c| match &c.sample_data {
+                FlatSamples::F32(values) => values.clone(),
+                _ => panic!("channel {} is not f32", name),
+            })
+    }
+
+    #[test]
+    fn save_exr() {
+        let mut img = Image::new(1, 2);
+        img.push(Color::from(Vector::new(510f64, 255f64, 0f64)));
+        img.push_aux(2f64, Vector::new(0f64, 1f64, 0f64), Color::RED);
+        img.push(Color::WHITE);
+        img.push_aux(f64::INFINITY, Vector::ZERO, Color::WHITE);
+
+        let path = std::env::temp_dir().join("raytracer_save_exr.exr");
+        let path = path.to_str().unwrap();
+        img.save_exr(path).unwrap();
+
+        assert_eq!(channel(path, "R"), Some(vec![2f32, 1f32]));
+        assert_eq!(channel(path, "B"), Some(vec![0f32, 1f32]));
+        assert_eq!(channel(path, "Z"), Some(vec![2f32, f32::INFINITY]));
+        assert_eq!(channel(path, "N.Y"), Some(vec![1f32, 0f32]));
+        assert_eq!(channel(path, "albedo.R"), Some(vec![1f32, 1f32]));
+        assert_eq!(channel(path, "albedo.G"), Some(vec![0f32, 1f32]));
+    }
+
+    #[test]
+    fn save_exr_without_aux() {
+        let mut img = Image::new(1, 1);
+        img.push(Color::BLACK);
+
+        let path = std::env::temp_dir().join("raytracer_save_exr_no_aux.exr");
+        let path = path.to_str().unwrap();
+        img.save_exr(path).unwrap();
+
+        assert_eq!(channel(path, "G"), Some(vec![0f32]));
+        assert_eq!(channel(path, "Z"), None);
+    }
+}
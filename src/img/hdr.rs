@@ -0,0 +1,70 @@
+//! Radiance RGBE input and output
+use crate::img::Image;
+use image::codecs::hdr::{HdrDecoder, HdrEncoder};
+use image::Rgb;
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+
+impl Image {
+    /// Reads a Radiance `.hdr` file as linear values, `1.0` being white
+    ///
+    /// The decoder of `image::open` clamps them to 8 bits.
+    pub fn load_hdr(filename: &str) -> Result<Image, Box<dyn Error>> {
+        let decoder = HdrDecoder::new(BufReader::new(File::open(filename)?))?;
+        let metadata = decoder.metadata();
+        let pixels = decoder.read_image_hdr()?;
+
+        Ok(Image::from_linear(
+            metadata.height as usize,
+            metadata.width as usize,
+            pixels.into_iter().map(|pixel| pixel.0),
+        ))
+    }
+
+    /// Writes the linear framebuffer as a Radiance `.hdr` file
+    ///
+    /// No tone mapping nor encoding is applied, white is written as `1.0`.
+    pub fn save_hdr(&self, filename: &str) -> Result<(), Box<dyn Error>> {
+        self.check_complete()?;
+
+        let pixels = self
+            .linear_pixels()
+            .map(|[r, g, b]| Rgb([r, g, b]))
+            .collect::<Vec<_>>();
+
+        let buffer = BufWriter::new(File::create(filename)?);
+        HdrEncoder::new(buffer).encode(&pixels, self.width, self.height)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::color::Color;
+    use crate::img::Image;
+    use crate::vector::Vector;
+    use image::codecs::hdr::HdrDecoder;
+    use std::fs::File;
+    use std::io::BufReader;
+
+    #[test]
+    fn save_hdr() {
+        let mut img = Image::new(1, 2);
+        img.push(Color::from(Vector::new(510f64, 255f64, 0f64)));
+        img.push(Color::WHITE);
+
+        let path = std::env::temp_dir().join("raytracer_save_hdr.hdr");
+        let path = path.to_str().unwrap();
+        img.save_hdr(path).unwrap();
+
+        let decoder = HdrDecoder::new(BufReader::new(File::open(path).unwrap())).unwrap();
+        let metadata = decoder.metadata();
+        assert_eq!((metadata.width, metadata.height), (2, 1));
+
+        let decoded = decoder.read_image_hdr().unwrap();
+        assert_eq!(decoded[0].0, [2f32, 1f32, 0f32]);
+        assert_eq!(decoded[1].0, [1f32, 1f32, 1f32]);
+    }
+}
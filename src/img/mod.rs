@@ -0,0 +1,364 @@
+//! Basic image representation with `Color` list
+use crate::color::{Color, Encoding};
+use crate::tonemap::ToneMapper;
+use crate::vector::Vector;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::path::Path;
+
+pub mod compare;
+pub mod exr;
+pub mod hdr;
+pub mod pnm;
+
+/// File format renders are written in
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Format {
+    /// 8-bit, tone mapped and encoded
+    Png,
+    /// Radiance RGBE, linear
+    Hdr,
+    /// OpenEXR, linear, with the depth, normal and albedo buffers
+    Exr,
+    /// Binary PPM (P6), tone mapped and encoded
+    Ppm,
+    /// Little endian PFM, linear
+    Pfm,
+}
+
+impl Format {
+    /// Format of the extension of `filename`
+    pub fn from_extension(filename: &str) -> Result<Format, Box<dyn Error>> {
+        match extension(filename).as_deref() {
+            Some("png") => Ok(Format::Png),
+            Some("hdr") => Ok(Format::Hdr),
+            Some("exr") => Ok(Format::Exr),
+            Some("ppm") => Ok(Format::Ppm),
+            Some("pfm") => Ok(Format::Pfm),
+            _ => Err(format!("{}: unknown image format", filename).into()),
+        }
+    }
+}
+
+/// Contains list of `color::Color`
+///
+/// Pixels are kept linear and unclamped, the `ToneMapper` and the output
+/// `Encoding` are only applied when writing 8-bit files.
+/// Renders may also fill the depth, normal and albedo buffers of the first hit.
+pub struct Image {
+    height: usize,
+    width: usize,
+    pixels: Vec<Color>,
+    depth: Vec<f64>,
+    normals: Vec<Vector>,
+    albedo: Vec<Color>,
+    tone_mapper: ToneMapper,
+    encoding: Encoding,
+}
+
+impl Image {
+    pub fn new(height: usize, width: usize) -> Self {
+        Image {
+            height,
+            width,
+            pixels: Vec::with_capacity(height * width),
+            depth: Vec::new(),
+            normals: Vec::new(),
+            albedo: Vec::new(),
+            tone_mapper: ToneMapper::default(),
+            encoding: Encoding::Srgb,
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn with_tone_mapper(&mut self, tone_mapper: ToneMapper) -> &mut Self {
+        self.tone_mapper = tone_mapper;
+        self
+    }
+
+    #[allow(dead_code)]
+    pub fn tone_mapper(&self) -> ToneMapper {
+        self.tone_mapper
+    }
+
+    /// Selects the transfer function of 8-bit output, sRGB by default
+    #[allow(dead_code)]
+    pub fn with_encoding(&mut self, encoding: Encoding) -> &mut Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Linear pixels normalized so that white is `1.0`
+    fn linear_pixels(&self) -> impl Iterator<Item = [f32; 3]> + '_ {
+        self.pixels.iter().map(|c| {
+            let v = c.to_vec() / 255f64;
+            [v.x as f32, v.y as f32, v.z as f32]
+        })
+    }
+
+    /// Tone maps, encodes and quantizes a linear pixel
+    fn rgb8(&self, c: Color) -> (u8, u8, u8) {
+        self.encoding.encode(self.tone_mapper.map(c)).values()
+    }
+
+    #[allow(dead_code)]
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    #[allow(dead_code)]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    #[allow(dead_code)]
+    pub fn pixels(&self) -> &Vec<Color> {
+        &self.pixels
+    }
+
+    /// Linear pixels, to be modified by post-processing before output
+    pub fn pixels_mut(&mut self) -> &mut Vec<Color> {
+        &mut self.pixels
+    }
+
+    pub fn push(&mut self, c: Color) {
+        self.pixels.push(c);
+    }
+
+    /// Depth along the camera axis, `f64::INFINITY` where nothing was hit
+    #[allow(dead_code)]
+    pub fn depth(&self) -> &Vec<f64> {
+        &self.depth
+    }
+
+    /// World space normals facing the camera, zero where nothing was hit
+    #[allow(dead_code)]
+    pub fn normals(&self) -> &Vec<Vector> {
+        &self.normals
+    }
+
+    /// Linear texture color of the first hit, the background color where
+    /// nothing was hit
+    #[allow(dead_code)]
+    pub fn albedo(&self) -> &Vec<Color> {
+        &self.albedo
+    }
+
+    /// Pushes the auxiliary data of the next pixel
+    pub fn push_aux(&mut self, depth: f64, normal: Vector, albedo: Color) {
+        self.depth.push(depth);
+        self.normals.push(normal);
+        self.albedo.push(albedo);
+    }
+
+    /// True when every pixel has its depth, normal and albedo
+    pub fn has_aux(&self) -> bool {
+        [self.depth.len(), self.normals.len(), self.albedo.len()]
+            .iter()
+            .all(|&len| len == self.pixels.len())
+    }
+
+    /// Returns an error when fewer or more pixels than `height * width` were pushed
+    fn check_complete(&self) -> Result<(), Box<dyn Error>> {
+        if self.pixels.len() != self.height * self.width {
+            return Err(format!(
+                "image has {} pixels, expected {}x{}",
+                self.pixels.len(),
+                self.width,
+                self.height
+            )
+            .into());
+        }
+
+        Ok(())
+    }
+
+    /// Tone mapped, encoded and quantized pixels, 3 bytes each
+    fn rgb8_bytes(&self) -> Vec<u8> {
+        self.pixels
+            .iter()
+            .flat_map(|&c| {
+                let (r, g, b) = self.rgb8(c);
+                [r, g, b]
+            })
+            .collect()
+    }
+
+    /// Writes the image in `format`
+    pub fn save_as(&self, filename: &str, format: Format) -> Result<(), Box<dyn Error>> {
+        match format {
+            Format::Png => self.save_png(filename),
+            Format::Hdr => self.save_hdr(filename),
+            Format::Exr => self.save_exr(filename),
+            Format::Ppm => self.save(filename),
+            Format::Pfm => self.save_pfm(filename),
+        }
+    }
+
+    pub fn save_png(&self, filename: &str) -> Result<(), Box<dyn Error>> {
+        self.check_complete()?;
+
+        image::save_buffer(
+            filename,
+            &self.rgb8_bytes(),
+            self.width as u32,
+            self.height as u32,
+            image::ColorType::Rgb8,
+        )?;
+
+        Ok(())
+    }
+
+    /// Reads an image, picking the reader from the file extension: `.ppm`,
+    /// `.pfm`, `.hdr`, `.exr`, or any format of the `image` crate otherwise
+    ///
+    /// 8-bit values are decoded with `encoding`, float ones are linear.
+    pub fn load(filename: &str, encoding: Encoding) -> Result<Image, Box<dyn Error>> {
+        match extension(filename).as_deref() {
+            Some("ppm") => Image::load_ppm(filename, encoding),
+            Some("pfm") => Image::load_pfm(filename),
+            Some("hdr") => Image::load_hdr(filename),
+            Some("exr") => Image::load_exr(filename),
+            _ => Image::load_png(filename, encoding),
+        }
+    }
+
+    /// Image of linear float pixels, row by row, white being `1.0`
+    fn from_linear(height: usize, width: usize, pixels: impl Iterator<Item = [f32; 3]>) -> Image {
+        let mut img = Image::new(height, width);
+        for pixel in pixels {
+            let [r, g, b] = pixel.map(|v| v as f64 * 255f64);
+            img.push(Color::from(Vector::new(r, g, b)));
+        }
+        img
+    }
+
+    /// Reads any 8-bit image supported by the `image` crate, values are
+    /// decoded with `encoding`
+    pub fn load_png(filename: &str, encoding: Encoding) -> Result<Image, Box<dyn Error>> {
+        let buffer = image::open(filename)?.into_rgb8();
+        let (width, height) = buffer.dimensions();
+
+        let mut img = Image::new(height as usize, width as usize);
+        img.with_encoding(encoding);
+
+        for pixel in buffer.pixels() {
+            let [r, g, b] = pixel.0;
+            img.push(encoding.decode(Color::new(r, g, b)));
+        }
+
+        Ok(img)
+    }
+
+    /// Reads the alpha channel of a file of `load`, row by row, `None` when
+    /// the format or the file has none
+    pub fn load_alpha(filename: &str) -> Result<Option<Vec<f64>>, Box<dyn Error>> {
+        if matches!(extension(filename).as_deref(), Some("ppm" | "pfm")) {
+            return Ok(None);
+        }
+
+        let image = image::open(filename)?;
+        if !image.color().has_alpha() {
+            return Ok(None);
+        }
+
+        let alpha = image
+            .into_rgba8()
+            .pixels()
+            .map(|pixel| pixel.0[3] as f64 / 255f64)
+            .collect();
+        Ok(Some(alpha))
+    }
+}
+
+/// Lowercase extension of `filename`
+fn extension(filename: &str) -> Option<String> {
+    Path::new(filename)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::color::{Color, Encoding};
+    use crate::img::{Format, Image};
+    use crate::vector::Vector;
+
+    #[test]
+    fn new() {
+        let image = Image::new(16, 16);
+        assert_eq!(image.pixels.capacity(), image.height * image.width)
+    }
+
+    #[test]
+    fn png_round_trip() {
+        let path = std::env::temp_dir().join("raytracer_png_round_trip.png");
+        let path = path.to_str().unwrap();
+
+        let mut img = Image::new(2, 2);
+        for c in [
+            Color::RED,
+            Color::new(3, 128, 250),
+            Color::WHITE,
+            Color::BLACK,
+        ] {
+            img.push(c.to_linear());
+        }
+        img.save_png(path).unwrap();
+
+        let loaded = Image::load_png(path, Encoding::Srgb).unwrap();
+        assert_eq!((loaded.height(), loaded.width()), (2, 2));
+        assert_eq!(loaded.pixels()[1].to_srgb().values(), (3, 128, 250));
+        assert_eq!(Image::load_alpha(path).unwrap(), None);
+    }
+
+    #[test]
+    fn load_alpha() {
+        let path = std::env::temp_dir().join("raytracer_load_alpha.png");
+        let path = path.to_str().unwrap();
+
+        let rgba = [255, 0, 0, 255, 0, 255, 0, 0];
+        image::save_buffer(path, &rgba, 2, 1, image::ColorType::Rgba8).unwrap();
+
+        assert_eq!(Image::load_alpha(path).unwrap(), Some(vec![1f64, 0f64]));
+        assert_eq!(Image::load_png(path, Encoding::Srgb).unwrap().width(), 2);
+    }
+
+    #[test]
+    fn format() {
+        assert_eq!(Format::from_extension("a/b.EXR").unwrap(), Format::Exr);
+        assert_eq!(Format::from_extension("b.hdr").unwrap(), Format::Hdr);
+        assert_eq!(Format::from_extension("b.pfm").unwrap(), Format::Pfm);
+        assert!(Format::from_extension("b.tiff").is_err());
+        assert!(Format::from_extension("b").is_err());
+
+        let path = std::env::temp_dir().join("raytracer_save_as.hdr");
+        let path = path.to_str().unwrap();
+        let mut img = Image::new(1, 1);
+        img.push(Color::WHITE);
+        img.save_as(path, Format::Hdr).unwrap();
+        assert!(image::open(path).is_ok());
+    }
+
+    #[test]
+    fn load_linear() {
+        // Highlights above white and linear values survive a round trip
+        let mut img = Image::new(1, 2);
+        img.push(Color::from(Vector::new(510f64, 255f64, 0f64)));
+        img.push(Color::from(Vector::new(64f64, 64f64, 64f64)));
+
+        for format in [Format::Hdr, Format::Exr] {
+            let path = std::env::temp_dir().join(format!("raytracer_load_linear.{:?}", format));
+            let path = path.to_str().unwrap().to_lowercase();
+            img.save_as(&path, format).unwrap();
+
+            let loaded = Image::load(&path, Encoding::Srgb).unwrap();
+            assert_eq!(loaded.pixels().len(), 2);
+            for (a, b) in loaded.pixels().iter().zip(img.pixels()) {
+                assert!((a.to_vec() - b.to_vec()).mag() < 1f64, "{:?}", format);
+            }
+        }
+    }
+}
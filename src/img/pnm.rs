@@ -0,0 +1,323 @@
+//! Binary PPM (P6) and PFM input / output
+use crate::color::{Color, Encoding};
+use crate::img::Image;
+use crate::vector::Vector;
+use std::error::Error;
+use std::fs;
+
+/// Splits the first `count` whitespace separated fields of a Netpbm header,
+/// skipping `#` comments.
+///
+/// Returns the fields and the offset of the raster, which starts after the
+/// single whitespace following the last field.
+fn header(bytes: &[u8], count: usize) -> Result<(Vec<String>, usize), Box<dyn Error>> {
+    let mut fields = Vec::with_capacity(count);
+    let mut pos = 0;
+
+    while fields.len() < count {
+        match bytes.get(pos) {
+            None => return Err("unexpected end of header".into()),
+            Some(b'#') => {
+                while pos < bytes.len() && bytes[pos] != b'\n' {
+                    pos += 1;
+                }
+            }
+            Some(c) if c.is_ascii_whitespace() => pos += 1,
+            Some(_) => {
+                let start = pos;
+                while pos < bytes.len() && !bytes[pos].is_ascii_whitespace() {
+                    pos += 1;
+                }
+                fields.push(String::from_utf8(bytes[start..pos].to_vec())?);
+            }
+        }
+    }
+
+    match bytes.get(pos) {
+        Some(c) if c.is_ascii_whitespace() => Ok((fields, pos + 1)),
+        _ => Err("missing whitespace after header".into()),
+    }
+}
+
+/// Bytes of a raster of `width * height` pixels of `sample_bytes` each,
+/// checked against overflows from malformed headers
+fn raster_size(width: usize, height: usize, sample_bytes: usize) -> Result<usize, Box<dyn Error>> {
+    width
+        .checked_mul(height)
+        .and_then(|pixels| pixels.checked_mul(sample_bytes))
+        .ok_or_else(|| format!("dimensions {}x{} too large", width, height).into())
+}
+
+/// Parses the `width height` fields of a header
+fn dimensions(fields: &[String]) -> Result<(usize, usize), Box<dyn Error>> {
+    let width = fields[1].parse::<usize>()?;
+    let height = fields[2].parse::<usize>()?;
+
+    if width == 0 || height == 0 {
+        return Err(format!("invalid dimensions {}x{}", width, height).into());
+    }
+
+    Ok((width, height))
+}
+
+impl Image {
+    /// Writes a binary PPM (P6), tone mapped and encoded like `save_png`
+    pub fn save(&self, filename: &str) -> Result<(), Box<dyn Error>> {
+        self.check_complete()?;
+
+        let mut content = format!("P6\n{} {}\n255\n", self.width, self.height).into_bytes();
+        content.extend(self.rgb8_bytes());
+
+        fs::write(filename, content)?;
+        Ok(())
+    }
+
+    /// Reads a binary PPM (P6) whose values were written with `encoding`
+    ///
+    /// Pixels are decoded back to the linear framebuffer, the returned image
+    /// keeps `encoding` so that `save` writes the same bytes.
+    pub fn load_ppm(filename: &str, encoding: Encoding) -> Result<Image, Box<dyn Error>> {
+        let bytes = fs::read(filename)?;
+        let (fields, offset) = header(&bytes, 4)?;
+
+        if fields[0] != "P6" {
+            return Err(format!("{}: not a binary PPM", filename).into());
+        }
+
+        let (width, height) = dimensions(&fields)?;
+        let max = fields[3].parse::<u16>()?;
+        if max == 0 {
+            return Err(format!("{}: invalid maximum value", filename).into());
+        }
+
+        // Samples are stored on two big endian bytes when max is above 255
+        let sample_size = if max > 255 { 2 } else { 1 };
+        let raster = &bytes[offset..];
+        if raster.len() < raster_size(width, height, 3 * sample_size)? {
+            return Err(format!("{}: truncated raster", filename).into());
+        }
+
+        let mut img = Image::new(height, width);
+        img.with_encoding(encoding);
+
+        for pixel in raster.chunks_exact(3 * sample_size).take(width * height) {
+            let sample = |i: usize| -> f64 {
+                let value = if sample_size == 2 {
+                    u16::from_be_bytes([pixel[2 * i], pixel[2 * i + 1]])
+                } else {
+                    pixel[i] as u16
+                };
+                value as f64 * 255f64 / max as f64
+            };
+
+            let c = Color::from(Vector::new(sample(0), sample(1), sample(2)));
+            img.push(encoding.decode(c));
+        }
+
+        Ok(img)
+    }
+
+    /// Writes the linear framebuffer as a little endian PFM, white being `1.0`
+    pub fn save_pfm(&self, filename: &str) -> Result<(), Box<dyn Error>> {
+        self.check_complete()?;
+
+        let mut content = format!("PF\n{} {}\n-1.0\n", self.width, self.height).into_bytes();
+        let linear = self.linear_pixels().collect::<Vec<_>>();
+
+        // PFM rows go from bottom to top
+        for row in linear.chunks(self.width).rev() {
+            for value in row.iter().flatten() {
+                content.extend(value.to_le_bytes());
+            }
+        }
+
+        fs::write(filename, content)?;
+        Ok(())
+    }
+
+    /// Reads a color (`PF`) or grayscale (`Pf`) PFM in either byte order
+    pub fn load_pfm(filename: &str) -> Result<Image, Box<dyn Error>> {
+        let bytes = fs::read(filename)?;
+        let (fields, offset) = header(&bytes, 4)?;
+
+        let channels = match fields[0].as_str() {
+            "PF" => 3,
+            "Pf" => 1,
+            _ => return Err(format!("{}: not a PFM", filename).into()),
+        };
+
+        let (width, height) = dimensions(&fields)?;
+        // The sign of the scale gives the byte order, its magnitude the value
+        // of `1.0`
+        let scale = fields[3].parse::<f64>()?;
+        let little_endian = scale < 0f64;
+        if scale == 0f64 || !scale.is_finite() {
+            return Err(format!("{}: invalid scale", filename).into());
+        }
+
+        let raster = &bytes[offset..];
+        if raster.len() < raster_size(width, height, channels * 4)? {
+            return Err(format!("{}: truncated raster", filename).into());
+        }
+
+        let values = raster
+            .chunks_exact(4)
+            .take(width * height * channels)
+            .map(|b| {
+                let b = [b[0], b[1], b[2], b[3]];
+                let value = if little_endian {
+                    f32::from_le_bytes(b)
+                } else {
+                    f32::from_be_bytes(b)
+                };
+                value as f64 * scale.abs() * 255f64
+            })
+            .collect::<Vec<_>>();
+
+        let mut img = Image::new(height, width);
+        for row in values.chunks(width * channels).rev() {
+            for pixel in row.chunks(channels) {
+                let v = if channels == 3 {
+                    Vector::new(pixel[0], pixel[1], pixel[2])
+                } else {
+                    Vector::new(pixel[0], pixel[0], pixel[0])
+                };
+                img.push(Color::from(v));
+            }
+        }
+
+        Ok(img)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::color::{Color, Encoding};
+    use crate::img::Image;
+    use crate::vector::Vector;
+    use std::fs;
+
+    fn path(name: &str) -> String {
+        let path = std::env::temp_dir().join(name);
+        String::from(path.to_str().unwrap())
+    }
+
+    fn gradient() -> Image {
+        let mut img = Image::new(3, 86);
+        for i in 0..(3 * 86) {
+            img.push(Color::new(i as u8, 255 - i as u8, 200));
+        }
+        img
+    }
+
+    #[test]
+    fn save_ppm_bytes() {
+        let path = path("raytracer_save_ppm_bytes.ppm");
+        let mut img = Image::new(1, 2);
+        img.with_encoding(Encoding::Linear);
+        img.push(Color::new(200, 128, 255));
+        img.push(Color::new(0, 10, 130));
+        img.save(&path).unwrap();
+
+        let mut expected = b"P6\n2 1\n255\n".to_vec();
+        expected.extend([200, 128, 255, 0, 10, 130]);
+        assert_eq!(fs::read(&path).unwrap(), expected);
+    }
+
+    #[test]
+    fn ppm_round_trip() {
+        let path = path("raytracer_ppm_round_trip.ppm");
+        let img = gradient();
+        img.save(&path).unwrap();
+        let before = fs::read(&path).unwrap();
+
+        let loaded = Image::load_ppm(&path, Encoding::Srgb).unwrap();
+        assert_eq!((loaded.height(), loaded.width()), (3, 86));
+        loaded.save(&path).unwrap();
+
+        assert_eq!(fs::read(&path).unwrap(), before);
+    }
+
+    #[test]
+    fn load_ppm_with_comments() {
+        let path = path("raytracer_load_ppm_comments.ppm");
+        let mut content = b"P6\n# comment\n1 1 # size\n255\n".to_vec();
+        content.extend([10, 20, 30]);
+        fs::write(&path, content).unwrap();
+
+        let img = Image::load_ppm(&path, Encoding::Linear).unwrap();
+        assert_eq!(img.pixels()[0].values(), (10, 20, 30));
+    }
+
+    #[test]
+    fn load_ppm_errors() {
+        let path = path("raytracer_load_ppm_errors.ppm");
+
+        fs::write(&path, b"P3\n1 1\n255\n0 0 0\n").unwrap();
+        assert!(Image::load_ppm(&path, Encoding::Srgb).is_err());
+
+        fs::write(&path, b"P6\n2 2\n255\n\x00\x00\x00").unwrap();
+        assert!(Image::load_ppm(&path, Encoding::Srgb).is_err());
+    }
+
+    #[test]
+    fn save_incomplete() {
+        let mut img = Image::new(2, 2);
+        img.push(Color::BLACK);
+
+        assert!(img.save(&path("raytracer_save_incomplete.ppm")).is_err());
+    }
+
+    #[test]
+    fn pfm_round_trip() {
+        let path = path("raytracer_pfm_round_trip.pfm");
+        let mut img = Image::new(2, 1);
+        img.push(Color::from(Vector::new(510f64, 0f64, 1f64)));
+        img.push(Color::from(Vector::new(0.25f64, 1e4f64, 255f64)));
+        img.save_pfm(&path).unwrap();
+
+        let loaded = Image::load_pfm(&path).unwrap();
+        assert_eq!((loaded.height(), loaded.width()), (2, 1));
+        for (a, b) in img.pixels().iter().zip(loaded.pixels()) {
+            let diff = (a.to_vec() - b.to_vec()).map(f64::abs).max();
+            assert!(diff < 1e-3, "{:?} != {:?}", a, b);
+        }
+    }
+
+    #[test]
+    fn load_pfm_big_endian() {
+        let path = path("raytracer_load_pfm_big_endian.pfm");
+        let mut content = b"Pf\n1 2\n1.0\n".to_vec();
+        content.extend(0.5f32.to_be_bytes());
+        content.extend(1f32.to_be_bytes());
+        fs::write(&path, content).unwrap();
+
+        let img = Image::load_pfm(&path).unwrap();
+        // Bottom row comes first
+        assert_eq!(img.pixels()[0].values(), (255, 255, 255));
+        assert_eq!(img.pixels()[1].values(), (128, 128, 128));
+    }
+
+    #[test]
+    fn load_pfm_scale() {
+        let path = path("raytracer_load_pfm_scale.pfm");
+        let mut content = b"Pf\n1 1\n-2.0\n".to_vec();
+        content.extend(0.25f32.to_le_bytes());
+        fs::write(&path, content).unwrap();
+
+        let img = Image::load_pfm(&path).unwrap();
+        assert_eq!(img.pixels()[0].to_vec().x, 127.5f64);
+    }
+
+    #[test]
+    fn oversized_header() {
+        let path = path("raytracer_oversized_header.ppm");
+        let huge = usize::MAX / 2;
+
+        fs::write(&path, format!("P6\n{} 3\n255\n\0", huge)).unwrap();
+        assert!(Image::load_ppm(&path, Encoding::Srgb).is_err());
+
+        fs::write(&path, format!("PF\n{} 3\n-1.0\n\0", huge)).unwrap();
+        assert!(Image::load_pfm(&path).is_err());
+    }
+}
@@ -9,6 +9,7 @@ pub trait LightTrait {
 }
 
 /// Abstract struct for Lighting
+#[allow(dead_code)]
 pub struct Light {}
 
 impl LightTrait for Light {
@@ -1,22 +1,40 @@
 use crate::camera::Camera;
-use crate::color::Color;
+use crate::color::{Color, Encoding};
+use crate::description::SceneDescription;
+use crate::img::Image;
 use crate::light::point::PointLight;
+use crate::material::Material;
 use crate::object::menger::Menger;
-use crate::object::turtle::Turtle;
 use crate::object::ObjectTrait;
 use crate::point::Point;
 use crate::scene::Scene;
+use crate::settings::RenderSettings;
 use crate::texture::uniform::UniformTexture;
 use crate::vector::Vector;
+use std::error::Error;
+use std::rc::Rc;
 
+mod bake;
+mod brdf;
 mod camera;
 mod color;
+mod description;
+mod expression;
+#[cfg(test)]
+mod golden;
 mod img;
 mod light;
+mod material;
+mod noise;
 mod object;
+mod palette;
 mod point;
+mod post;
 mod scene;
+mod settings;
 mod texture;
+mod tonemap;
+mod uv;
 mod vector;
 
 const OFFSET: f64 = 0f64;
@@ -27,18 +45,102 @@ const UP: Vector = Vector::new(0f64, 1f64, 0f64);
 
 const LIGHT_CENTER: Point = Point::new(-3f64, 2f64, 0f64 + OFFSET);
 
-fn main() {
+const USAGE: &str = "usage: raytracer [render [settings.json [scene.json]] \
+    | compare <a> <b> [heatmap] | bake <scene.json> <id> <texture.png> [size [lit]]]";
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+
+    match args.first().map(String::as_str) {
+        None => render(RenderSettings::default(), default_scene()),
+        Some("render") if args.len() == 1 => render(RenderSettings::default(), default_scene()),
+        Some("render") if args.len() == 2 => {
+            render(RenderSettings::from_file(&args[1])?, default_scene())
+        }
+        Some("render") if args.len() == 3 => render(
+            RenderSettings::from_file(&args[1])?,
+            SceneDescription::from_file(&args[2])?.build()?,
+        ),
+        Some("compare") => compare(&args[1..]),
+        Some("bake") => bake(&args[1..]),
+        Some(_) => Err(USAGE.into()),
+    }
+}
+
+/// Prints the differences between two images, optionally writing the
+/// heatmap of the errors
+fn compare(args: &[String]) -> Result<(), Box<dyn Error>> {
+    if args.len() < 2 || args.len() > 3 {
+        return Err(USAGE.into());
+    }
+
+    let a = Image::load(&args[0], Encoding::Srgb)?;
+    let b = Image::load(&args[1], Encoding::Srgb)?;
+    let comparison = img::compare::compare(&a, &b)?;
+
+    println!("channel  max error        MSE   PSNR (dB)");
+    let psnr = comparison.channel_psnr();
+    for (i, name) in ["R", "G", "B"].iter().enumerate() {
+        println!(
+            "{:<7}  {:>9}  {:>9.3}  {:>10.2}",
+            name, comparison.channel_max_errors[i], comparison.channel_mse[i], psnr[i]
+        );
+    }
+    println!(
+        "{:<7}  {:>9}  {:>9.3}  {:>10.2}",
+        "all", comparison.max_error, comparison.mse, comparison.psnr
+    );
+    println!("SSIM: {:.4}", comparison.ssim);
+
+    if let Some(path) = args.get(2) {
+        img::compare::heatmap(&a, &b)?.save_png(path)?;
+    }
+
+    Ok(())
+}
+
+/// Bakes the objects of a scene file with the given id into a square
+/// texture, lit by the lights of the scene with `lit`
+fn bake(args: &[String]) -> Result<(), Box<dyn Error>> {
+    if args.len() < 3 || args.len() > 5 {
+        return Err(USAGE.into());
+    }
+
+    let scene = SceneDescription::from_file(&args[0])?.build()?;
+    let size = args.get(3).map_or(Ok(512), |s| s.parse())?;
+    let lit = match args.get(4).map(String::as_str) {
+        None => false,
+        Some("lit") => true,
+        Some(_) => return Err(USAGE.into()),
+    };
+
+    let surfaces = scene
+        .objects
+        .iter()
+        .filter(|o| o.id() == &args[1])
+        .map(|o| o.as_ref())
+        .collect::<Vec<_>>();
+    if surfaces.is_empty() {
+        return Err(format!("no object `{}` to bake", args[1]).into());
+    }
+
+    let lights = lit.then_some(scene.lights.as_slice());
+    bake::bake(&surfaces, lights, size, size).save_png(&args[2])
+}
+
+/// Menger sponge lit from the left, rendered when no scene file is given
+fn default_scene() -> Scene {
     let menger = Menger::new(
         3,
         Point::new(0f64, 0f64, 0f64),
         Point::new(4f64, 4f64, 4f64),
-        Box::new(UniformTexture {
+        Rc::new(Material::new(Box::new(UniformTexture {
             kd: 1f64,
             ka: 0f64,
             ks: 0.1f64,
 
             color: Color::RED,
-        }),
+        }))),
         String::from("rect"),
     );
 
@@ -56,24 +158,29 @@ fn main() {
 
     // let turtle = Turtle::new(
     //     String::from("./grammar.json"),
-    //     Box::new(UniformTexture {
+    //     Rc::new(Material::new(Box::new(UniformTexture {
     //         kd: 1f64,
     //         ka: 0f64,
     //         ks: 0.1f64,
     //
     //         color: Color::WHITE,
-    //     }),
+    //     }))),
+    //     None,
+    //     None,
     //     String::from("turtle"),
     // );
 
     let objs: Vec<Box<dyn ObjectTrait>> = vec![Box::new(menger)];
 
-    let scene = Scene {
+    Scene {
         cam,
         lights: vec![Box::new(light)],
         objects: objs,
-    };
+    }
+}
 
-    let img = scene.image(1080 / 2, 1920 / 2);
-    img.save_png("output_0.png");
+fn render(settings: RenderSettings, scene: Scene) -> Result<(), Box<dyn Error>> {
+    let mut img = scene.image(settings.height, settings.width);
+    settings.apply(&mut img)?;
+    settings.save(&img)
 }
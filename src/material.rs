@@ -0,0 +1,275 @@
+//! Surface appearance shared between objects
+use crate::brdf::blinn_phong::BlinnPhong;
+use crate::brdf::cook_torrance::CookTorrance;
+use crate::brdf::Brdf;
+use crate::color::Color;
+use crate::texture::{ShadingContext, TextureTrait};
+use crate::uv::Uv;
+use crate::vector::Vector;
+
+/// Step heights are differentiated over, one texture unit being taken as one
+/// world unit
+const BUMP_DELTA: f64 = 1e-3;
+
+/// Scalar input of a material, constant or read from a texture
+pub enum Parameter {
+    Value(f64),
+    /// Luminance of the declared color of the texture
+    Texture(Box<dyn TextureTrait>),
+}
+
+impl Parameter {
+    pub fn value(&self, ctx: &ShadingContext) -> f64 {
+        match self {
+            Parameter::Value(v) => *v,
+            Parameter::Texture(t) => t.color(ctx).luminance(),
+        }
+    }
+}
+
+/// Perturbation of the shading normal
+pub enum Bump {
+    /// Heights read from the luminance of a texture, `strength` scaling the
+    /// slopes
+    Height {
+        texture: Box<dyn TextureTrait>,
+        strength: f64,
+    },
+    /// Tangent space normals, the channels mapping `[0, 255]` to `[-1, 1]`
+    /// along the `u` direction, the `v` direction and the normal
+    NormalMap(Box<dyn TextureTrait>),
+}
+
+/// Reflectance model of a material
+#[derive(Default)]
+pub enum Model {
+    /// Phong highlights, every term being weighted by the texture
+    /// coefficients
+    #[default]
+    Phong,
+    /// Normalized Blinn-Phong, the diffuse and specular colors being weighted
+    /// by the texture coefficients
+    BlinnPhong,
+    /// GGX microfacets, only the ambient term uses the texture coefficients
+    CookTorrance {
+        roughness: Parameter,
+        metallic: Parameter,
+    },
+}
+
+/// How a surface reacts to light
+///
+/// The texture gives the diffuse color and the diffuse, specular and ambient
+/// weights, the material decides how highlights look and how much light is
+/// reflected, transmitted or emitted.
+pub struct Material {
+    /// Diffuse color
+    pub texture: Box<dyn TextureTrait>,
+    /// Color of highlights, declared in sRGB, white keeping the light color
+    pub specular: Color,
+    /// Phong exponent, larger values give smaller highlights
+    pub shininess: f64,
+    /// Share of the color coming from the mirror direction
+    pub reflectivity: f64,
+    /// Share of the color coming from behind the surface
+    pub transparency: f64,
+    /// Light given off by the surface, declared in sRGB
+    pub emission: Color,
+    pub model: Model,
+    pub bump: Option<Bump>,
+}
+
+impl Material {
+    /// Opaque material showing `texture`, with white highlights and no
+    /// reflection
+    pub fn new(texture: Box<dyn TextureTrait>) -> Material {
+        Material {
+            texture,
+            specular: Color::WHITE,
+            shininess: 50f64,
+            reflectivity: 0f64,
+            transparency: 0f64,
+            emission: Color::BLACK,
+            model: Model::Phong,
+            bump: None,
+        }
+    }
+
+    /// Normal lighting is computed with at `ctx`, `tangents` being the
+    /// directions the texture coordinates increase along
+    pub fn shading_normal(
+        &self,
+        ctx: &ShadingContext,
+        (tangent, bitangent): (Vector, Vector),
+    ) -> Vector {
+        let normal = ctx.normal;
+        let Some(bump) = &self.bump else {
+            return normal;
+        };
+
+        // Tangents made orthogonal to the normal facing the viewer
+        let t = tangent - normal * (tangent * normal);
+        let b = bitangent - normal * (bitangent * normal);
+        if t.mag() < f64::EPSILON || b.mag() < f64::EPSILON {
+            return normal;
+        }
+        let t = t.normalize();
+        let b = (b - t * (b * t)).normalize();
+
+        match bump {
+            Bump::Height { texture, strength } => {
+                let height = |du: f64, dv: f64| {
+                    let shifted = ShadingContext {
+                        point: ctx.point + t * du + b * dv,
+                        uv: ctx.uv + Uv::new(du, dv),
+                        ..*ctx
+                    };
+                    texture.color(&shifted).luminance()
+                };
+
+                let h = height(0f64, 0f64);
+                let slope_u = (height(BUMP_DELTA, 0f64) - h) / BUMP_DELTA;
+                let slope_v = (height(0f64, BUMP_DELTA) - h) / BUMP_DELTA;
+                (normal - (t * slope_u + b * slope_v) * *strength).normalize()
+            }
+            Bump::NormalMap(texture) => {
+                let n = texture.color(ctx).to_vec() / 255f64 * 2f64 - 1f64;
+                (t * n.x + b * n.y + normal * n.z).normalize()
+            }
+        }
+    }
+
+    /// Reflectance at the point of `ctx`, `None` for `Model::Phong` which is
+    /// shaded by the scene from the texture coefficients
+    pub fn brdf(&self, ctx: &ShadingContext) -> Option<Box<dyn Brdf>> {
+        let albedo = self.texture.color(ctx).to_linear().to_vec() / 255f64;
+        let (kd, ks, _) = self.texture.coefficients(ctx);
+
+        match &self.model {
+            Model::Phong => None,
+            Model::BlinnPhong => Some(Box::new(BlinnPhong {
+                diffuse: albedo * kd,
+                specular: self.specular.to_linear().to_vec() / 255f64 * ks,
+                shininess: self.shininess,
+            })),
+            Model::CookTorrance {
+                roughness,
+                metallic,
+            } => Some(Box::new(CookTorrance {
+                albedo,
+                roughness: roughness.value(ctx).clamp(0f64, 1f64),
+                metallic: metallic.value(ctx).clamp(0f64, 1f64),
+            })),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::brdf::tests::{direction, NORMAL};
+    use crate::color::Color;
+    use crate::material::{Bump, Material, Model, Parameter};
+    use crate::point::Point;
+    use crate::texture::checker::CheckerTexture;
+    use crate::texture::gradient::{GradientKind, GradientTexture};
+    use crate::texture::uniform::UniformTexture;
+    use crate::texture::{Mapping, ShadingContext, TextureTrait};
+    use crate::vector::Vector;
+
+    fn uniform(color: Color) -> Box<dyn TextureTrait> {
+        Box::new(UniformTexture {
+            kd: 1f64,
+            ks: 0f64,
+            ka: 0f64,
+            color,
+        })
+    }
+
+    #[test]
+    fn phong_has_no_brdf() {
+        let material = Material::new(uniform(Color::WHITE));
+        assert!(material.brdf(&ShadingContext::at(Point::ZERO)).is_none());
+    }
+
+    #[test]
+    fn textured_roughness() {
+        // Polished on even cells, rough on odd ones
+        let material = Material {
+            model: Model::CookTorrance {
+                roughness: Parameter::Texture(Box::new(CheckerTexture {
+                    even: uniform(Color::BLACK),
+                    odd: uniform(Color::WHITE),
+                    scale: 1f64,
+                    mapping: Mapping::Solid,
+                })),
+                metallic: Parameter::Value(1f64),
+            },
+            ..Material::new(uniform(Color::WHITE))
+        };
+
+        let (wo, mirror) = (
+            direction(0.5f64, 0f64),
+            direction(0.5f64, std::f64::consts::PI),
+        );
+        let highlight = |p: Point| {
+            let brdf = material.brdf(&ShadingContext::at(p)).unwrap();
+            brdf.eval(NORMAL, wo, mirror).x
+        };
+
+        assert!(
+            highlight(Point::new(0.5f64, 0.5f64, 0.5f64))
+                > 10f64 * highlight(Point::new(1.5f64, 0.5f64, 0.5f64))
+        );
+    }
+
+    /// Context of the origin on the `z = 0` plane, seen from above
+    fn flat() -> ShadingContext<'static> {
+        ShadingContext {
+            normal: NORMAL,
+            ..ShadingContext::at(Point::ZERO)
+        }
+    }
+
+    const TANGENTS: (Vector, Vector) =
+        (Vector::new(1f64, 0f64, 0f64), Vector::new(0f64, 1f64, 0f64));
+
+    #[test]
+    fn height_slope() {
+        // Rises along `x`
+        let material = |strength: f64| Material {
+            bump: Some(Bump::Height {
+                texture: Box::new(GradientTexture {
+                    start: uniform(Color::BLACK),
+                    end: uniform(Color::WHITE),
+                    kind: GradientKind::Linear,
+                    origin: Point::new(-10f64, 0f64, 0f64),
+                    axis: Vector::new(20f64, 0f64, 0f64),
+                    mapping: Mapping::Solid,
+                }),
+                strength,
+            }),
+            ..Material::new(uniform(Color::WHITE))
+        };
+
+        assert_eq!(material(0f64).shading_normal(&flat(), TANGENTS), NORMAL);
+
+        // Tilted away from the slope
+        let n = material(20f64).shading_normal(&flat(), TANGENTS);
+        assert!(n.x < -0.1f64 && n.y.abs() < 1e-9 && n.z > 0f64, "{:?}", n);
+    }
+
+    #[test]
+    fn normal_map() {
+        let material = |color: Color| Material {
+            bump: Some(Bump::NormalMap(uniform(color))),
+            ..Material::new(uniform(Color::WHITE))
+        };
+
+        let n = material(Color::new(128, 128, 255)).shading_normal(&flat(), TANGENTS);
+        assert!((n - NORMAL).mag() < 0.01f64);
+
+        // Leaning toward `u`
+        let n = material(Color::new(255, 128, 128)).shading_normal(&flat(), TANGENTS);
+        assert!(n.x > 0.99f64);
+    }
+}
@@ -0,0 +1,103 @@
+//! Continuous, seedable noise functions and their fractal sums
+use crate::point::Point;
+use serde::{Deserialize, Serialize};
+
+pub mod perlin;
+pub mod worley;
+
+pub trait Noise {
+    /// Value at `p`, roughly in `[-1, 1]`
+    fn noise(&self, p: Point) -> f64;
+}
+
+/// How noise octaves are summed, each one being `lacunarity` times finer and
+/// `gain` times weaker than the previous
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Octaves {
+    pub octaves: usize,
+    pub lacunarity: f64,
+    pub gain: f64,
+}
+
+impl Default for Octaves {
+    fn default() -> Self {
+        Octaves {
+            octaves: 6,
+            lacunarity: 2f64,
+            gain: 0.5f64,
+        }
+    }
+}
+
+impl Octaves {
+    /// Sum of `f` applied to every octave, normalized by the total amplitude
+    fn sum(&self, noise: &dyn Noise, p: Point, f: fn(f64) -> f64) -> f64 {
+        let (mut frequency, mut amplitude) = (1f64, 1f64);
+        let (mut sum, mut total) = (0f64, 0f64);
+
+        for _ in 0..self.octaves {
+            sum += f(noise.noise(p * frequency)) * amplitude;
+            total += amplitude;
+            frequency *= self.lacunarity;
+            amplitude *= self.gain;
+        }
+
+        if total == 0f64 {
+            0f64
+        } else {
+            sum / total
+        }
+    }
+}
+
+/// Fractional Brownian motion, roughly in `[-1, 1]`
+pub fn fbm(noise: &dyn Noise, p: Point, octaves: Octaves) -> f64 {
+    octaves.sum(noise, p, |n| n)
+}
+
+/// Sum of absolute octaves, in `[0, 1]` and creased where the noise changes
+/// sign
+pub fn turbulence(noise: &dyn Noise, p: Point, octaves: Octaves) -> f64 {
+    octaves.sum(noise, p, f64::abs)
+}
+
+/// Hermite interpolation from `0` at `edge0` to `1` at `edge1`
+pub fn smoothstep(edge0: f64, edge1: f64, x: f64) -> f64 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0f64, 1f64);
+    t * t * (3f64 - 2f64 * t)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::noise::perlin::Perlin;
+    use crate::noise::{fbm, turbulence, Noise, Octaves};
+    use crate::point::Point;
+
+    #[test]
+    fn fractal_sums_are_bounded() {
+        let perlin = Perlin::new(3);
+        let octaves = Octaves::default();
+
+        for i in 0..500 {
+            let p = Point::new(i as f64 * 0.173f64, i as f64 * 0.031f64, 2.5f64);
+            assert!(fbm(&perlin, p, octaves).abs() <= 1f64);
+
+            let t = turbulence(&perlin, p, octaves);
+            assert!((0f64..=1f64).contains(&t));
+        }
+    }
+
+    #[test]
+    fn single_octave_is_the_noise() {
+        let perlin = Perlin::new(3);
+        let octaves = Octaves {
+            octaves: 1,
+            ..Octaves::default()
+        };
+        let p = Point::new(0.3f64, 0.7f64, 0.1f64);
+
+        assert_eq!(fbm(&perlin, p, octaves), perlin.noise(p));
+        assert_eq!(turbulence(&perlin, p, octaves), perlin.noise(p).abs());
+    }
+}
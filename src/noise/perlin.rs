@@ -0,0 +1,148 @@
+//! Improved Perlin gradient noise
+//!
+//! From Ken Perlin, "Improving Noise": lattice corners get one of 12 edge
+//! gradients through a permutation table, and their contributions are
+//! blended with a quintic fade curve so that the noise has continuous
+//! second derivatives.
+use crate::noise::Noise;
+use crate::point::Point;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+/// Perlin noise whose permutation table is shuffled from a seed
+#[derive(Debug, Clone)]
+pub struct Perlin {
+    /// Permutation of `0..256`, repeated so that lookups can overflow
+    permutation: Vec<usize>,
+}
+
+/// `6t^5 - 15t^4 + 10t^3`
+fn fade(t: f64) -> f64 {
+    t * t * t * (t * (t * 6f64 - 15f64) + 10f64)
+}
+
+fn lerp(t: f64, a: f64, b: f64) -> f64 {
+    a + t * (b - a)
+}
+
+/// Dot product of `(x, y, z)` with the gradient picked by `hash`
+fn gradient(hash: usize, x: f64, y: f64, z: f64) -> f64 {
+    let h = hash & 15;
+    let u = if h < 8 { x } else { y };
+    let v = if h < 4 {
+        y
+    } else if h == 12 || h == 14 {
+        x
+    } else {
+        z
+    };
+
+    (if h & 1 == 0 { u } else { -u }) + (if h & 2 == 0 { v } else { -v })
+}
+
+impl Perlin {
+    pub fn new(seed: u64) -> Perlin {
+        let mut table = (0..256).collect::<Vec<usize>>();
+        table.shuffle(&mut StdRng::seed_from_u64(seed));
+
+        Perlin {
+            permutation: table.iter().chain(table.iter()).copied().collect(),
+        }
+    }
+}
+
+impl Noise for Perlin {
+    /// In `[-1, 1]`, zero on integer coordinates
+    fn noise(&self, p: Point) -> f64 {
+        let cell = |v: f64| (v.floor() as i64).rem_euclid(256) as usize;
+        let (xi, yi, zi) = (cell(p.x), cell(p.y), cell(p.z));
+        let (x, y, z) = (p.x - p.x.floor(), p.y - p.y.floor(), p.z - p.z.floor());
+        let (u, v, w) = (fade(x), fade(y), fade(z));
+
+        let perm = &self.permutation;
+        let a = perm[xi] + yi;
+        let (aa, ab) = (perm[a] + zi, perm[a + 1] + zi);
+        let b = perm[xi + 1] + yi;
+        let (ba, bb) = (perm[b] + zi, perm[b + 1] + zi);
+
+        lerp(
+            w,
+            lerp(
+                v,
+                lerp(
+                    u,
+                    gradient(perm[aa], x, y, z),
+                    gradient(perm[ba], x - 1f64, y, z),
+                ),
+                lerp(
+                    u,
+                    gradient(perm[ab], x, y - 1f64, z),
+                    gradient(perm[bb], x - 1f64, y - 1f64, z),
+                ),
+            ),
+            lerp(
+                v,
+                lerp(
+                    u,
+                    gradient(perm[aa + 1], x, y, z - 1f64),
+                    gradient(perm[ba + 1], x - 1f64, y, z - 1f64),
+                ),
+                lerp(
+                    u,
+                    gradient(perm[ab + 1], x, y - 1f64, z - 1f64),
+                    gradient(perm[bb + 1], x - 1f64, y - 1f64, z - 1f64),
+                ),
+            ),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::noise::perlin::Perlin;
+    use crate::noise::Noise;
+    use crate::point::Point;
+    use crate::vector::Vector;
+
+    fn samples() -> impl Iterator<Item = Point> {
+        (0..1000).map(|i| {
+            let i = i as f64;
+            Point::new(i * 0.137f64 - 50f64, i * 0.071f64, i * -0.053f64)
+        })
+    }
+
+    #[test]
+    fn zero_on_lattice() {
+        let perlin = Perlin::new(1);
+        assert_eq!(perlin.noise(Point::new(3f64, -2f64, 7f64)), 0f64);
+    }
+
+    #[test]
+    fn bounded_and_varied() {
+        let perlin = Perlin::new(1);
+        let values = samples().map(|p| perlin.noise(p)).collect::<Vec<_>>();
+
+        assert!(values.iter().all(|v| v.abs() <= 1f64));
+        assert!(values.iter().any(|&v| v > 0.3f64));
+        assert!(values.iter().any(|&v| v < -0.3f64));
+    }
+
+    #[test]
+    fn continuous() {
+        let perlin = Perlin::new(1);
+        let step = Vector::new(1e-4, 1e-4, 1e-4);
+
+        for p in samples() {
+            assert!((perlin.noise(p) - perlin.noise(p + step)).abs() < 1e-2);
+        }
+    }
+
+    #[test]
+    fn seeded() {
+        let p = Point::new(0.5f64, 1.25f64, 2.75f64);
+
+        assert_eq!(Perlin::new(7).noise(p), Perlin::new(7).noise(p));
+        assert_ne!(Perlin::new(7).noise(p), Perlin::new(8).noise(p));
+    }
+}
@@ -0,0 +1,171 @@
+//! Worley cellular noise
+//!
+//! Space is split into unit cells holding one random feature point each,
+//! the noise being made of the distances to the closest feature points.
+use crate::point::Point;
+use serde::{Deserialize, Serialize};
+
+/// How distances to feature points are measured
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Metric {
+    #[default]
+    Euclidean,
+    Manhattan,
+    Chebyshev,
+}
+
+impl Metric {
+    pub fn distance(&self, a: Point, b: Point) -> f64 {
+        let d = a - b;
+        match self {
+            Metric::Euclidean => d.mag(),
+            Metric::Manhattan => d.x.abs() + d.y.abs() + d.z.abs(),
+            Metric::Chebyshev => d.x.abs().max(d.y.abs()).max(d.z.abs()),
+        }
+    }
+}
+
+/// Which distances make the noise value
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DistanceMode {
+    /// Distance to the closest feature point
+    #[default]
+    F1,
+    /// Distance to the second closest feature point
+    F2,
+    /// Difference of both, zero on the cell borders
+    F2MinusF1,
+}
+
+/// Distances of a point to the feature points around it
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Cells {
+    pub f1: f64,
+    pub f2: f64,
+    /// Cell of the closest feature point
+    pub cell: (i64, i64, i64),
+}
+
+impl Cells {
+    pub fn value(&self, mode: DistanceMode) -> f64 {
+        match mode {
+            DistanceMode::F1 => self.f1,
+            DistanceMode::F2 => self.f2,
+            DistanceMode::F2MinusF1 => self.f2 - self.f1,
+        }
+    }
+}
+
+/// SplitMix64 finalizer
+fn mix(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+/// Worley noise whose feature points are placed from a seed
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Worley {
+    pub seed: u64,
+    pub metric: Metric,
+}
+
+impl Worley {
+    pub fn new(seed: u64, metric: Metric) -> Worley {
+        Worley { seed, metric }
+    }
+
+    /// Random value identifying `cell`
+    pub fn hash(&self, cell: (i64, i64, i64)) -> u64 {
+        let (x, y, z) = cell;
+        [x, y, z].iter().fold(mix(self.seed), |h, &c| {
+            mix(h ^ (c as u64).wrapping_add(0x9e3779b97f4a7c15))
+        })
+    }
+
+    /// Feature point of `cell`
+    pub fn feature(&self, cell: (i64, i64, i64)) -> Point {
+        let h = self.hash(cell);
+        let unit = |h: u64| (mix(h) >> 11) as f64 / (1u64 << 53) as f64;
+
+        Point::new(
+            cell.0 as f64 + unit(h),
+            cell.1 as f64 + unit(h ^ 1),
+            cell.2 as f64 + unit(h ^ 2),
+        )
+    }
+
+    /// Distances of `p` to the feature points of its cell and the 26 around
+    pub fn cells(&self, p: Point) -> Cells {
+        let (cx, cy, cz) = (p.x.floor() as i64, p.y.floor() as i64, p.z.floor() as i64);
+        let mut cells = Cells {
+            f1: f64::INFINITY,
+            f2: f64::INFINITY,
+            cell: (cx, cy, cz),
+        };
+
+        for dx in -1..=1 {
+            for dy in -1..=1 {
+                for dz in -1..=1 {
+                    let cell = (cx + dx, cy + dy, cz + dz);
+                    let d = self.metric.distance(p, self.feature(cell));
+
+                    if d < cells.f1 {
+                        cells.f2 = cells.f1;
+                        cells.f1 = d;
+                        cells.cell = cell;
+                    } else if d < cells.f2 {
+                        cells.f2 = d;
+                    }
+                }
+            }
+        }
+
+        cells
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::noise::worley::{DistanceMode, Metric, Worley};
+    use crate::point::Point;
+
+    #[test]
+    fn feature_points() {
+        let worley = Worley::new(4, Metric::Euclidean);
+        let feature = worley.feature((2, -1, 0));
+
+        assert_eq!(
+            (feature.x.floor(), feature.y.floor(), feature.z.floor()),
+            (2f64, -1f64, 0f64)
+        );
+        assert_eq!(
+            feature,
+            Worley::new(4, Metric::Euclidean).feature((2, -1, 0))
+        );
+        assert_ne!(
+            feature,
+            Worley::new(5, Metric::Euclidean).feature((2, -1, 0))
+        );
+
+        let cells = worley.cells(feature);
+        assert_eq!(cells.f1, 0f64);
+        assert_eq!(cells.cell, (2, -1, 0));
+    }
+
+    #[test]
+    fn distances() {
+        let p = Point::new(0.3f64, 4.7f64, -2.2f64);
+        let value = |metric, mode| Worley::new(1, metric).cells(p).value(mode);
+
+        let f1 = value(Metric::Euclidean, DistanceMode::F1);
+        let f2 = value(Metric::Euclidean, DistanceMode::F2);
+        assert!(f1 <= f2);
+        assert_eq!(value(Metric::Euclidean, DistanceMode::F2MinusF1), f2 - f1);
+
+        assert!(value(Metric::Manhattan, DistanceMode::F1) >= f1);
+        assert!(value(Metric::Chebyshev, DistanceMode::F1) <= f1);
+    }
+}
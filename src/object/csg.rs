@@ -0,0 +1,346 @@
+//! Constructive solid geometry
+use crate::material::Material;
+use crate::object::{
+    Boundary, GetMaterial, Intersect, Interval, Level, Normal, ObjectId, ObjectTrait, Solid,
+    TextureCoordinates,
+};
+use crate::uv::{Footprint, Uv};
+use crate::{Point, Vector};
+use serde::{Deserialize, Serialize};
+use std::cell::{Cell, RefCell};
+
+/// Boolean operation combining the insides of two solids
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Operation {
+    Union,
+    Intersection,
+    /// Inside of the left solid outside of the right one
+    Difference,
+}
+
+impl Operation {
+    fn inside(self, left: bool, right: bool) -> bool {
+        match self {
+            Operation::Union => left || right,
+            Operation::Intersection => left && right,
+            Operation::Difference => left && !right,
+        }
+    }
+
+    /// Intervals inside the combination of the intervals of two solids, the
+    /// parts of `right` being offset by `offset`
+    pub fn combine(self, left: &[Interval], right: &[Interval], offset: usize) -> Vec<Interval> {
+        let mut events = Vec::with_capacity(2 * (left.len() + right.len()));
+        for interval in left {
+            events.push((interval.enter, true, true));
+            events.push((interval.exit, true, false));
+        }
+        for interval in right {
+            // Carved surfaces face the inside of the right solid
+            let side = |boundary: Boundary| Boundary {
+                normal: match self {
+                    Operation::Difference => -boundary.normal,
+                    _ => boundary.normal,
+                },
+                part: boundary.part + offset,
+                ..boundary
+            };
+            events.push((side(interval.enter), false, true));
+            events.push((side(interval.exit), false, false));
+        }
+        // Entries first on ties, so that touching solids are merged
+        events.sort_by(|(a, _, a_enter), (b, _, b_enter)| {
+            a.t.total_cmp(&b.t).then(b_enter.cmp(a_enter))
+        });
+
+        let (mut in_left, mut in_right) = (false, false);
+        let mut enter = None;
+        let mut intervals = Vec::new();
+        for (boundary, is_left, is_enter) in events {
+            let was_inside = self.inside(in_left, in_right);
+            if is_left {
+                in_left = is_enter;
+            } else {
+                in_right = is_enter;
+            }
+
+            match (was_inside, self.inside(in_left, in_right)) {
+                (false, true) => enter = Some(boundary),
+                (true, false) => {
+                    let enter = enter.take().unwrap();
+                    if boundary.t > enter.t {
+                        intervals.push(Interval {
+                            enter,
+                            exit: boundary,
+                        });
+                    }
+                }
+                _ => {}
+            }
+        }
+        intervals
+    }
+}
+
+/// Solid made of the inside of `left` and `right` combined by `operation`,
+/// its surfaces keeping the materials of the solids they come from
+pub struct Csg {
+    pub operation: Operation,
+    pub left: Box<dyn Solid>,
+    pub right: Box<dyn Solid>,
+    pub id: String,
+
+    /// Points and boundaries of the latest intersection
+    hits: RefCell<Vec<(Point, Boundary)>>,
+    /// Primitive of the closest of these points
+    latest_hit: Cell<usize>,
+}
+
+impl Csg {
+    pub fn new(
+        operation: Operation,
+        left: Box<dyn Solid>,
+        right: Box<dyn Solid>,
+        id: String,
+    ) -> Csg {
+        Csg {
+            operation,
+            left,
+            right,
+            id,
+            hits: RefCell::new(Vec::new()),
+            latest_hit: Cell::new(0),
+        }
+    }
+
+    /// Boundary of the latest intersection at `p`
+    fn boundary(&self, p: Point) -> Option<Boundary> {
+        self.hits
+            .borrow()
+            .iter()
+            .min_by(|(a, _), (b, _)| (*a - p).mag().total_cmp(&(*b - p).mag()))
+            .map(|&(_, boundary)| boundary)
+    }
+
+    /// Primitive of the surface at `p`
+    fn part_at(&self, p: Point) -> &dyn ObjectTrait {
+        self.part(self.boundary(p).map_or(0, |boundary| boundary.part))
+    }
+}
+
+impl Solid for Csg {
+    fn intervals(&self, p: Point, v: Vector) -> Vec<Interval> {
+        self.operation.combine(
+            &self.left.intervals(p, v),
+            &self.right.intervals(p, v),
+            self.left.parts(),
+        )
+    }
+
+    fn parts(&self) -> usize {
+        self.left.parts() + self.right.parts()
+    }
+
+    fn part(&self, index: usize) -> &dyn ObjectTrait {
+        let left = self.left.parts();
+        if index < left {
+            self.left.part(index)
+        } else {
+            self.right.part(index - left)
+        }
+    }
+}
+
+impl Intersect for Csg {
+    fn is_intersect(&self, p: Point, v: Vector) -> bool {
+        !self.intersect_points(p, v).is_empty()
+    }
+
+    fn intersect_points(&self, p: Point, v: Vector) -> Vec<Point> {
+        let hits = self
+            .intervals(p, v)
+            .into_iter()
+            .flat_map(|interval| [interval.enter, interval.exit])
+            .filter(|boundary| boundary.t > 0f64)
+            .map(|boundary| (p + v * boundary.t, boundary))
+            .collect::<Vec<_>>();
+
+        // Skipping the surface rays start from, like the scene does
+        if let Some(&(_, closest)) = hits.iter().find(|(hit, _)| (*hit - p).mag() > 0.000001f64) {
+            self.latest_hit.set(closest.part);
+        }
+
+        let points = hits.iter().map(|&(hit, _)| hit).collect();
+        self.hits.replace(hits);
+        points
+    }
+}
+
+impl Normal for Csg {
+    fn normal(&self, p: Point) -> Vector {
+        match self.boundary(p) {
+            Some(boundary) => boundary.normal,
+            None => self.left.normal(p),
+        }
+    }
+
+    fn tangents(&self, p: Point) -> (Vector, Vector) {
+        self.part_at(p).tangents(p)
+    }
+}
+
+impl TextureCoordinates for Csg {
+    fn uv(&self, p: Point) -> Uv {
+        self.part_at(p).uv(p)
+    }
+
+    fn footprint(&self, p: Point, px: Point, py: Point) -> Footprint {
+        self.part_at(p).footprint(p, px, py)
+    }
+}
+
+impl Level for Csg {
+    fn level(&self) -> usize {
+        self.part(self.latest_hit.get()).level()
+    }
+}
+
+impl GetMaterial for Csg {
+    fn material(&self) -> &Material {
+        self.part(self.latest_hit.get()).material()
+    }
+}
+
+impl ObjectId for Csg {
+    fn id(&self) -> &String {
+        &self.id
+    }
+}
+
+impl ObjectTrait for Csg {}
+
+#[cfg(test)]
+mod tests {
+    use crate::material::Material;
+    use crate::object::csg::{Csg, Operation};
+    use crate::object::menger::Menger;
+    use crate::object::sphere::Sphere;
+    use crate::object::{GetMaterial, Intersect, Normal, Solid};
+    use crate::texture::ShadingContext;
+    use crate::{Color, Point, UniformTexture, Vector};
+    use std::rc::Rc;
+
+    fn material(color: Color) -> Rc<Material> {
+        Rc::new(Material::new(Box::new(UniformTexture {
+            kd: 1f64,
+            ks: 0f64,
+            ka: 0f64,
+            color,
+        })))
+    }
+
+    fn sphere(x: f64, color: Color) -> Box<dyn Solid> {
+        Box::new(Sphere {
+            p: Point::new(x, 0f64, 0f64),
+            r: 1f64,
+            material: material(color),
+            id: String::from("sphere"),
+        })
+    }
+
+    /// Distances along the x axis from `-10` where the line is inside `csg`
+    fn spans(csg: &Csg) -> Vec<(f64, f64)> {
+        csg.intervals(
+            Point::new(-10f64, 0f64, 0f64),
+            Vector::new(1f64, 0f64, 0f64),
+        )
+        .iter()
+        .map(|i| (i.enter.t, i.exit.t))
+        .collect()
+    }
+
+    #[test]
+    fn operations() {
+        let csg = |operation| {
+            Csg::new(
+                operation,
+                sphere(0f64, Color::RED),
+                sphere(1f64, Color::BLUE),
+                String::from("csg"),
+            )
+        };
+
+        assert_eq!(spans(&csg(Operation::Union)), vec![(9f64, 12f64)]);
+        assert_eq!(spans(&csg(Operation::Intersection)), vec![(10f64, 11f64)]);
+        assert_eq!(spans(&csg(Operation::Difference)), vec![(9f64, 10f64)]);
+
+        // Solids touching at a point are merged
+        let touching = Csg::new(
+            Operation::Union,
+            sphere(0f64, Color::RED),
+            sphere(2f64, Color::BLUE),
+            String::from("csg"),
+        );
+        assert_eq!(spans(&touching), vec![(9f64, 13f64)]);
+    }
+
+    #[test]
+    fn carved_surfaces() {
+        let csg = Csg::new(
+            Operation::Difference,
+            sphere(0f64, Color::RED),
+            sphere(1f64, Color::BLUE),
+            String::from("csg"),
+        );
+        let origin = Point::new(3f64, 0f64, 0f64);
+        let v = Vector::new(-1f64, 0f64, 0f64);
+
+        // Through the hole carved by the right sphere, out of the left one
+        let hits = csg.intersect_points(origin, v);
+        assert_eq!(
+            hits,
+            vec![Point::new(0f64, 0f64, 0f64), Point::new(-1f64, 0f64, 0f64)]
+        );
+        assert_eq!(csg.normal(hits[0]), Vector::new(1f64, 0f64, 0f64));
+        assert_eq!(csg.normal(hits[1]), Vector::new(-1f64, 0f64, 0f64));
+        let ctx = ShadingContext::at(hits[0]);
+        assert_eq!(csg.material().texture.color(&ctx), Color::BLUE);
+    }
+
+    #[test]
+    fn carved_menger() {
+        let menger = Menger::new(
+            2,
+            Point::new(0f64, 0f64, 0f64),
+            Point::new(9f64, 9f64, 9f64),
+            material(Color::RED),
+            String::from("menger"),
+        );
+        let cutter = Sphere {
+            p: Point::new(0f64, 0f64, 0f64),
+            r: 2f64,
+            material: material(Color::BLUE),
+            id: String::from("cutter"),
+        };
+        let csg = Csg::new(
+            Operation::Difference,
+            Box::new(menger),
+            Box::new(cutter),
+            String::from("csg"),
+        );
+
+        // The first row of cubes is solid, from the carved sphere to the far
+        // side
+        let origin = Point::new(-1f64, 0.5f64, 0.5f64);
+        let v = Vector::new(1f64, 0f64, 0f64);
+        let hits = csg.intersect_points(origin, v);
+        let x = 3.5f64.sqrt();
+        assert_eq!(hits.len(), 2);
+        assert!((hits[0].x - x).abs() < 1e-12);
+        assert_eq!(hits[1], Point::new(9f64, 0.5f64, 0.5f64));
+        // Facing the center of the sphere
+        assert!(csg.normal(hits[0]).x < 0f64);
+        assert_eq!(csg.normal(hits[1]), Vector::new(1f64, 0f64, 0f64));
+    }
+}
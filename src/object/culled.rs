@@ -0,0 +1,113 @@
+//! Objects seen from the front only
+use crate::material::Material;
+use crate::object::{
+    GetMaterial, Intersect, Level, Normal, ObjectId, ObjectTrait, TextureCoordinates,
+};
+use crate::uv::{Footprint, Uv};
+use crate::{Point, Vector};
+
+/// `object` with back face culling: rays coming from behind its surface,
+/// the side its normal points away from, go through it
+pub struct Culled {
+    pub object: Box<dyn ObjectTrait>,
+}
+
+impl Intersect for Culled {
+    fn is_intersect(&self, p: Point, v: Vector) -> bool {
+        !self.intersect_points(p, v).is_empty()
+    }
+
+    fn intersect_points(&self, p: Point, v: Vector) -> Vec<Point> {
+        self.object
+            .intersect_points(p, v)
+            .into_iter()
+            .filter(|&hit| self.object.normal(hit) * v < 0f64)
+            .collect()
+    }
+}
+
+impl Normal for Culled {
+    fn normal(&self, p: Point) -> Vector {
+        self.object.normal(p)
+    }
+
+    fn tangents(&self, p: Point) -> (Vector, Vector) {
+        self.object.tangents(p)
+    }
+}
+
+impl TextureCoordinates for Culled {
+    fn uv(&self, p: Point) -> Uv {
+        self.object.uv(p)
+    }
+
+    fn footprint(&self, p: Point, px: Point, py: Point) -> Footprint {
+        self.object.footprint(p, px, py)
+    }
+
+    fn surface_point(&self, uv: Uv) -> Option<Point> {
+        self.object.surface_point(uv)
+    }
+}
+
+impl Level for Culled {
+    fn level(&self) -> usize {
+        self.object.level()
+    }
+}
+
+impl GetMaterial for Culled {
+    fn material(&self) -> &Material {
+        self.object.material()
+    }
+
+    fn back_material(&self) -> &Material {
+        self.object.back_material()
+    }
+}
+
+impl ObjectId for Culled {
+    fn id(&self) -> &String {
+        self.object.id()
+    }
+}
+
+impl ObjectTrait for Culled {}
+
+#[cfg(test)]
+mod tests {
+    use crate::material::Material;
+    use crate::object::culled::Culled;
+    use crate::object::triangle::Triangle;
+    use crate::object::Intersect;
+    use crate::{Color, Point, UniformTexture, Vector};
+    use std::rc::Rc;
+
+    #[test]
+    fn back_rays_go_through() {
+        let material = Rc::new(Material::new(Box::new(UniformTexture {
+            kd: 1f64,
+            ks: 0f64,
+            ka: 0f64,
+            color: Color::WHITE,
+        })));
+        // Normal toward -z
+        let triangle = Triangle::new(
+            Point::new(0f64, 0f64, 1f64),
+            Point::new(0f64, 1f64, 1f64),
+            Point::new(1f64, 0f64, 1f64),
+            material,
+            String::from("triangle"),
+        );
+        let culled = Culled {
+            object: Box::new(triangle),
+        };
+
+        let origin = Point::new(0.2f64, 0.2f64, 0f64);
+        let front = culled.intersect_points(origin, Vector::new(0f64, 0f64, 1f64));
+        assert_eq!(front, vec![Point::new(0.2f64, 0.2f64, 1f64)]);
+
+        let behind = Point::new(0.2f64, 0.2f64, 2f64);
+        assert!(!culled.is_intersect(behind, Vector::new(0f64, 0f64, -1f64)));
+    }
+}
@@ -1,8 +1,13 @@
+use crate::material::Material;
 use crate::object::rect::RectangleInner;
-use crate::object::{GetTexture, Intersect, Normal, ObjectId, ObjectTrait};
-use crate::texture::TextureTrait;
+use crate::object::{
+    GetMaterial, Intersect, Interval, Level, Normal, ObjectId, ObjectTrait, Solid,
+    TextureCoordinates,
+};
+use crate::uv::{Footprint, Uv};
 use crate::{Point, Vector};
 use std::cell::RefCell;
+use std::rc::Rc;
 
 #[derive(Debug)]
 pub struct MengerRec {
@@ -47,12 +52,53 @@ impl MengerRec {
             latest_hit: RefCell::new(None),
         }
     }
+
+    /// Intervals of the smallest sub cubes, merged where they touch
+    pub fn intervals(&self, p: Point, v: Vector) -> Vec<Interval> {
+        self.latest_hit.replace(None);
+
+        let Some(interval) = self.rect.interval(p, v) else {
+            return Vec::new();
+        };
+        if self.sub_menger.is_empty() {
+            return vec![interval];
+        }
+
+        let mut intervals = self
+            .sub_menger
+            .iter()
+            .flat_map(|sub| sub.intervals(p, v))
+            .collect::<Vec<_>>();
+        intervals.sort_by(|a, b| a.enter.t.total_cmp(&b.enter.t));
+
+        let mut merged: Vec<Interval> = Vec::new();
+        for interval in intervals {
+            match merged.last_mut() {
+                Some(last) if interval.enter.t <= last.exit.t + 0.0000001f64 => {
+                    if interval.exit.t > last.exit.t {
+                        last.exit = interval.exit;
+                    }
+                }
+                _ => merged.push(interval),
+            }
+        }
+        merged
+    }
+
+    /// Normal at `p` of the smallest sub cube holding it, found from the
+    /// geometry rather than from the latest hit
+    fn surface_normal(&self, p: Point) -> Vector {
+        match self.sub_menger.iter().find(|sub| sub.rect.contains(p)) {
+            Some(sub) => sub.surface_normal(p),
+            None => self.rect.normal(p),
+        }
+    }
 }
 
 /// Object
 pub struct Menger {
     pub menger: MengerRec,
-    pub texture: Box<dyn TextureTrait>,
+    pub material: Rc<Material>,
     pub id: String,
 }
 
@@ -61,7 +107,7 @@ impl Menger {
         rec_count: usize,
         p1: Point,
         p2: Point,
-        texture: Box<dyn TextureTrait>,
+        material: Rc<Material>,
         id: String,
     ) -> Menger {
         let mut xs = [p1.x, p2.x];
@@ -77,7 +123,7 @@ impl Menger {
         Menger {
             menger: MengerRec::new(rec_count, p1, p2),
             id,
-            texture,
+            material,
         }
     }
 }
@@ -89,6 +135,8 @@ impl Intersect for MengerRec {
     }
 
     fn intersect_points(&self, p: Point, v: Vector) -> Vec<Point> {
+        self.latest_hit.replace(None);
+
         let inter = self.rect.intersect_points(p, v);
         if inter.is_empty() || self.sub_menger.is_empty() {
             return inter;
@@ -117,10 +165,21 @@ impl Intersect for MengerRec {
 
 impl Normal for MengerRec {
     fn normal(&self, p: Point) -> Vector {
-        if let Some(index) = self.latest_hit.take() {
+        if let Some(index) = *self.latest_hit.borrow() {
             self.sub_menger[index].normal(p)
         } else {
-            self.rect.normal(p)
+            // No sub cube was hit last after `intervals`
+            self.surface_normal(p)
+        }
+    }
+}
+
+impl Level for MengerRec {
+    /// Recursion depth of the sub cube hit last
+    fn level(&self) -> usize {
+        match *self.latest_hit.borrow() {
+            Some(index) => 1 + self.sub_menger[index].level(),
+            None => 0,
         }
     }
 }
@@ -139,11 +198,34 @@ impl Normal for Menger {
     fn normal(&self, p: Point) -> Vector {
         self.menger.normal(p)
     }
+
+    fn tangents(&self, p: Point) -> (Vector, Vector) {
+        self.menger.rect.face_tangents(self.menger.normal(p))
+    }
+}
+
+impl TextureCoordinates for Menger {
+    /// Faces of the outer cube, so that textures continue across sub cubes
+    fn uv(&self, p: Point) -> Uv {
+        self.menger.rect.face_uv(p, self.menger.normal(p))
+    }
+
+    fn footprint(&self, p: Point, px: Point, py: Point) -> Footprint {
+        self.menger
+            .rect
+            .face_footprint(p, px, py, self.menger.normal(p))
+    }
+}
+
+impl Level for Menger {
+    fn level(&self) -> usize {
+        self.menger.level()
+    }
 }
 
-impl GetTexture for Menger {
-    fn texture(&self) -> &Box<dyn TextureTrait> {
-        &self.texture
+impl GetMaterial for Menger {
+    fn material(&self) -> &Material {
+        &self.material
     }
 }
 
@@ -155,10 +237,23 @@ impl ObjectId for Menger {
 
 impl ObjectTrait for Menger {}
 
+impl Solid for Menger {
+    fn intervals(&self, p: Point, v: Vector) -> Vec<Interval> {
+        self.menger.intervals(p, v)
+    }
+
+    fn part(&self, _index: usize) -> &dyn ObjectTrait {
+        self
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::object::Intersect;
+    use crate::material::Material;
+    use crate::object::menger::Menger;
+    use crate::object::{Intersect, Level};
     use crate::{Color, Point, UniformTexture, Vector};
+    use std::rc::Rc;
 
     const UNIFORM_TEXTURE: UniformTexture = UniformTexture {
         kd: 1f64,
@@ -167,4 +262,26 @@ mod tests {
 
         color: Color::BLACK,
     };
+
+    #[test]
+    fn level() {
+        let menger = Menger::new(
+            2,
+            Point::new(0f64, 0f64, 0f64),
+            Point::new(3f64, 3f64, 3f64),
+            Rc::new(Material::new(Box::new(UNIFORM_TEXTURE))),
+            String::from("menger"),
+        );
+        let v = Vector::new(0f64, 0f64, 1f64);
+
+        let hits = menger.intersect_points(Point::new(0.1f64, 0.1f64, -1f64), v);
+        assert_eq!(hits[0], Point::new(0.1f64, 0.1f64, 0f64));
+        assert_eq!(menger.level(), 2);
+
+        // Through the central hole
+        assert!(menger
+            .intersect_points(Point::new(1.5f64, 1.5f64, -1f64), v)
+            .is_empty());
+        assert_eq!(menger.level(), 0);
+    }
 }
@@ -1,4 +1,6 @@
 //! Object representation
+pub mod csg;
+pub mod culled;
 pub mod menger;
 pub mod plane;
 pub mod rect;
@@ -6,8 +8,11 @@ pub mod sphere;
 pub mod triangle;
 pub mod turtle;
 
+use crate::brdf::frame;
+use crate::material::Material;
 use crate::point::Point;
-use crate::texture::TextureTrait;
+use crate::texture::{ShadingContext, ALPHA_CUTOFF};
+use crate::uv::{Footprint, Uv};
 use crate::vector::Vector;
 
 pub trait ObjectId {
@@ -25,16 +30,127 @@ pub trait Intersect {
 pub trait Normal {
     /// Returns the normal at `p` point
     fn normal(&self, p: Point) -> Vector;
+
+    /// Directions the `u` and `v` texture coordinates increase along at `p`,
+    /// any frame around the normal by default
+    fn tangents(&self, p: Point) -> (Vector, Vector) {
+        frame(self.normal(p))
+    }
+}
+
+pub trait TextureCoordinates {
+    /// Returns the texture coordinates at `p` point
+    fn uv(&self, p: Point) -> Uv;
+
+    /// Texture coordinate differences between `p` and `px`, `py`, the
+    /// points of the surface seen through the neighbouring pixels
+    fn footprint(&self, p: Point, px: Point, py: Point) -> Footprint {
+        let uv = self.uv(p);
+        Footprint {
+            dx: self.uv(px) - uv,
+            dy: self.uv(py) - uv,
+        }
+    }
+
+    /// Point of the surface at `uv`, the inverse of `uv` used to bake
+    /// textures, `None` where the surface has no point or cannot be unwrapped
+    fn surface_point(&self, _uv: Uv) -> Option<Point> {
+        None
+    }
+}
+
+pub trait Level {
+    /// Subdivision level of the part of the object hit last, `0` for simple
+    /// primitives
+    fn level(&self) -> usize {
+        0
+    }
 }
 
-pub trait GetTexture {
-    fn texture(&self) -> &Box<dyn TextureTrait>;
+pub trait GetMaterial {
+    /// Returns the material of the part of the object hit last
+    fn material(&self) -> &Material;
+
+    /// Returns the material of the back of the part of the object hit last,
+    /// the same as the front unless the surface is two sided
+    fn back_material(&self) -> &Material {
+        self.material()
+    }
 }
 
 /// SuperTrait for objects
-pub trait ObjectTrait: Intersect + Normal + GetTexture + ObjectId {}
+pub trait ObjectTrait:
+    Intersect + Normal + TextureCoordinates + Level + GetMaterial + ObjectId
+{
+}
+
+/// Crossing of the surface of a solid by a line
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Boundary {
+    /// Distance along the line, as a multiple of its direction
+    pub t: f64,
+    /// Normal pointing out of the solid
+    pub normal: Vector,
+    /// Primitive the surface belongs to, see `Solid::part`
+    pub part: usize,
+}
+
+/// Part of a line inside a solid
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Interval {
+    pub enter: Boundary,
+    pub exit: Boundary,
+}
+
+/// Closed objects, which constructive solid geometry combines by their
+/// inside rather than by their surface
+pub trait Solid: ObjectTrait {
+    /// Sorted and disjoint intervals of the whole line through `p` along
+    /// `v` inside the solid, distances behind `p` being negative
+    fn intervals(&self, p: Point, v: Vector) -> Vec<Interval>;
+
+    /// Number of primitives the surfaces of the solid come from
+    fn parts(&self) -> usize {
+        1
+    }
+
+    /// Primitive `index` of the surfaces, the solid itself unless it
+    /// combines others
+    fn part(&self, index: usize) -> &dyn ObjectTrait;
+}
+
+/// Normal at `p` turned toward the origin of a ray going along `v`, with the
+/// material of the side of the surface the ray hits
+pub fn facing(obj: &dyn ObjectTrait, p: Point, v: Vector) -> (Vector, &Material) {
+    let normal = obj.normal(p);
+    if normal * v > 0f64 {
+        (-normal, obj.back_material())
+    } else {
+        (normal, obj.material())
+    }
+}
+
+/// Whether `obj` is solid at `p` for a ray going along `v` rather than cut
+/// out by the alpha of its texture, `obj` having just been intersected
+pub fn opaque(obj: &dyn ObjectTrait, p: Point, v: Vector) -> bool {
+    let (normal, material) = facing(obj, p, v);
+    let texture = &material.texture;
+    if !texture.has_alpha() {
+        return true;
+    }
+
+    let ctx = ShadingContext {
+        normal,
+        uv: obj.uv(p),
+        id: obj.id(),
+        level: obj.level(),
+        ..ShadingContext::at(p)
+    };
+    texture.alpha(&ctx) >= ALPHA_CUTOFF
+}
 
 /// Object
+#[allow(dead_code)]
 pub struct Object {}
 
 impl Intersect for Object {
@@ -52,8 +168,16 @@ impl Normal for Object {
     }
 }
 
-impl GetTexture for Object {
-    fn texture(&self) -> &Box<dyn TextureTrait> {
+impl TextureCoordinates for Object {
+    fn uv(&self, _p: Point) -> Uv {
+        unimplemented!()
+    }
+}
+
+impl Level for Object {}
+
+impl GetMaterial for Object {
+    fn material(&self) -> &Material {
         unimplemented!()
     }
 }
@@ -1,16 +1,37 @@
-use crate::object::{GetTexture, Intersect, Normal, ObjectId, ObjectTrait};
-use crate::texture::TextureTrait;
+use crate::material::Material;
+use crate::object::{
+    GetMaterial, Intersect, Level, Normal, ObjectId, ObjectTrait, TextureCoordinates,
+};
+use crate::uv::Uv;
 use crate::{Point, Vector};
+use std::rc::Rc;
 
 /// Object
 pub struct Plane {
     pub p: Point,
     pub normal: Vector,
 
-    pub texture: Box<dyn TextureTrait>,
+    pub material: Rc<Material>,
+    /// Material seen from the side the normal points away from
+    pub back: Option<Rc<Material>>,
     pub id: String,
 }
 
+impl Plane {
+    /// Orthonormal `u` and `v` directions lying in the plane
+    fn basis(&self) -> (Vector, Vector) {
+        let normal = self.normal.normalize();
+        let axis = if normal.x.abs() > 0.9f64 {
+            Vector::new(0f64, 1f64, 0f64)
+        } else {
+            Vector::new(1f64, 0f64, 0f64)
+        };
+
+        let u = axis.cross_product(&normal).normalize();
+        (u, normal.cross_product(&u))
+    }
+}
+
 impl Intersect for Plane {
     fn is_intersect(&self, _p: Point, v: Vector) -> bool {
         const NULL_VEC: Vector = Vector::new(0f64, 0f64, 0f64);
@@ -30,7 +51,7 @@ impl Intersect for Plane {
         if t <= 0f64 {
             return Vec::new();
         }
-        return vec![p + v * t];
+        vec![p + v * t]
     }
 }
 
@@ -38,11 +59,30 @@ impl Normal for Plane {
     fn normal(&self, _p: Point) -> Vector {
         self.normal
     }
+
+    fn tangents(&self, _p: Point) -> (Vector, Vector) {
+        self.basis()
+    }
 }
 
-impl GetTexture for Plane {
-    fn texture(&self) -> &Box<dyn TextureTrait> {
-        &self.texture
+impl TextureCoordinates for Plane {
+    /// Distances from `self.p` along the plane basis, textures repeat every unit
+    fn uv(&self, p: Point) -> Uv {
+        let (u, v) = self.basis();
+        let d = p - self.p;
+        Uv::new(d * u, d * v)
+    }
+}
+
+impl Level for Plane {}
+
+impl GetMaterial for Plane {
+    fn material(&self) -> &Material {
+        &self.material
+    }
+
+    fn back_material(&self) -> &Material {
+        self.back.as_deref().unwrap_or(&self.material)
     }
 }
 
@@ -56,9 +96,12 @@ impl ObjectTrait for Plane {}
 
 #[cfg(test)]
 mod tests {
+    use crate::material::Material;
     use crate::object::plane::Plane;
-    use crate::object::Intersect;
+    use crate::object::{Intersect, Normal, TextureCoordinates};
+    use crate::uv::Uv;
     use crate::{Color, Point, UniformTexture, Vector};
+    use std::rc::Rc;
 
     const UNIFORM_TEXTURE: UniformTexture = UniformTexture {
         kd: 1f64,
@@ -70,26 +113,28 @@ mod tests {
 
     fn plane() -> Plane {
         Plane {
-            texture: Box::new(UNIFORM_TEXTURE),
+            material: Rc::new(Material::new(Box::new(UNIFORM_TEXTURE))),
+            back: None,
             normal: Vector::new(1f64, 0f64, 0f64),
             p: Point::new(0f64, 0f64, 0f64),
-            id: "plane",
+            id: String::from("plane"),
         }
     }
 
     #[test]
     fn intersect() {
         let plane = Plane {
-            texture: Box::new(UNIFORM_TEXTURE),
+            material: Rc::new(Material::new(Box::new(UNIFORM_TEXTURE))),
+            back: None,
             normal: Vector::new(2f64, 3f64, 6f64),
             p: Point::new(1f64, 5f64, 3f64),
-            id: "plane",
+            id: String::from("plane"),
         };
 
         let (p, v) = (Point::new(0f64, 0f64, 0f64), Vector::new(1f64, 0f64, 0f64));
 
         let is_intersect = plane.is_intersect(p, v);
-        assert_eq!(true, is_intersect);
+        assert!(is_intersect);
 
         let intersect_points = plane.intersect_points(p, v);
         assert_ne!(intersect_points.len(), 0);
@@ -102,8 +147,9 @@ mod tests {
         let plane = Plane {
             normal: Vector::new(0f64, 1f64, 0f64),
             p: Point::new(0f64, -2f64, 0f64),
-            id: "plane1",
-            texture: Box::new(UNIFORM_TEXTURE),
+            id: String::from("plane1"),
+            material: Rc::new(Material::new(Box::new(UNIFORM_TEXTURE))),
+            back: None,
         };
 
         let (p, v) = (
@@ -128,7 +174,7 @@ mod tests {
         let (p, v) = (Point::new(0f64, 0f64, 0f64), Vector::new(0f64, 1f64, 0f64));
 
         let is_intersect = plane.is_intersect(p, v);
-        assert_eq!(false, is_intersect);
+        assert!(!is_intersect);
 
         let intersect_points = plane.intersect_points(p, v);
         assert_eq!(intersect_points.len(), 0)
@@ -141,9 +187,30 @@ mod tests {
         let (p, v) = (Point::new(0f64, 0f64, 0f64), Vector::new(0f64, 0f64, 0f64));
 
         let is_intersect = plane.is_intersect(p, v);
-        assert_eq!(false, is_intersect);
+        assert!(!is_intersect);
 
         let intersect_points = plane.intersect_points(p, v);
         assert_eq!(intersect_points.len(), 0)
     }
+
+    #[test]
+    fn uv() {
+        let plane = Plane {
+            normal: Vector::new(0f64, 1f64, 0f64),
+            ..plane()
+        };
+
+        assert_eq!(plane.uv(Point::new(0f64, 0f64, 0f64)), Uv::new(0f64, 0f64));
+
+        assert_eq!(plane.uv(Point::new(2f64, 0f64, 3f64)), Uv::new(3f64, 2f64));
+    }
+
+    #[test]
+    fn tangents_follow_uv() {
+        let plane = plane();
+        let (t, b) = plane.tangents(Point::ZERO);
+
+        assert_eq!(plane.uv(t), Uv::new(1f64, 0f64));
+        assert_eq!(plane.uv(b), Uv::new(0f64, 1f64));
+    }
 }
@@ -1,6 +1,11 @@
-use crate::object::{GetTexture, Intersect, Normal, ObjectId, ObjectTrait};
-use crate::texture::TextureTrait;
+use crate::material::Material;
+use crate::object::{
+    Boundary, GetMaterial, Intersect, Interval, Level, Normal, ObjectId, ObjectTrait, Solid,
+    TextureCoordinates,
+};
+use crate::uv::{Footprint, Uv};
 use crate::{Point, Vector};
+use std::rc::Rc;
 
 #[derive(Debug)]
 pub struct RectangleInner {
@@ -20,43 +25,75 @@ impl RectangleInner {
             size: (p2 - p1).map(|x| x.abs()) * 0.5,
         }
     }
-}
 
-/// Object
-pub struct Rectangle {
-    pub rect: RectangleInner,
+    /// Texture coordinates of `p` on the face of `normal`, each face
+    /// covering `[0, 1]` and seen unmirrored from outside the box
+    pub fn face_uv(&self, p: Point, normal: Vector) -> Uv {
+        let relative = |value: f64, min: f64, max: f64| {
+            if max > min {
+                (value - min) / (max - min)
+            } else {
+                0f64
+            }
+        };
+        let x = relative(p.x, self.p_min.x, self.p_max.x);
+        let y = relative(p.y, self.p_min.y, self.p_max.y);
+        let z = relative(p.z, self.p_min.z, self.p_max.z);
+
+        if normal.x.abs() >= normal.y.abs() && normal.x.abs() >= normal.z.abs() {
+            Uv::new(if normal.x > 0f64 { 1f64 - z } else { z }, y)
+        } else if normal.y.abs() >= normal.z.abs() {
+            Uv::new(x, if normal.y > 0f64 { 1f64 - z } else { z })
+        } else {
+            Uv::new(if normal.z > 0f64 { 1f64 - x } else { x }, y)
+        }
+    }
 
-    pub texture: Box<dyn TextureTrait>,
-    pub id: String,
-}
+    /// Directions `face_uv` increases along on the face of `normal`
+    pub fn face_tangents(&self, normal: Vector) -> (Vector, Vector) {
+        let (x, y, z) = (
+            Vector::new(1f64, 0f64, 0f64),
+            Vector::new(0f64, 1f64, 0f64),
+            Vector::new(0f64, 0f64, 1f64),
+        );
+        let flip = |v: Vector, positive: bool| if positive { -v } else { v };
 
-impl Rectangle {
-    pub fn new(p1: Point, p2: Point, texture: Box<dyn TextureTrait>, id: String) -> Rectangle {
-        let mut xs = [p1.x, p2.x];
-        let mut ys = [p1.y, p2.y];
-        let mut zs = [p1.z, p2.z];
-        xs.sort_by(|&a, &b| a.partial_cmp(&b).unwrap());
-        ys.sort_by(|&a, &b| a.partial_cmp(&b).unwrap());
-        zs.sort_by(|&a, &b| a.partial_cmp(&b).unwrap());
+        if normal.x.abs() >= normal.y.abs() && normal.x.abs() >= normal.z.abs() {
+            (flip(z, normal.x > 0f64), y)
+        } else if normal.y.abs() >= normal.z.abs() {
+            (x, flip(z, normal.y > 0f64))
+        } else {
+            (flip(x, normal.z > 0f64), y)
+        }
+    }
 
-        let p1 = Point::new(xs[0], ys[0], zs[0]);
-        let p2 = Point::new(xs[1], ys[1], zs[1]);
+    /// Whether `p` is inside the box or on its surface
+    pub fn contains(&self, p: Point) -> bool {
+        const K_EPSILON: f64 = 0.0000001f64;
 
-        Rectangle {
-            rect: RectangleInner::new(p1, p2),
-            texture,
-            id,
-        }
+        let inside = |x: f64, min: f64, max: f64| min - K_EPSILON <= x && x <= max + K_EPSILON;
+        inside(p.x, self.p_min.x, self.p_max.x)
+            && inside(p.y, self.p_min.y, self.p_max.y)
+            && inside(p.z, self.p_min.z, self.p_max.z)
     }
-}
 
-impl Intersect for RectangleInner {
-    fn is_intersect(&self, p: Point, v: Vector) -> bool {
-        const NULL_VEC: Vector = Vector::new(0f64, 0f64, 0f64);
-        v != NULL_VEC && !self.intersect_points(p, v).is_empty()
+    /// Interval of the line through `p` along `v` inside the box
+    pub fn interval(&self, p: Point, v: Vector) -> Option<Interval> {
+        let (tmin, tmax) = self.slabs(p, v)?;
+        let boundary = |t: f64| Boundary {
+            t,
+            normal: self.normal(p + v * t),
+            part: 0,
+        };
+        Some(Interval {
+            enter: boundary(tmin),
+            exit: boundary(tmax),
+        })
     }
 
-    fn intersect_points(&self, p: Point, v: Vector) -> Vec<Point> {
+    /// Distances along `v` where the line through `p` enters and leaves the
+    /// box
+    fn slabs(&self, p: Point, v: Vector) -> Option<(f64, f64)> {
         // https://www.scratchapixel.com/lessons/3d-basic-rendering/minimal-ray-tracer-rendering-simple-shapes/ray-box-intersection
 
         let invdir = 1f64 / v;
@@ -73,7 +110,7 @@ impl Intersect for RectangleInner {
         let tymax = (aabb[1 - sign[1]].y - p.y) * invdir.y;
 
         if (tmin > tymax) || (tymin > tmax) {
-            return Vec::new();
+            return None;
         }
         if tymin > tmin {
             tmin = tymin;
@@ -86,7 +123,7 @@ impl Intersect for RectangleInner {
         let tzmax = (aabb[1 - sign[2]].z - p.z) * invdir.z;
 
         if (tmin > tzmax) || (tzmin > tmax) {
-            return Vec::new();
+            return None;
         }
         if tzmin > tmin {
             tmin = tzmin;
@@ -95,6 +132,58 @@ impl Intersect for RectangleInner {
             tmax = tzmax;
         }
 
+        Some((tmin, tmax))
+    }
+
+    /// Footprint of `p` on the face of `normal`
+    pub fn face_footprint(&self, p: Point, px: Point, py: Point, normal: Vector) -> Footprint {
+        let uv = self.face_uv(p, normal);
+        Footprint {
+            dx: self.face_uv(px, normal) - uv,
+            dy: self.face_uv(py, normal) - uv,
+        }
+    }
+}
+
+/// Object
+pub struct Rectangle {
+    pub rect: RectangleInner,
+
+    pub material: Rc<Material>,
+    pub id: String,
+}
+
+impl Rectangle {
+    pub fn new(p1: Point, p2: Point, material: Rc<Material>, id: String) -> Rectangle {
+        let mut xs = [p1.x, p2.x];
+        let mut ys = [p1.y, p2.y];
+        let mut zs = [p1.z, p2.z];
+        xs.sort_by(|&a, &b| a.partial_cmp(&b).unwrap());
+        ys.sort_by(|&a, &b| a.partial_cmp(&b).unwrap());
+        zs.sort_by(|&a, &b| a.partial_cmp(&b).unwrap());
+
+        let p1 = Point::new(xs[0], ys[0], zs[0]);
+        let p2 = Point::new(xs[1], ys[1], zs[1]);
+
+        Rectangle {
+            rect: RectangleInner::new(p1, p2),
+            material,
+            id,
+        }
+    }
+}
+
+impl Intersect for RectangleInner {
+    fn is_intersect(&self, p: Point, v: Vector) -> bool {
+        const NULL_VEC: Vector = Vector::new(0f64, 0f64, 0f64);
+        v != NULL_VEC && !self.intersect_points(p, v).is_empty()
+    }
+
+    fn intersect_points(&self, p: Point, v: Vector) -> Vec<Point> {
+        let Some((tmin, tmax)) = self.slabs(p, v) else {
+            return Vec::new();
+        };
+
         if tmin > 0f64 && tmax > 0f64 {
             vec![p + v * tmin, p + v * tmax]
         } else if tmax > 0f64 {
@@ -132,11 +221,28 @@ impl Normal for Rectangle {
     fn normal(&self, p: Point) -> Vector {
         self.rect.normal(p)
     }
+
+    fn tangents(&self, p: Point) -> (Vector, Vector) {
+        self.rect.face_tangents(self.rect.normal(p))
+    }
+}
+
+impl TextureCoordinates for Rectangle {
+    fn uv(&self, p: Point) -> Uv {
+        self.rect.face_uv(p, self.rect.normal(p))
+    }
+
+    /// `px` and `py` may lie off the box, they are mapped on the face of `p`
+    fn footprint(&self, p: Point, px: Point, py: Point) -> Footprint {
+        self.rect.face_footprint(p, px, py, self.rect.normal(p))
+    }
 }
 
-impl GetTexture for Rectangle {
-    fn texture(&self) -> &Box<dyn TextureTrait> {
-        &self.texture
+impl Level for Rectangle {}
+
+impl GetMaterial for Rectangle {
+    fn material(&self) -> &Material {
+        &self.material
     }
 }
 
@@ -148,9 +254,23 @@ impl ObjectId for Rectangle {
 
 impl ObjectTrait for Rectangle {}
 
+impl Solid for Rectangle {
+    fn intervals(&self, p: Point, v: Vector) -> Vec<Interval> {
+        self.rect.interval(p, v).into_iter().collect()
+    }
+
+    fn part(&self, _index: usize) -> &dyn ObjectTrait {
+        self
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::{object::Intersect, point::Point, vector::Vector, Color, UniformTexture};
+    use crate::material::Material;
+    use crate::object::{Intersect, TextureCoordinates};
+    use crate::uv::Uv;
+    use crate::{point::Point, vector::Vector, Color, UniformTexture};
+    use std::rc::Rc;
 
     use super::Rectangle;
 
@@ -163,12 +283,12 @@ mod tests {
     };
 
     fn rect() -> Rectangle {
-        return Rectangle::new(
+        Rectangle::new(
             Point::new(0f64, 0f64, 0f64),
             Point::new(10f64, 10f64, 10f64),
-            Box::new(UNIFORM_TEXTURE),
+            Rc::new(Material::new(Box::new(UNIFORM_TEXTURE))),
             String::from("rect"),
-        );
+        )
     }
 
     #[test]
@@ -181,4 +301,26 @@ mod tests {
         dbg!(&points);
         assert_eq!(points.len(), 2)
     }
+
+    #[test]
+    fn uv() {
+        let rect = rect();
+
+        assert_eq!(
+            rect.uv(Point::new(0f64, 5f64, 2f64)),
+            Uv::new(0.2f64, 0.5f64)
+        );
+        assert_eq!(
+            rect.uv(Point::new(10f64, 5f64, 2f64)),
+            Uv::new(0.8f64, 0.5f64)
+        );
+        assert_eq!(
+            rect.uv(Point::new(3f64, 10f64, 4f64)),
+            Uv::new(0.3f64, 0.6f64)
+        );
+        assert_eq!(
+            rect.uv(Point::new(3f64, 6f64, 0f64)),
+            Uv::new(0.3f64, 0.6f64)
+        );
+    }
 }
@@ -1,12 +1,19 @@
-use crate::object::{GetTexture, Intersect, Normal, ObjectId, ObjectTrait};
+use crate::brdf::frame;
+use crate::material::Material;
+use crate::object::{
+    Boundary, GetMaterial, Intersect, Interval, Level, Normal, ObjectId, ObjectTrait, Solid,
+    TextureCoordinates,
+};
 use crate::point::Point;
-use crate::texture::{LightCoefficients, TextureTrait};
+use crate::uv::{Footprint, Uv};
 use crate::vector::Vector;
+use std::f64::consts::PI;
+use std::rc::Rc;
 
 pub struct Sphere {
     pub p: Point,
     pub r: f64,
-    pub texture: Box<dyn TextureTrait>,
+    pub material: Rc<Material>,
     pub id: String,
 }
 
@@ -76,17 +83,60 @@ impl Normal for Sphere {
         }
         .normalize()
     }
+
+    /// Along the parallel and the meridian of `p`
+    fn tangents(&self, p: Point) -> (Vector, Vector) {
+        let n = self.normal(p);
+        let tangent = Vector::new(-n.z, 0f64, n.x);
+        if tangent.mag() < f64::EPSILON {
+            // Longitude is undefined at the poles
+            return frame(n);
+        }
+
+        let tangent = tangent.normalize();
+        (tangent, tangent.cross_product(&n))
+    }
 }
 
-impl LightCoefficients for Sphere {
-    fn coefficients(&self, point: Point) -> (f64, f64, f64) {
-        self.texture.coefficients(point)
+impl TextureCoordinates for Sphere {
+    /// Longitude and latitude, `v` going up from the south pole
+    fn uv(&self, p: Point) -> Uv {
+        let n = self.normal(p);
+        Uv::new(
+            0.5f64 + n.z.atan2(n.x) / (2f64 * PI),
+            0.5f64 + n.y.clamp(-1f64, 1f64).asin() / PI,
+        )
+    }
+
+    /// Longitude differences are wrapped so the seam keeps a small footprint
+    fn footprint(&self, p: Point, px: Point, py: Point) -> Footprint {
+        let wrap = |d: Uv| Uv::new(d.u - d.u.round(), d.v);
+        let uv = self.uv(p);
+        Footprint {
+            dx: wrap(self.uv(px) - uv),
+            dy: wrap(self.uv(py) - uv),
+        }
+    }
+
+    /// Point of longitude and latitude `uv`, texture coordinates wrapping
+    /// around the sphere
+    fn surface_point(&self, uv: Uv) -> Option<Point> {
+        let longitude = (uv.u - 0.5f64) * 2f64 * PI;
+        let latitude = (uv.v.clamp(0f64, 1f64) - 0.5f64) * PI;
+        let n = Vector::new(
+            longitude.cos() * latitude.cos(),
+            latitude.sin(),
+            longitude.sin() * latitude.cos(),
+        );
+        Some(self.p + n * self.r)
     }
 }
 
-impl GetTexture for Sphere {
-    fn texture(&self) -> &Box<dyn TextureTrait> {
-        &self.texture
+impl Level for Sphere {}
+
+impl GetMaterial for Sphere {
+    fn material(&self) -> &Material {
+        &self.material
     }
 }
 
@@ -98,14 +148,43 @@ impl ObjectId for Sphere {
 
 impl ObjectTrait for Sphere {}
 
+impl Solid for Sphere {
+    fn intervals(&self, p: Point, v: Vector) -> Vec<Interval> {
+        let (a, b, c) = self.intersect_coeff(p, v);
+        let delta = b * b - 4f64 * a * c;
+        // Grazing lines have no inside
+        if delta <= 0f64 {
+            return Vec::new();
+        }
+
+        let boundary = |t: f64| Boundary {
+            t,
+            normal: self.normal(p + v * t),
+            part: 0,
+        };
+        let t_sqrt = delta.sqrt();
+        vec![Interval {
+            enter: boundary((-b - t_sqrt) / (2f64 * a)),
+            exit: boundary((-b + t_sqrt) / (2f64 * a)),
+        }]
+    }
+
+    fn part(&self, _index: usize) -> &dyn ObjectTrait {
+        self
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::color::Color;
+    use crate::material::Material;
     use crate::object::sphere::Sphere;
-    use crate::object::Intersect;
+    use crate::object::{Intersect, Normal, TextureCoordinates};
     use crate::point::Point;
     use crate::texture::uniform::UniformTexture;
+    use crate::uv::Uv;
     use crate::vector::Vector;
+    use std::rc::Rc;
 
     const UNIFORM_TEXTURE: UniformTexture = UniformTexture {
         kd: 1f64,
@@ -119,7 +198,7 @@ mod tests {
         Sphere {
             p: Point::new(4f64, 5f64, 6f64),
             r: 1f64,
-            texture: Box::new(UNIFORM_TEXTURE),
+            material: Rc::new(Material::new(Box::new(UNIFORM_TEXTURE))),
             id: String::from("First"),
         }
     }
@@ -130,7 +209,7 @@ mod tests {
         let ray = Vector::new(1f64, 1f64, 1f64);
         let is_intersect = sphere().is_intersect(point, ray);
 
-        assert_eq!(true, is_intersect)
+        assert!(is_intersect)
     }
 
     #[test]
@@ -139,7 +218,7 @@ mod tests {
         let ray = Vector::new(1f64, 1f64, 1f64);
         let is_intersect = sphere().is_intersect(point, ray);
 
-        assert_eq!(true, is_intersect)
+        assert!(is_intersect)
     }
 
     #[test]
@@ -148,7 +227,7 @@ mod tests {
         let ray = Vector::new(1f64, 1f64, 1.5f64);
         let is_intersect = sphere().is_intersect(point, ray);
 
-        assert_eq!(true, is_intersect)
+        assert!(is_intersect)
     }
 
     #[test]
@@ -202,7 +281,7 @@ mod tests {
         let ray = Vector::new(1f64, 1f64, 1f64);
         let is_intersect = sphere().is_intersect(point, ray);
 
-        assert_eq!(false, is_intersect)
+        assert!(!is_intersect)
     }
 
     #[test]
@@ -211,7 +290,7 @@ mod tests {
         let ray = Vector::new(1f64, 1f64, 1f64);
         let is_intersect = sphere().is_intersect(point, ray);
 
-        assert_eq!(false, is_intersect)
+        assert!(!is_intersect)
     }
 
     #[test]
@@ -220,7 +299,7 @@ mod tests {
         let ray = Vector::new(1f64, 1f64, 1f64);
         let is_intersect = sphere().is_intersect(point, ray);
 
-        assert_eq!(false, is_intersect)
+        assert!(!is_intersect)
     }
 
     #[test]
@@ -228,14 +307,55 @@ mod tests {
         let sphere = Sphere {
             p: Point::new(5f64, 0f64, 5f64),
             r: 1f64,
-            texture: Box::new(UNIFORM_TEXTURE),
-            id: "First",
+            material: Rc::new(Material::new(Box::new(UNIFORM_TEXTURE))),
+            id: String::from("First"),
         };
 
         let point = Point::new(0f64, 0f64, 0f64);
         let ray = Vector::new(1f64, 1f64, 1f64);
         let is_intersect = sphere.is_intersect(point, ray);
 
-        assert_eq!(false, is_intersect)
+        assert!(!is_intersect)
+    }
+
+    #[test]
+    fn uv() {
+        let sphere = sphere();
+
+        assert_eq!(
+            sphere.uv(Point::new(5f64, 5f64, 6f64)),
+            Uv::new(0.5f64, 0.5f64)
+        );
+        assert_eq!(sphere.uv(Point::new(4f64, 6f64, 6f64)).v, 1f64);
+        assert_eq!(sphere.uv(Point::new(4f64, 4f64, 6f64)).v, 0f64);
+        assert_eq!(sphere.uv(Point::new(4f64, 5f64, 7f64)).u, 0.75f64);
+    }
+
+    #[test]
+    fn surface_point() {
+        let sphere = sphere();
+        let uv = Uv::new(0.3f64, 0.8f64);
+        let p = sphere.surface_point(uv).unwrap();
+
+        assert!(((p - sphere.p).mag() - sphere.r).abs() < 1e-12);
+        let back = sphere.uv(p);
+        assert!((back.u - uv.u).abs() < 1e-12 && (back.v - uv.v).abs() < 1e-12);
+    }
+
+    #[test]
+    fn tangents_follow_uv() {
+        let sphere = sphere();
+        let p = sphere.p + Vector::new(1f64, 1f64, -1f64).normalize();
+        let (t, b) = sphere.tangents(p);
+        let n = sphere.normal(p);
+
+        assert!((t * n).abs() < 1e-12 && (b * n).abs() < 1e-12);
+        assert!((t * b).abs() < 1e-12);
+
+        // Small steps along the tangents increase `u` and `v`
+        let step = |d: Vector| sphere.uv(sphere.p + (p + d * 1e-4 - sphere.p).normalize());
+        let uv = sphere.uv(p);
+        assert!(step(t).u > uv.u && (step(t).v - uv.v).abs() < 1e-6);
+        assert!(step(b).v > uv.v && (step(b).u - uv.u).abs() < 1e-6);
     }
 }
@@ -1,11 +1,18 @@
-use crate::object::{GetTexture, Intersect, Normal, ObjectId, ObjectTrait};
-use crate::texture::TextureTrait;
+use crate::brdf::frame;
+use crate::material::Material;
+use crate::object::{
+    GetMaterial, Intersect, Level, Normal, ObjectId, ObjectTrait, TextureCoordinates,
+};
+use crate::uv::Uv;
 use crate::{Point, Vector};
+use std::rc::Rc;
 
 /// Object
 pub struct Triangle {
     pub p1: Point,
+    #[allow(dead_code)]
     pub p2: Point,
+    #[allow(dead_code)]
     pub p3: Point,
 
     pub edge1: Vector,
@@ -13,18 +20,17 @@ pub struct Triangle {
 
     pub normal: Vector,
 
-    pub texture: Box<dyn TextureTrait>,
+    /// Texture coordinates of `p1`, `p2` and `p3`
+    pub uvs: [Uv; 3],
+
+    pub material: Rc<Material>,
+    /// Material seen from the side the normal points away from
+    pub back: Option<Rc<Material>>,
     pub id: String,
 }
 
 impl Triangle {
-    pub fn new(
-        p1: Point,
-        p2: Point,
-        p3: Point,
-        texture: Box<dyn TextureTrait>,
-        id: String,
-    ) -> Triangle {
+    pub fn new(p1: Point, p2: Point, p3: Point, material: Rc<Material>, id: String) -> Triangle {
         let edge1 = p2 - p1;
         let edge2 = p3 - p1;
 
@@ -35,11 +41,46 @@ impl Triangle {
             edge1,
             edge2,
             normal: edge1.cross_product(&edge2).normalize(),
-            texture,
+            uvs: [
+                Uv::new(0f64, 0f64),
+                Uv::new(1f64, 0f64),
+                Uv::new(0f64, 1f64),
+            ],
+            material,
+            back: None,
             id,
         }
     }
 
+    /// Sets the texture coordinates of the vertices
+    pub fn with_uvs(mut self, uvs: [Uv; 3]) -> Triangle {
+        self.uvs = uvs;
+        self
+    }
+
+    /// Sets the material of the back of the triangle
+    pub fn with_back(mut self, back: Option<Rc<Material>>) -> Triangle {
+        self.back = back;
+        self
+    }
+
+    /// Weights of `p2` and `p3` for `p` point, `p1` having the remainder
+    pub fn barycentric(&self, p: Point) -> (f64, f64) {
+        let s = p - self.p1;
+        let (d00, d01, d11) = (
+            self.edge1 * self.edge1,
+            self.edge1 * self.edge2,
+            self.edge2 * self.edge2,
+        );
+        let (d20, d21) = (s * self.edge1, s * self.edge2);
+        let denom = d00 * d11 - d01 * d01;
+
+        (
+            (d11 * d20 - d01 * d21) / denom,
+            (d00 * d21 - d01 * d20) / denom,
+        )
+    }
+
     pub fn intersection(&self, p: Point, v: Vector) -> (bool, f64) {
         let h = v.cross_product(&self.edge2);
         let angle = self.edge1 * h;
@@ -83,7 +124,7 @@ impl Intersect for Triangle {
             return Vec::new();
         }
 
-        return vec![p + v * t];
+        vec![p + v * t]
     }
 }
 
@@ -91,11 +132,63 @@ impl Normal for Triangle {
     fn normal(&self, _p: Point) -> Vector {
         self.normal
     }
+
+    /// Edges expressed in texture coordinates, solved for the directions of
+    /// `u` and `v`
+    fn tangents(&self, _p: Point) -> (Vector, Vector) {
+        let d1 = self.uvs[1] - self.uvs[0];
+        let d2 = self.uvs[2] - self.uvs[0];
+        let det = d1.u * d2.v - d2.u * d1.v;
+        if det.abs() < f64::EPSILON {
+            return frame(self.normal);
+        }
+
+        (
+            ((self.edge1 * d2.v - self.edge2 * d1.v) / det).normalize(),
+            ((self.edge2 * d1.u - self.edge1 * d2.u) / det).normalize(),
+        )
+    }
 }
 
-impl GetTexture for Triangle {
-    fn texture(&self) -> &Box<dyn TextureTrait> {
-        &self.texture
+impl TextureCoordinates for Triangle {
+    /// Vertex texture coordinates interpolated with barycentric weights
+    fn uv(&self, p: Point) -> Uv {
+        let (b2, b3) = self.barycentric(p);
+        self.uvs[0] * (1f64 - b2 - b3) + self.uvs[1] * b2 + self.uvs[2] * b3
+    }
+
+    /// Barycentric weights solved from the texture coordinates of the
+    /// vertices, `None` outside of the triangle
+    fn surface_point(&self, uv: Uv) -> Option<Point> {
+        let d1 = self.uvs[1] - self.uvs[0];
+        let d2 = self.uvs[2] - self.uvs[0];
+        let s = uv - self.uvs[0];
+        let det = d1.u * d2.v - d2.u * d1.v;
+        if det.abs() < f64::EPSILON {
+            return None;
+        }
+
+        let b2 = (s.u * d2.v - d2.u * s.v) / det;
+        let b3 = (d1.u * s.v - s.u * d1.v) / det;
+        // Texels on shared edges belong to both triangles
+        let inside = |b: f64| b >= -1e-9;
+        if !(inside(b2) && inside(b3) && inside(1f64 - b2 - b3)) {
+            return None;
+        }
+
+        Some(self.p1 + self.edge1 * b2 + self.edge2 * b3)
+    }
+}
+
+impl Level for Triangle {}
+
+impl GetMaterial for Triangle {
+    fn material(&self) -> &Material {
+        &self.material
+    }
+
+    fn back_material(&self) -> &Material {
+        self.back.as_deref().unwrap_or(&self.material)
     }
 }
 
@@ -109,9 +202,12 @@ impl ObjectTrait for Triangle {}
 
 #[cfg(test)]
 mod tests {
+    use crate::material::Material;
     use crate::object::triangle::Triangle;
-    use crate::object::Intersect;
-    use crate::{Color, Point, Triangle, UniformTexture, Vector};
+    use crate::object::{Intersect, Normal, TextureCoordinates};
+    use crate::uv::Uv;
+    use crate::{Color, Point, UniformTexture, Vector};
+    use std::rc::Rc;
 
     const UNIFORM_TEXTURE: UniformTexture = UniformTexture {
         kd: 1f64,
@@ -126,7 +222,7 @@ mod tests {
             Point::new(0f64, 0f64, -5f64),
             Point::new(0f64, 0f64, 5f64),
             Point::new(0f64, 4f64, 0f64),
-            Box::new(UNIFORM_TEXTURE),
+            Rc::new(Material::new(Box::new(UNIFORM_TEXTURE))),
             String::from("Triangle"),
         )
     }
@@ -138,7 +234,7 @@ mod tests {
         let ray = Vector::new(1f64, 0f64, 0f64);
 
         let res = triangle.intersection(start, ray);
-        assert_eq!(res.0, true);
+        assert!(res.0);
         assert_eq!(res.1, 3f64)
     }
 
@@ -151,4 +247,57 @@ mod tests {
         let res = triangle.intersect_points(start, ray);
         assert_eq!(res.len(), 1);
     }
+
+    #[test]
+    fn uv() {
+        let triangle = triangle();
+        assert_eq!(
+            triangle.uv(Point::new(0f64, 0f64, 5f64)),
+            Uv::new(1f64, 0f64)
+        );
+        assert_eq!(
+            triangle.uv(Point::new(0f64, 2f64, 0f64)),
+            Uv::new(0.25f64, 0.5f64)
+        );
+
+        let triangle = triangle.with_uvs([
+            Uv::new(1f64, 1f64),
+            Uv::new(1f64, 1f64),
+            Uv::new(0f64, 0f64),
+        ]);
+        assert_eq!(
+            triangle.uv(Point::new(0f64, 4f64, 0f64)),
+            Uv::new(0f64, 0f64)
+        );
+        assert_eq!(
+            triangle.uv(Point::new(0f64, 2f64, 0f64)),
+            Uv::new(0.5f64, 0.5f64)
+        );
+    }
+
+    #[test]
+    fn surface_point() {
+        let triangle = triangle();
+        let p = Point::new(0f64, 2f64, 0f64);
+        assert_eq!(triangle.surface_point(triangle.uv(p)), Some(p));
+        assert_eq!(triangle.surface_point(Uv::new(0.75f64, 0.75f64)), None);
+
+        // Degenerate texture coordinates cannot be inverted
+        let triangle = triangle.with_uvs([Uv::new(0.5f64, 0.5f64); 3]);
+        assert_eq!(triangle.surface_point(Uv::new(0.5f64, 0.5f64)), None);
+    }
+
+    #[test]
+    fn tangents_follow_uv() {
+        // Texture rotated by a quarter turn over the triangle
+        let triangle = triangle().with_uvs([
+            Uv::new(0f64, 0f64),
+            Uv::new(0f64, 1f64),
+            Uv::new(-1f64, 0f64),
+        ]);
+        let (t, b) = triangle.tangents(triangle.p1);
+
+        assert!((t + triangle.edge2.normalize()).mag() < 1e-12);
+        assert!((b - triangle.edge1.normalize()).mag() < 1e-12);
+    }
 }
@@ -1,13 +1,19 @@
 pub mod grammar;
 pub mod state;
 
+use crate::material::{Bump, Material};
 use crate::object::rect::RectangleInner;
 use crate::object::turtle::grammar::parse_grammar;
 use crate::object::turtle::state::State;
-use crate::object::{GetTexture, Intersect, Normal, ObjectId, ObjectTrait};
-use crate::texture::TextureTrait;
+use crate::object::{
+    opaque, GetMaterial, Intersect, Level, Normal, ObjectId, ObjectTrait, TextureCoordinates,
+};
+use crate::texture::ShadingContext;
+use crate::uv::{Footprint, Uv};
 use crate::{Color, Point, UniformTexture, Vector};
 use std::cell::RefCell;
+use std::error::Error;
+use std::rc::Rc;
 
 use crate::object::sphere::Sphere;
 use crate::object::triangle::Triangle;
@@ -15,7 +21,9 @@ use crate::object::triangle::Triangle;
 /// Object
 pub struct Turtle {
     pub objects: Vec<Box<dyn ObjectTrait>>,
-    pub texture: Box<dyn TextureTrait>,
+    /// Branch depth of every object
+    pub levels: Vec<usize>,
+    pub material: Rc<Material>,
     pub id: String,
 
     pub rect: RectangleInner,
@@ -24,8 +32,16 @@ pub struct Turtle {
 }
 
 impl Turtle {
-    fn generate(s: String, angle: f64, ka: f64) -> (Vec<Box<dyn ObjectTrait>>, Vector, Vector) {
+    fn generate(
+        s: String,
+        angle: f64,
+        ka: f64,
+        leaf: Option<Rc<Material>>,
+        leaf_back: Option<Rc<Material>>,
+        bump: Option<Bump>,
+    ) -> (Vec<Box<dyn ObjectTrait>>, Vec<usize>, Vector, Vector) {
         let mut res = Vec::<Box<dyn ObjectTrait>>::new();
+        let mut levels = Vec::new();
         let mut states = Vec::new();
         let mut polygon_edges = Vec::new();
 
@@ -36,8 +52,29 @@ impl Turtle {
             left: Vector::new(0f64, 0f64, 1f64),
             color_index: 0,
             radius: 0.2f64,
+            depth: 0,
         };
 
+        // Shared by every branch
+        let bark = Rc::new(Material {
+            bump,
+            ..Material::new(Box::new(UniformTexture {
+                kd: 1f64,
+                ka,
+                ks: 0.1f64,
+
+                color: Color::new(103, 78, 31),
+            }))
+        });
+        let leaf = leaf.unwrap_or_else(|| {
+            Rc::new(Material::new(Box::new(UniformTexture {
+                kd: 1f64,
+                ks: 0.2f64,
+                ka: 0.2f64,
+                color: Color::GREEN,
+            })))
+        });
+
         let mut min = Vector::ZERO;
         let mut max = Vector::ZERO;
 
@@ -50,14 +87,9 @@ impl Turtle {
                         p: current.position,
                         id: format!("Turtle {}", res.len()),
                         r: current.radius,
-                        texture: Box::new(UniformTexture {
-                            kd: 1f64,
-                            ka,
-                            ks: 0.1f64,
-
-                            color: Color::new(103, 78, 31),
-                        }),
+                        material: bark.clone(),
                     }));
+                    levels.push(current.depth);
 
                     min = min.min_against(&(current.position - current.radius));
                     max = max.max_against(&(current.position + current.radius));
@@ -81,7 +113,7 @@ impl Turtle {
                     min = min.min_against(&current.position);
                     max = max.max_against(&current.position);
 
-                    polygon_edges.push(current.position.clone());
+                    polygon_edges.push(current.position);
                     current.move_forward(0.4f64);
                 }
                 // Decrement diameter
@@ -93,6 +125,7 @@ impl Turtle {
                 // Closes a polygon
                 '}' => {
                     let start = polygon_edges[0];
+                    let uvs = polygon_uvs(&polygon_edges);
 
                     for (idx, win) in polygon_edges.windows(2).skip(1).enumerate() {
                         let (first, second) = (win[0], win[1]);
@@ -100,47 +133,96 @@ impl Turtle {
                             start,
                             first,
                             second,
-                            Box::new(UniformTexture {
-                                kd: 1f64,
-                                ks: 0.2f64,
-                                ka: 0.2f64,
-                                color: Color::GREEN,
-                            }),
+                            leaf.clone(),
                             format!("Triangle {}", idx),
-                        );
+                        )
+                        .with_uvs([uvs[0], uvs[idx + 1], uvs[idx + 2]])
+                        .with_back(leaf_back.clone());
 
                         res.push(Box::new(triangle));
+                        levels.push(current.depth);
                     }
                 }
                 // Saves states
-                '[' => states.push(current.clone()),
+                '[' => {
+                    states.push(current.clone());
+                    current.depth += 1;
+                }
                 // Restores state
                 ']' => current = states.pop().unwrap(),
                 _ => {}
             };
         }
 
-        dbg!(res.len());
-        (res, min, max)
+        (res, levels, min, max)
     }
 
-    pub fn new(path: String, texture: Box<dyn TextureTrait>, id: String) -> Turtle {
-        let g = parse_grammar(path).unwrap();
+    /// Tree of `material` branches, leaves being green unless `leaf` is given
+    /// and having `leaf_back` as back material, branches being perturbed by
+    /// `bump`
+    pub fn new(
+        path: String,
+        material: Rc<Material>,
+        leaf: Option<Rc<Material>>,
+        leaf_back: Option<Rc<Material>>,
+        bump: Option<Bump>,
+        id: String,
+    ) -> Result<Turtle, Box<dyn Error>> {
+        let g = parse_grammar(path.clone()).map_err(|e| format!("`{}`: {}", path, e))?;
         let s = g.expand();
 
-        dbg!(&texture.coefficients(Point::ZERO).2);
-        let (objects, min, max) = Turtle::generate(s, g.angle, texture.coefficients(Point::ZERO).2);
+        let ka = material
+            .texture
+            .coefficients(&ShadingContext::at(Point::ZERO))
+            .2;
+        let (objects, levels, min, max) = Turtle::generate(s, g.angle, ka, leaf, leaf_back, bump);
 
-        Turtle {
+        Ok(Turtle {
             objects,
+            levels,
             id,
-            texture,
+            material,
             rect: RectangleInner::new(min, max),
             latest_hit: RefCell::new(None),
-        }
+        })
     }
 }
 
+/// Texture coordinates of the vertices of a polygon, its bounding rectangle
+/// in its plane spanning `[0, 1]`, `u` following the first edge
+///
+/// Leaf images are stretched over whole polygons rather than repeated on
+/// every triangle of the fan.
+fn polygon_uvs(vertices: &[Point]) -> Vec<Uv> {
+    if vertices.len() < 3 {
+        return vec![Uv::default(); vertices.len()];
+    }
+
+    let origin = vertices[0];
+    let u_axis = (vertices[1] - origin).normalize();
+    let normal = u_axis.cross_product(&(vertices[2] - origin));
+    let v_axis = normal.cross_product(&u_axis).normalize();
+
+    let projected = vertices
+        .iter()
+        .map(|&p| ((p - origin) * u_axis, (p - origin) * v_axis))
+        .collect::<Vec<_>>();
+    let (mut min, mut max) = ((f64::MAX, f64::MAX), (f64::MIN, f64::MIN));
+    for &(u, v) in &projected {
+        min = (min.0.min(u), min.1.min(v));
+        max = (max.0.max(u), max.1.max(v));
+    }
+    let (width, height) = (
+        (max.0 - min.0).max(f64::EPSILON),
+        (max.1 - min.1).max(f64::EPSILON),
+    );
+
+    projected
+        .into_iter()
+        .map(|(u, v)| Uv::new((u - min.0) / width, (v - min.1) / height))
+        .collect()
+}
+
 impl Intersect for Turtle {
     fn is_intersect(&self, _p: Point, _v: Vector) -> bool {
         false
@@ -155,7 +237,12 @@ impl Intersect for Turtle {
         let mut best_inter = Vec::new();
 
         for i in 0..self.objects.len() {
-            let hits = self.objects[i].intersect_points(p, v);
+            // Cut out leaves let the ray through to the branches behind
+            let hits = self.objects[i]
+                .intersect_points(p, v)
+                .into_iter()
+                .filter(|&hit| opaque(self.objects[i].as_ref(), hit, v))
+                .collect::<Vec<_>>();
             if hits.is_empty() {
                 continue;
             }
@@ -182,16 +269,70 @@ impl Normal for Turtle {
             Vector::ZERO
         }
     }
+
+    fn tangents(&self, p: Point) -> (Vector, Vector) {
+        let old = self.latest_hit.take();
+        if let Some(index) = old {
+            self.latest_hit.replace(old);
+            self.objects[index].tangents(p)
+        } else {
+            (Vector::ZERO, Vector::ZERO)
+        }
+    }
+}
+
+impl TextureCoordinates for Turtle {
+    fn uv(&self, p: Point) -> Uv {
+        let old = self.latest_hit.take();
+        if let Some(index) = old {
+            self.latest_hit.replace(old);
+            self.objects[index].uv(p)
+        } else {
+            Uv::default()
+        }
+    }
+
+    fn footprint(&self, p: Point, px: Point, py: Point) -> Footprint {
+        let old = self.latest_hit.take();
+        if let Some(index) = old {
+            self.latest_hit.replace(old);
+            self.objects[index].footprint(p, px, py)
+        } else {
+            Footprint::default()
+        }
+    }
 }
 
-impl GetTexture for Turtle {
-    fn texture(&self) -> &Box<dyn TextureTrait> {
+impl Level for Turtle {
+    fn level(&self) -> usize {
         let old = self.latest_hit.take();
         if let Some(index) = old {
             self.latest_hit.replace(old);
-            self.objects[index].texture()
+            self.levels[index]
         } else {
-            &self.texture
+            0
+        }
+    }
+}
+
+impl GetMaterial for Turtle {
+    fn material(&self) -> &Material {
+        let old = self.latest_hit.take();
+        if let Some(index) = old {
+            self.latest_hit.replace(old);
+            self.objects[index].material()
+        } else {
+            &self.material
+        }
+    }
+
+    fn back_material(&self) -> &Material {
+        let old = self.latest_hit.take();
+        if let Some(index) = old {
+            self.latest_hit.replace(old);
+            self.objects[index].back_material()
+        } else {
+            &self.material
         }
     }
 }
@@ -212,8 +353,11 @@ impl ObjectTrait for Turtle {}
 
 #[cfg(test)]
 mod tests {
-    use crate::object::Intersect;
-    use crate::{Color, Point, UniformTexture, Vector};
+    use crate::material::{Bump, Material};
+    use crate::object::turtle::{polygon_uvs, Turtle};
+    use crate::uv::Uv;
+    use crate::{Color, Point, UniformTexture};
+    use std::rc::Rc;
 
     const UNIFORM_TEXTURE: UniformTexture = UniformTexture {
         kd: 1f64,
@@ -222,4 +366,58 @@ mod tests {
 
         color: Color::BLACK,
     };
+
+    #[test]
+    fn leaf_uvs() {
+        // Kite whose first edge goes along `y`
+        let vertices = [
+            Point::new(0f64, 0f64, 0f64),
+            Point::new(0f64, 2f64, 0f64),
+            Point::new(1f64, 3f64, 0f64),
+            Point::new(0f64, 4f64, 0f64),
+            Point::new(-1f64, 3f64, 0f64),
+        ];
+
+        let uvs = polygon_uvs(&vertices);
+        assert_eq!(uvs[0], Uv::new(0f64, 0.5f64));
+        assert_eq!(uvs[2], Uv::new(0.75f64, 1f64));
+        assert_eq!(uvs[3], Uv::new(1f64, 0.5f64));
+        assert_eq!(uvs[4], Uv::new(0.75f64, 0f64));
+        assert_eq!(polygon_uvs(&vertices[..2]), vec![Uv::default(); 2]);
+    }
+
+    #[test]
+    fn branch_bump() {
+        let turtle = |bump| {
+            Turtle::new(
+                String::from("grammar.json"),
+                Rc::new(Material::new(Box::new(UNIFORM_TEXTURE))),
+                None,
+                None,
+                bump,
+                String::from("turtle"),
+            )
+            .unwrap()
+        };
+        let bumped = |turtle: &Turtle| {
+            turtle
+                .objects
+                .iter()
+                .filter(|obj| obj.material().bump.is_some())
+                .count()
+        };
+        let branches = |turtle: &Turtle| {
+            turtle
+                .objects
+                .iter()
+                .filter(|obj| obj.id().starts_with("Turtle"))
+                .count()
+        };
+
+        // Smooth unless a bump is given, leaves keeping their own material
+        assert_eq!(bumped(&turtle(None)), 0);
+        let turtle = turtle(Some(Bump::NormalMap(Box::new(UNIFORM_TEXTURE))));
+        assert!(branches(&turtle) > 0);
+        assert_eq!(bumped(&turtle), branches(&turtle));
+    }
 }
@@ -6,8 +6,11 @@ pub struct State {
     pub head: Vector,
     pub up: Vector,
     pub left: Vector,
+    #[allow(dead_code)]
     pub color_index: usize,
     pub radius: f64,
+    /// Number of branches the turtle is in
+    pub depth: usize,
 }
 
 impl State {
@@ -0,0 +1,62 @@
+//! Generated color palettes
+use crate::color::Color;
+use serde::{Deserialize, Serialize};
+
+/// Hue wheels starting from red, colors being declared in sRGB
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Palette {
+    /// Half saturated hues at full value
+    #[default]
+    Pastel,
+    /// Fully saturated hues at full value
+    Rainbow,
+    /// Fully saturated hues at 60% value
+    Deep,
+}
+
+impl Palette {
+    /// Color at `hue` degrees around the wheel, rounded to 8 bits
+    pub fn color(&self, hue: f64) -> Color {
+        let (r, g, b) = match self {
+            Palette::Pastel => Color::from_hsv(hue, 0.5f64, 1f64),
+            Palette::Rainbow => Color::from_hsv(hue, 1f64, 1f64),
+            Palette::Deep => Color::from_hsv(hue, 1f64, 0.6f64),
+        }
+        .values();
+        Color::new(r, g, b)
+    }
+
+    /// `count` colors evenly spread around the wheel
+    pub fn colors(&self, count: usize) -> Vec<Color> {
+        (0..count)
+            .map(|i| self.color(i as f64 * 360f64 / count as f64))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::color::Color;
+    use crate::palette::Palette;
+
+    #[test]
+    fn pastel() {
+        let colors = Palette::Pastel.colors(360);
+
+        assert_eq!(colors.len(), 360);
+        assert_eq!(colors[0], Color::new(255, 128, 128));
+        assert_eq!(colors[1], Color::new(255, 130, 128));
+        assert_eq!(colors[120], Color::new(128, 255, 128));
+        assert_eq!(colors[359], Color::new(255, 128, 130));
+    }
+
+    #[test]
+    fn wheels() {
+        assert_eq!(
+            Palette::Rainbow.colors(3),
+            vec![Color::RED, Color::GREEN, Color::BLUE]
+        );
+        assert_eq!(Palette::Deep.color(240f64), Color::new(0, 0, 153));
+    }
+}
@@ -0,0 +1,73 @@
+//! Glow around over-bright pixels
+use crate::color::Color;
+use crate::img::Image;
+use crate::post::{gaussian_blur, Effect};
+use crate::vector::Vector;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Bloom {
+    /// Linear value above which light spills, white being `1`
+    pub threshold: f64,
+    /// Factor of the blurred highlights added back to the image
+    pub intensity: f64,
+    /// Gaussian sigma of the glow, in pixels
+    pub radius: f64,
+}
+
+impl Default for Bloom {
+    fn default() -> Self {
+        Bloom {
+            threshold: 1f64,
+            intensity: 0.5f64,
+            radius: 4f64,
+        }
+    }
+}
+
+impl Effect for Bloom {
+    fn apply(&self, img: &mut Image) {
+        let threshold = self.threshold * 255f64;
+        let highlights = img
+            .pixels()
+            .iter()
+            .map(|c| (c.to_vec() - threshold).max_against(&Vector::ZERO))
+            .collect::<Vec<_>>();
+
+        let glow = gaussian_blur(&highlights, img.width(), self.radius);
+
+        for (c, g) in img.pixels_mut().iter_mut().zip(glow) {
+            *c = Color::from(c.to_vec() + g * self.intensity);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::color::Color;
+    use crate::img::Image;
+    use crate::post::bloom::Bloom;
+    use crate::post::Effect;
+    use crate::vector::Vector;
+
+    #[test]
+    fn spills_highlights_only() {
+        let mut img = Image::new(1, 9);
+        for i in 0..9 {
+            let v = if i == 4 { 255f64 * 10f64 } else { 100f64 };
+            img.push(Color::from(Vector::ONE * v));
+        }
+
+        let mut dim = Image::new(1, 2);
+        dim.push(Color::new(100, 100, 100));
+        dim.push(Color::WHITE);
+
+        Bloom::default().apply(&mut img);
+        Bloom::default().apply(&mut dim);
+
+        assert!(img.pixels()[3].to_vec().x > 100f64);
+        assert_eq!(dim.pixels()[0], Color::new(100, 100, 100));
+        assert_eq!(dim.pixels()[1], Color::WHITE);
+    }
+}
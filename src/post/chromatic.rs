@@ -0,0 +1,64 @@
+//! Lateral chromatic aberration: red and blue are scaled away from the center
+use crate::color::Color;
+use crate::img::Image;
+use crate::post::{sample_bilinear, Effect};
+use crate::vector::Vector;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChromaticAberration {
+    /// Shift of the red and blue channels at the corners, in pixels
+    pub strength: f64,
+}
+
+impl Default for ChromaticAberration {
+    fn default() -> Self {
+        ChromaticAberration { strength: 2f64 }
+    }
+}
+
+impl Effect for ChromaticAberration {
+    fn apply(&self, img: &mut Image) {
+        let (width, height) = (img.width(), img.height());
+        let (cx, cy) = ((width - 1) as f64 / 2f64, (height - 1) as f64 / 2f64);
+        let half_diagonal = (cx * cx + cy * cy).sqrt().max(f64::EPSILON);
+        let scale = self.strength / half_diagonal;
+
+        let source = img.pixels().iter().map(|c| c.to_vec()).collect::<Vec<_>>();
+
+        for (i, c) in img.pixels_mut().iter_mut().enumerate() {
+            let (x, y) = ((i % width) as f64, (i / width) as f64);
+            let (dx, dy) = ((x - cx) * scale, (y - cy) * scale);
+
+            let red = sample_bilinear(&source, width, x + dx, y + dy).x;
+            let blue = sample_bilinear(&source, width, x - dx, y - dy).z;
+
+            *c = Color::from(Vector::new(red, c.to_vec().y, blue));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::color::Color;
+    use crate::img::Image;
+    use crate::post::chromatic::ChromaticAberration;
+    use crate::post::Effect;
+
+    #[test]
+    fn splits_channels_at_edges() {
+        let mut img = Image::new(1, 5);
+        for i in 0..5 {
+            img.push(if i == 3 { Color::WHITE } else { Color::BLACK });
+        }
+
+        let effect = ChromaticAberration { strength: 1f64 };
+        effect.apply(&mut img);
+
+        // Center is untouched, red comes from further out, blue from closer in
+        assert_eq!(img.pixels()[2], Color::BLACK);
+        assert_eq!(img.pixels()[3].values(), (128, 255, 128));
+        assert_eq!(img.pixels()[4].values(), (0, 0, 255));
+    }
+}
@@ -0,0 +1,161 @@
+//! Color grading with piecewise linear curves
+use crate::color::Color;
+use crate::img::Image;
+use crate::post::Effect;
+use crate::vector::Vector;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+
+/// Control points `[input, output]` sorted by input, white being `1`
+///
+/// Values outside of the points follow the identity from the closest point.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "Vec<[f64; 2]>", into = "Vec<[f64; 2]>")]
+pub struct Curve {
+    points: Vec<[f64; 2]>,
+}
+
+impl Curve {
+    /// Sorts `points` by input, returns an error if there are none or if a
+    /// value is not finite
+    pub fn new(mut points: Vec<[f64; 2]>) -> Result<Curve, Box<dyn Error>> {
+        if points.is_empty() {
+            return Err("a curve needs at least one point".into());
+        }
+        if let Some(point) = points
+            .iter()
+            .find(|p| !(p[0].is_finite() && p[1].is_finite()))
+        {
+            return Err(format!("curve points must be finite, not {:?}", point).into());
+        }
+
+        points.sort_by(|a, b| a[0].total_cmp(&b[0]));
+        Ok(Curve { points })
+    }
+
+    fn evaluate(&self, x: f64) -> f64 {
+        let (first, last) = (self.points[0], self.points[self.points.len() - 1]);
+        if x <= first[0] {
+            return first[1] + (x - first[0]);
+        }
+        if x >= last[0] {
+            return last[1] + (x - last[0]);
+        }
+
+        // Only NaN is neither before, after nor between the points
+        let Some(segment) = self.points.windows(2).find(|w| x <= w[1][0]) else {
+            return x;
+        };
+        let ([x0, y0], [x1, y1]) = (segment[0], segment[1]);
+        if x1 == x0 {
+            return y1;
+        }
+
+        y0 + (y1 - y0) * (x - x0) / (x1 - x0)
+    }
+}
+
+impl TryFrom<Vec<[f64; 2]>> for Curve {
+    type Error = Box<dyn Error>;
+
+    fn try_from(points: Vec<[f64; 2]>) -> Result<Self, Self::Error> {
+        Curve::new(points)
+    }
+}
+
+impl From<Curve> for Vec<[f64; 2]> {
+    fn from(curve: Curve) -> Self {
+        curve.points
+    }
+}
+
+/// Applies `master` to every channel, then the per-channel curves, missing
+/// curves being the identity
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Curves {
+    pub master: Option<Curve>,
+    pub red: Option<Curve>,
+    pub green: Option<Curve>,
+    pub blue: Option<Curve>,
+}
+
+fn evaluate(curve: &Option<Curve>, x: f64) -> f64 {
+    match curve {
+        Some(curve) => curve.evaluate(x),
+        None => x,
+    }
+}
+
+impl Curves {
+    fn grade(&self, v: Vector) -> Vector {
+        let v = v / 255f64;
+        let (r, g, b) = (
+            evaluate(&self.master, v.x),
+            evaluate(&self.master, v.y),
+            evaluate(&self.master, v.z),
+        );
+
+        Vector::new(
+            evaluate(&self.red, r),
+            evaluate(&self.green, g),
+            evaluate(&self.blue, b),
+        )
+        .max_against(&Vector::ZERO)
+            * 255f64
+    }
+}
+
+impl Effect for Curves {
+    fn apply(&self, img: &mut Image) {
+        for c in img.pixels_mut().iter_mut() {
+            *c = Color::from(self.grade(c.to_vec()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::color::Color;
+    use crate::img::Image;
+    use crate::post::curves::{evaluate, Curve, Curves};
+    use crate::post::Effect;
+
+    #[test]
+    fn piecewise_linear() {
+        // Points are sorted by input
+        let curve = Some(Curve::new(vec![[0.6f64, 0.9f64], [0.2f64, 0.1f64]]).unwrap());
+
+        assert_eq!(evaluate(&None, 0.3f64), 0.3f64);
+        assert!((evaluate(&curve, 0.4f64) - 0.5f64).abs() < 1e-9);
+        assert!((evaluate(&curve, 0f64) + 0.1f64).abs() < 1e-9);
+        assert!((evaluate(&curve, 2f64) - 2.3f64).abs() < 1e-9);
+        assert!(evaluate(&curve, f64::NAN).is_nan());
+    }
+
+    #[test]
+    fn invalid_curves() {
+        assert!(Curve::new(Vec::new()).is_err());
+        assert!(Curve::new(vec![[0f64, f64::INFINITY]]).is_err());
+
+        let error = serde_json::from_str::<Curves>(r#"{ "blue": [] }"#).unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("a curve needs at least one point"));
+    }
+
+    #[test]
+    fn per_channel() {
+        let mut img = Image::new(1, 1);
+        img.push(Color::new(255, 255, 102));
+
+        let curves = Curves {
+            red: Some(Curve::new(vec![[0f64, 0f64], [1f64, 0.5f64]]).unwrap()),
+            blue: Some(Curve::new(vec![[0f64, 0f64], [0.4f64, 0.8f64]]).unwrap()),
+            ..Curves::default()
+        };
+        curves.apply(&mut img);
+
+        assert_eq!(img.pixels()[0].values(), (128, 255, 204));
+    }
+}
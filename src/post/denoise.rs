@@ -0,0 +1,274 @@
+//! Edge-avoiding à-trous wavelet denoiser
+//!
+//! From Dammertz et al., "Edge-Avoiding À-Trous Wavelet Transform for fast
+//! Global Illumination Filtering": a 5x5 B3 spline kernel is applied with
+//! holes growing as `2^i`, each tap being weighted by how close its color,
+//! normal, depth and albedo are to the center pixel.
+use crate::color::Color;
+use crate::img::Image;
+use crate::vector::Vector;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+
+/// 1D B3 spline, the 5x5 kernel is its outer product
+const KERNEL: [f64; 5] = [
+    1f64 / 16f64,
+    1f64 / 4f64,
+    3f64 / 8f64,
+    1f64 / 4f64,
+    1f64 / 16f64,
+];
+
+/// Passes of the largest kernel `validate` accepts, covering `4 * 2^16`
+/// pixels which is past the size of any render
+const MAX_ITERATIONS: usize = 16;
+
+/// Denoiser parameters, a larger sigma makes a buffer less edge-preserving
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Denoiser {
+    /// Number of passes, the kernel then covers `4 * 2^iterations` pixels
+    pub iterations: usize,
+    /// Color sigma, white being `1`, halved after every pass
+    pub sigma_color: f64,
+    pub sigma_normal: f64,
+    /// Relative depth sigma
+    pub sigma_depth: f64,
+    /// Albedo sigma, white being `1`
+    pub sigma_albedo: f64,
+}
+
+impl Default for Denoiser {
+    fn default() -> Self {
+        Denoiser {
+            iterations: 4,
+            sigma_color: 0.5f64,
+            sigma_normal: 0.1f64,
+            sigma_depth: 0.05f64,
+            sigma_albedo: 0.1f64,
+        }
+    }
+}
+
+/// `exp(-d / sigma^2)`
+fn weight(distance: f64, sigma: f64) -> f64 {
+    (-distance / (sigma * sigma)).exp()
+}
+
+/// Squared distance of depths relative to the center, infinite depths
+/// only match each other
+fn depth_distance(center: f64, other: f64) -> f64 {
+    match (center.is_finite(), other.is_finite()) {
+        (true, true) => ((center - other) / center.abs().max(f64::EPSILON)).powf(2f64),
+        (false, false) => 0f64,
+        _ => f64::INFINITY,
+    }
+}
+
+fn color_distance(a: Color, b: Color) -> f64 {
+    ((a.to_vec() - b.to_vec()) / 255f64).powf(2f64).sum()
+}
+
+fn normal_distance(a: Vector, b: Vector) -> f64 {
+    (a - b).powf(2f64).sum()
+}
+
+impl Denoiser {
+    /// Returns an error unless every sigma is positive, a zero sigma giving
+    /// `0 / 0` weights, or if there are more than `MAX_ITERATIONS` passes
+    pub fn validate(&self) -> Result<(), Box<dyn Error>> {
+        if self.iterations > MAX_ITERATIONS {
+            return Err(format!(
+                "denoiser iterations must be at most {}, not {}",
+                MAX_ITERATIONS, self.iterations
+            )
+            .into());
+        }
+
+        let sigmas = [
+            ("sigma_color", self.sigma_color),
+            ("sigma_normal", self.sigma_normal),
+            ("sigma_depth", self.sigma_depth),
+            ("sigma_albedo", self.sigma_albedo),
+        ];
+        for (name, sigma) in sigmas {
+            if !(sigma > 0f64 && sigma.is_finite()) {
+                return Err(format!("denoiser {} must be positive, not {}", name, sigma).into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Filters the linear pixels of `img` in place, guided by its depth,
+    /// normal and albedo buffers
+    pub fn apply(&self, img: &mut Image) -> Result<(), Box<dyn Error>> {
+        self.validate()?;
+        if !img.has_aux() {
+            return Err("denoising requires the depth, normal and albedo buffers".into());
+        }
+
+        let (width, height) = (img.width(), img.height());
+        let depth = img.depth().clone();
+        let normals = img.normals().clone();
+        let albedo = img.albedo().clone();
+
+        let mut current = img.pixels().clone();
+        let mut sigma_color = self.sigma_color;
+
+        for iteration in 0..self.iterations {
+            let step = 1isize << iteration;
+            let mut next = Vec::with_capacity(current.len());
+
+            for y in 0..height as isize {
+                for x in 0..width as isize {
+                    let p = (y * width as isize + x) as usize;
+                    let mut sum = Vector::ZERO;
+                    let mut total = 0f64;
+
+                    for (j, ky) in KERNEL.iter().enumerate() {
+                        for (i, kx) in KERNEL.iter().enumerate() {
+                            let qx = x + (i as isize - 2) * step;
+                            let qy = y + (j as isize - 2) * step;
+                            if qx < 0 || qy < 0 || qx >= width as isize || qy >= height as isize {
+                                continue;
+                            }
+                            let q = (qy * width as isize + qx) as usize;
+
+                            let w = kx
+                                * ky
+                                * weight(color_distance(current[p], current[q]), sigma_color)
+                                * weight(
+                                    normal_distance(normals[p], normals[q]),
+                                    self.sigma_normal,
+                                )
+                                * weight(depth_distance(depth[p], depth[q]), self.sigma_depth)
+                                * weight(color_distance(albedo[p], albedo[q]), self.sigma_albedo);
+
+                            sum = sum + current[q].to_vec() * w;
+                            total += w;
+                        }
+                    }
+
+                    // The center tap always has a non zero weight
+                    next.push(Color::from(sum / total));
+                }
+            }
+
+            current = next;
+            sigma_color /= 2f64;
+        }
+
+        *img.pixels_mut() = current;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::color::Color;
+    use crate::img::Image;
+    use crate::post::denoise::Denoiser;
+    use crate::vector::Vector;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    const SIZE: usize = 32;
+
+    /// Left half faces the camera, right half faces left, both gray with
+    /// uniform noise of `amplitude`
+    fn noisy(amplitude: f64) -> Image {
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut img = Image::new(SIZE, SIZE);
+
+        for i in 0..(SIZE * SIZE) {
+            let left = i % SIZE < SIZE / 2;
+            let base = if left { 50f64 } else { 200f64 };
+            let noise = rng.gen_range(-amplitude..=amplitude);
+
+            img.push(Color::from(Vector::ONE * (base + noise)));
+            let normal = if left {
+                Vector::new(0f64, 0f64, -1f64)
+            } else {
+                Vector::new(-1f64, 0f64, 0f64)
+            };
+            img.push_aux(4f64, normal, Color::new(128, 128, 128));
+        }
+
+        img
+    }
+
+    fn variance(img: &Image, left: bool) -> f64 {
+        let values = img
+            .pixels()
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| (i % SIZE < SIZE / 2) == left)
+            .map(|(_, c)| c.to_vec().x)
+            .collect::<Vec<_>>();
+
+        let mean = values.iter().sum::<f64>() / values.len() as f64;
+        values.iter().map(|v| (v - mean).powf(2f64)).sum::<f64>() / values.len() as f64
+    }
+
+    #[test]
+    fn reduces_noise() {
+        let mut img = noisy(30f64);
+        let before = variance(&img, true);
+
+        Denoiser::default().apply(&mut img).unwrap();
+
+        assert!(variance(&img, true) < before / 10f64);
+        assert!(variance(&img, false) < before / 10f64);
+    }
+
+    #[test]
+    fn preserves_edges() {
+        let mut img = noisy(0f64);
+        Denoiser::default().apply(&mut img).unwrap();
+
+        let row = &img.pixels()[..SIZE];
+        assert!((row[SIZE / 2 - 1].to_vec().x - 50f64).abs() < 1f64);
+        assert!((row[SIZE / 2].to_vec().x - 200f64).abs() < 1f64);
+    }
+
+    #[test]
+    fn requires_aux() {
+        let mut img = Image::new(1, 1);
+        img.push(Color::BLACK);
+
+        assert!(Denoiser::default().apply(&mut img).is_err());
+    }
+
+    #[test]
+    fn invalid_sigmas() {
+        let denoiser = Denoiser {
+            sigma_depth: 0f64,
+            ..Denoiser::default()
+        };
+        let error = denoiser.apply(&mut noisy(0f64)).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "denoiser sigma_depth must be positive, not 0"
+        );
+
+        let denoiser = Denoiser {
+            sigma_color: -1f64,
+            ..Denoiser::default()
+        };
+        assert!(denoiser.validate().is_err());
+        assert!(Denoiser::default().validate().is_ok());
+    }
+
+    #[test]
+    fn too_many_iterations() {
+        let denoiser = Denoiser {
+            iterations: 64,
+            ..Denoiser::default()
+        };
+        let error = denoiser.apply(&mut noisy(0f64)).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "denoiser iterations must be at most 16, not 64"
+        );
+    }
+}
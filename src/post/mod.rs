@@ -0,0 +1,89 @@
+//! Image-space processing of the linear framebuffer, before tone mapping
+use crate::img::Image;
+use crate::vector::Vector;
+
+pub mod bloom;
+pub mod chromatic;
+pub mod curves;
+pub mod denoise;
+pub mod sharpen;
+pub mod vignette;
+
+/// Image-space effect applied to the linear pixels of a render
+pub trait Effect {
+    fn apply(&self, img: &mut Image);
+}
+
+/// Separable gaussian blur of a `width` wide buffer, borders are clamped
+pub fn gaussian_blur(values: &[Vector], width: usize, sigma: f64) -> Vec<Vector> {
+    if sigma <= 0f64 || values.is_empty() {
+        return values.to_vec();
+    }
+
+    let height = values.len() / width;
+    let radius = (sigma * 3f64).ceil() as isize;
+    let kernel = (-radius..=radius)
+        .map(|i| (-(i * i) as f64 / (2f64 * sigma * sigma)).exp())
+        .collect::<Vec<_>>();
+    let total = kernel.iter().sum::<f64>();
+
+    let pass = |input: &[Vector], horizontal: bool| -> Vec<Vector> {
+        let mut output = Vec::with_capacity(input.len());
+        for y in 0..height as isize {
+            for x in 0..width as isize {
+                let mut sum = Vector::ZERO;
+                for (k, w) in (-radius..=radius).zip(&kernel) {
+                    let (sx, sy) = if horizontal { (x + k, y) } else { (x, y + k) };
+                    let sx = sx.clamp(0, width as isize - 1) as usize;
+                    let sy = sy.clamp(0, height as isize - 1) as usize;
+                    sum = sum + input[sy * width + sx] * *w;
+                }
+                output.push(sum / total);
+            }
+        }
+        output
+    };
+
+    pass(&pass(values, true), false)
+}
+
+/// Bilinear lookup at continuous pixel coordinates, borders are clamped
+pub fn sample_bilinear(values: &[Vector], width: usize, x: f64, y: f64) -> Vector {
+    let height = values.len() / width;
+    let x = x.clamp(0f64, (width - 1) as f64);
+    let y = y.clamp(0f64, (height - 1) as f64);
+
+    let (x0, y0) = (x.floor() as usize, y.floor() as usize);
+    let (x1, y1) = ((x0 + 1).min(width - 1), (y0 + 1).min(height - 1));
+    let (fx, fy) = (x - x0 as f64, y - y0 as f64);
+
+    let top = values[y0 * width + x0] * (1f64 - fx) + values[y0 * width + x1] * fx;
+    let bottom = values[y1 * width + x0] * (1f64 - fx) + values[y1 * width + x1] * fx;
+    top * (1f64 - fy) + bottom * fy
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::post::{gaussian_blur, sample_bilinear};
+    use crate::vector::Vector;
+
+    #[test]
+    fn blur_keeps_energy() {
+        let mut values = vec![Vector::ZERO; 81];
+        values[40] = Vector::ONE * 81f64;
+
+        let blurred = gaussian_blur(&values, 9, 1f64);
+        let total = blurred.iter().map(|v| v.x).sum::<f64>();
+
+        assert!((total - 81f64).abs() < 1e-6);
+        assert!(blurred[40].x < 81f64 && blurred[41].x > 0f64);
+    }
+
+    #[test]
+    fn bilinear() {
+        let values = vec![Vector::ZERO, Vector::ONE, Vector::ONE, Vector::ONE * 2f64];
+
+        assert_eq!(sample_bilinear(&values, 2, 0.5f64, 0.5f64), Vector::ONE);
+        assert_eq!(sample_bilinear(&values, 2, 5f64, -1f64), Vector::ONE);
+    }
+}
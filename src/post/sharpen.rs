@@ -0,0 +1,61 @@
+//! Unsharp masking
+use crate::color::Color;
+use crate::img::Image;
+use crate::post::{gaussian_blur, Effect};
+use crate::vector::Vector;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Sharpen {
+    /// Factor of the detail added back, `0` leaves the image unchanged
+    pub amount: f64,
+    /// Gaussian sigma separating details from the base, in pixels
+    pub radius: f64,
+}
+
+impl Default for Sharpen {
+    fn default() -> Self {
+        Sharpen {
+            amount: 0.5f64,
+            radius: 1f64,
+        }
+    }
+}
+
+impl Effect for Sharpen {
+    fn apply(&self, img: &mut Image) {
+        let source = img.pixels().iter().map(|c| c.to_vec()).collect::<Vec<_>>();
+        let blurred = gaussian_blur(&source, img.width(), self.radius);
+
+        for ((c, v), b) in img.pixels_mut().iter_mut().zip(source).zip(blurred) {
+            *c = Color::from((v + (v - b) * self.amount).max_against(&Vector::ZERO));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::color::Color;
+    use crate::img::Image;
+    use crate::post::sharpen::Sharpen;
+    use crate::post::Effect;
+
+    #[test]
+    fn increases_contrast_at_edges() {
+        let mut img = Image::new(1, 6);
+        for i in 0..6 {
+            img.push(if i < 3 {
+                Color::new(50, 50, 50)
+            } else {
+                Color::new(150, 150, 150)
+            });
+        }
+
+        Sharpen::default().apply(&mut img);
+
+        assert!(img.pixels()[2].to_vec().x < 50f64);
+        assert!(img.pixels()[3].to_vec().x > 150f64);
+        assert_eq!(img.pixels()[0].values(), (50, 50, 50));
+    }
+}
@@ -0,0 +1,88 @@
+//! Darkening towards the image corners
+use crate::img::Image;
+use crate::post::Effect;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Vignette {
+    /// Fraction of light removed at the corners
+    pub strength: f64,
+    /// Exponent of the distance to the center, higher keeps more of the center
+    pub falloff: f64,
+}
+
+impl Default for Vignette {
+    fn default() -> Self {
+        Vignette {
+            strength: 0.5f64,
+            falloff: 2f64,
+        }
+    }
+}
+
+impl Vignette {
+    /// Returns an error unless `strength` is in `[0, 1]`, stronger vignettes
+    /// making the corners negative
+    pub fn validate(&self) -> Result<(), Box<dyn Error>> {
+        if !(0f64..=1f64).contains(&self.strength) {
+            return Err(format!(
+                "vignette strength must be between 0 and 1, not {}",
+                self.strength
+            )
+            .into());
+        }
+        Ok(())
+    }
+}
+
+impl Effect for Vignette {
+    fn apply(&self, img: &mut Image) {
+        let (width, height) = (img.width(), img.height());
+        let (cx, cy) = ((width - 1) as f64 / 2f64, (height - 1) as f64 / 2f64);
+        let half_diagonal = (cx * cx + cy * cy).sqrt().max(f64::EPSILON);
+
+        for (i, c) in img.pixels_mut().iter_mut().enumerate() {
+            let (x, y) = ((i % width) as f64, (i / width) as f64);
+            let d = ((x - cx).powf(2f64) + (y - cy).powf(2f64)).sqrt() / half_diagonal;
+
+            *c = *c * (1f64 - self.strength * d.powf(self.falloff));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::color::Color;
+    use crate::img::Image;
+    use crate::post::vignette::Vignette;
+    use crate::post::Effect;
+
+    #[test]
+    fn darkens_corners() {
+        let mut img = Image::new(3, 3);
+        for _ in 0..9 {
+            img.push(Color::WHITE);
+        }
+
+        Vignette::default().apply(&mut img);
+
+        assert_eq!(img.pixels()[4], Color::WHITE);
+        assert_eq!(img.pixels()[0].values(), (128, 128, 128));
+        assert!(img.pixels()[1].to_vec().x > img.pixels()[0].to_vec().x);
+    }
+
+    #[test]
+    fn invalid_strength() {
+        let vignette = |strength| Vignette {
+            strength,
+            ..Vignette::default()
+        };
+
+        assert!(vignette(1f64).validate().is_ok());
+        assert!(vignette(1.5f64).validate().is_err());
+        assert!(vignette(-0.1f64).validate().is_err());
+        assert!(vignette(f64::NAN).validate().is_err());
+    }
+}
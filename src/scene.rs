@@ -2,9 +2,10 @@ use crate::camera::Camera;
 use crate::color::Color;
 use crate::img::Image;
 use crate::light::LightTrait;
-use crate::object::ObjectTrait;
+use crate::object::{facing, opaque, ObjectTrait};
+use crate::texture::ShadingContext;
 use crate::{Point, Vector};
-use std::io::Write;
+use std::f64::consts::PI;
 
 pub struct Scene {
     pub cam: Camera,
@@ -12,7 +13,50 @@ pub struct Scene {
     pub objects: Vec<Box<dyn ObjectTrait>>,
 }
 
-const MAX_REC: usize = 0;
+const MAX_REC: usize = 4;
+
+/// Rays through the neighbouring pixels, following a primary ray to estimate
+/// the texture footprint of its hits
+#[derive(Debug, Copy, Clone)]
+struct RayDifferential {
+    /// Origin and direction of the ray through the next pixel to the right
+    dx: (Point, Vector),
+    /// Origin and direction of the ray through the next pixel below
+    dy: (Point, Vector),
+}
+
+impl RayDifferential {
+    /// Points where the offset rays cross the tangent plane at `p`
+    fn transfer(&self, p: Point, normal: Vector) -> (Point, Point) {
+        let hit = |(origin, direction): (Point, Vector)| {
+            let cos = direction * normal;
+            if cos.abs() < f64::EPSILON {
+                return p;
+            }
+            origin + direction * (((p - origin) * normal) / cos)
+        };
+
+        (hit(self.dx), hit(self.dy))
+    }
+
+    /// Offset rays going on from `px` and `py` through a transparent surface
+    fn transmit(&self, px: Point, py: Point) -> RayDifferential {
+        RayDifferential {
+            dx: (px, self.dx.1),
+            dy: (py, self.dy.1),
+        }
+    }
+
+    /// Offset rays mirrored at `px` and `py`, the surface being assumed flat
+    fn reflect(&self, px: Point, py: Point, normal: Vector) -> RayDifferential {
+        let mirror = |v: Vector| v - normal * (v * normal) * 2f64;
+
+        RayDifferential {
+            dx: (px, mirror(self.dx.1)),
+            dy: (py, mirror(self.dy.1)),
+        }
+    }
+}
 
 impl Scene {
     pub fn image(&self, height: usize, width: usize) -> Image {
@@ -27,24 +71,39 @@ impl Scene {
         let p_top_left =
             self.cam.center + self.cam.forward - self.cam.right * gx + self.cam.up * gy;
 
-        let size = height;
-        for (_ii, i) in (0..height).map(|i| qy * i as f64).enumerate() {
-            // print!("{:.2}%\r", (_ii * 100) as f64 / (size as f64));
-            // std::io::stdout().flush().unwrap();
-
-            for (_jj, j) in (0..width).map(|j| qx * j as f64).enumerate() {
+        for i in (0..height).map(|i| qy * i as f64) {
+            for j in (0..width).map(|j| qx * j as f64) {
                 let p_pixel = p_top_left + j - i;
                 let v = (p_pixel - self.cam.center).normalize();
-                // dbg!((ii, jj));
+                let ray = RayDifferential {
+                    dx: (
+                        self.cam.center,
+                        (p_pixel + qx - self.cam.center).normalize(),
+                    ),
+                    dy: (
+                        self.cam.center,
+                        (p_pixel - qy - self.cam.center).normalize(),
+                    ),
+                };
 
                 let collision = self.cast_ray(self.cam.center, v);
                 if collision.is_none() {
                     img.push(Color::WHITE);
+                    img.push_aux(f64::INFINITY, Vector::ZERO, Color::WHITE);
                     continue;
                 }
 
                 let (p, obj, _) = collision.unwrap();
-                let color = self.cast_ray_rebound(p, obj, v, 0);
+
+                let (normal, material) = facing(obj, p, v);
+                let ctx = self.shading_context(p, obj, normal, &ray, 0);
+                img.push_aux(
+                    (p - self.cam.center) * self.cam.forward,
+                    material.shading_normal(&ctx, obj.tangents(p)),
+                    material.texture.color(&ctx).to_linear(),
+                );
+
+                let color = self.cast_ray_rebound(p, obj, v, &ray, 0);
 
                 match color {
                     Some(c) => img.push(c),
@@ -52,8 +111,6 @@ impl Scene {
                 }
             }
         }
-        // println!();
-
         img
     }
 
@@ -63,71 +120,123 @@ impl Scene {
         self
     }
 
+    /// Shading context of `p` on `obj` hit after `depth` reflections
+    fn shading_context<'a>(
+        &self,
+        p: Point,
+        obj: &'a dyn ObjectTrait,
+        normal: Vector,
+        ray: &RayDifferential,
+        depth: usize,
+    ) -> ShadingContext<'a> {
+        let (px, py) = ray.transfer(p, normal);
+
+        ShadingContext {
+            point: p,
+            normal,
+            uv: obj.uv(p),
+            footprint: obj.footprint(p, px, py),
+            id: obj.id(),
+            depth,
+            level: obj.level(),
+        }
+    }
+
     fn cast_ray_rebound(
         &self,
         p: Point,
-        obj: &Box<dyn ObjectTrait>,
+        obj: &dyn ObjectTrait,
         v: Vector,
+        ray: &RayDifferential,
         rec: usize,
     ) -> Option<Color> {
-        let mut normal = obj.normal(p);
-        if normal * self.cam.forward > 0f64 {
-            normal = -normal;
-        }
-
-        let reflect = v - normal * (v * normal) * 2f64;
-        let (kd, ks, ka) = obj.texture().coefficients(p);
+        // Thin surfaces show their back material to rays hitting them from
+        // behind
+        let (normal, material) = facing(obj, p, v);
+        let ctx = self.shading_context(p, obj, normal, ray, rec);
+        // Lighting follows the bumped normal, ray differentials the geometry
+        let shading = material.shading_normal(&ctx, obj.tangents(p));
+        let reflect = v - shading * (v * shading) * 2f64;
+        let (kd, ks, ka) = material.texture.coefficients(&ctx);
+        // Textures are declared in sRGB, lighting is computed on linear values
+        let albedo = material.texture.color(&ctx).to_linear().to_vec();
+        let specular = material.specular.to_linear().to_vec() / 255f64;
+        let brdf = material.brdf(&ctx);
 
         let mut current_color = Color::BLACK;
 
         for light in &self.lights {
             let l_vec = light.point() - p;
             let l_dist = l_vec.mag();
+            let ambient = albedo * ka;
+
+            // Occluded lights only add their ambient term
+            let occluded = self
+                .cast_ray(p, l_vec)
+                .is_some_and(|(_, _, i_dist)| i_dist < l_dist);
+            if occluded {
+                current_color = current_color + Color::from(ambient);
+                continue;
+            }
 
             let intensity = light.intensity();
 
-            let i_d = (obj.texture().color(p).v).mul(intensity.normalize())
-                * kd
-                * (normal * l_vec.normalize());
-
-            let rl = reflect * l_vec.normalize();
-            let i_s = intensity * (ks * rl.powf(50f64)).copysign(rl);
-            let out = i_d + i_s + obj.texture().color(p).v * ka;
-
-            let intersect = self.cast_ray(p, l_vec);
-            if let Some((_i_p, _i_obj, i_dist)) = intersect {
-                if i_dist < l_dist {
-                    current_color.v = current_color.v + out;
-                    // current_color.v = current_color.v + i_s;
-                    continue;
+            let out = match &brdf {
+                // Lights are scaled for a white diffuse surface facing them
+                // to show their intensity
+                Some(brdf) => {
+                    let wi = l_vec.normalize();
+                    brdf.eval(shading, -v, wi).mul(intensity) * PI * (shading * wi).max(0f64)
+                        + ambient
                 }
-            }
+                None => {
+                    let i_d =
+                        albedo.mul(intensity.normalize()) * kd * (shading * l_vec.normalize());
+
+                    // Fractional exponents of negative cosines are not numbers
+                    let rl = (reflect * l_vec.normalize()).max(0f64);
+                    let i_s = intensity.mul(specular) * ks * rl.powf(material.shininess);
+                    i_d + i_s + ambient
+                }
+            };
 
-            current_color.v = current_color.v + out;
+            current_color = current_color + Color::from(out);
         }
+        current_color = current_color + material.emission.to_linear();
 
-        if rec == MAX_REC {
-            return Some(current_color);
-        }
+        if rec < MAX_REC {
+            let (px, py) = ray.transfer(p, normal);
 
-        let rebound = self.cast_ray(p, reflect);
-        if rebound.is_none() {
-            return Some(current_color);
+            if material.reflectivity > 0f64 {
+                let reflected = self.trace(p, reflect, &ray.reflect(px, py, normal), rec + 1);
+                current_color = current_color * (1f64 - material.reflectivity)
+                    + reflected * material.reflectivity;
+            }
+
+            if material.transparency > 0f64 {
+                let behind = self.trace(p, v, &ray.transmit(px, py), rec + 1);
+                current_color =
+                    current_color * (1f64 - material.transparency) + behind * material.transparency;
+            }
         }
 
-        let (col_p, col_obj, _) = rebound.unwrap();
-        let color = self.cast_ray_rebound(col_p, col_obj, reflect, rec + 1);
+        // Only negative light is discarded, highlights are kept for tone mapping
+        Some(Color::from(
+            current_color.to_vec().max_against(&Vector::ZERO),
+        ))
+    }
 
-        if let Some(c) = color {
-            let c = c.v * ks / 2f64;
-            current_color.v = current_color.v + c;
+    /// Color seen from `p` in the `v` direction, white when nothing is hit
+    fn trace(&self, p: Point, v: Vector, ray: &RayDifferential, rec: usize) -> Color {
+        match self.cast_ray(p, v) {
+            Some((hit, obj, _)) => self
+                .cast_ray_rebound(hit, obj, v, ray, rec)
+                .unwrap_or(Color::BLACK),
+            None => Color::WHITE,
         }
-
-        current_color.v = current_color.v.clamp(0f64, 255f64);
-        Some(current_color)
     }
 
-    fn cast_ray(&self, p: Point, v: Vector) -> Option<(Point, &Box<dyn ObjectTrait>, f64)> {
+    fn cast_ray(&self, p: Point, v: Vector) -> Option<(Point, &dyn ObjectTrait, f64)> {
         let (mut closest_obj, mut point) = (None, None);
         let mut distance = f64::MAX;
 
@@ -141,6 +250,8 @@ impl Scene {
                 .into_iter()
                 .map(|ip| (ip, (ip - p).mag()))
                 .filter(|&(_, distance)| distance > 0.000001f64)
+                // Cut out texels let the ray through to the next surface
+                .filter(|&(ip, _)| opaque(obj.as_ref(), ip, v))
                 .min_by(|(_, d1), (_, d2)| d1.partial_cmp(d2).unwrap());
             if intersect.is_none() {
                 continue;
@@ -150,7 +261,7 @@ impl Scene {
             // Avoid collision with self
             if intersect.1 < distance {
                 distance = intersect.1;
-                closest_obj = Some(obj);
+                closest_obj = Some(obj.as_ref());
                 point = Some(intersect.0);
             }
         }
@@ -160,3 +271,219 @@ impl Scene {
         Some((point.unwrap(), closest_obj.unwrap(), distance))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::camera::Camera;
+    use crate::color::Color;
+    use crate::img::Image;
+    use crate::light::point::PointLight;
+    use crate::material::{Material, Model};
+    use crate::object::plane::Plane;
+    use crate::object::sphere::Sphere;
+    use crate::scene::{RayDifferential, Scene};
+    use crate::texture::image::{Filtering, ImageTexture};
+    use crate::texture::uniform::UniformTexture;
+    use crate::{Point, Vector};
+    use std::rc::Rc;
+
+    /// Unlit black sphere of `material` in front of the camera
+    fn sphere(material: impl FnOnce(Material) -> Material) -> Scene {
+        let texture = Box::new(UniformTexture {
+            kd: 0f64,
+            ks: 0f64,
+            ka: 0f64,
+            color: Color::BLACK,
+        });
+
+        Scene {
+            cam: Camera::new(
+                Point::ZERO,
+                Point::new(0f64, 0f64, 1f64),
+                Vector::new(0f64, 1f64, 0f64),
+                std::f64::consts::FRAC_PI_2,
+            ),
+            lights: Vec::new(),
+            objects: vec![Box::new(Sphere {
+                p: Point::new(0f64, 0f64, 3f64),
+                r: 1f64,
+                material: Rc::new(material(Material::new(texture))),
+                id: String::from("sphere"),
+            })],
+        }
+    }
+
+    /// Pixel at the center of a 3x3 render
+    fn center(scene: &Scene) -> Color {
+        scene.image(3, 3).pixels()[4]
+    }
+
+    #[test]
+    fn emission() {
+        let scene = sphere(|m| Material {
+            emission: Color::new(188, 128, 0),
+            ..m
+        });
+        assert_eq!(center(&scene).values(), (128, 55, 0));
+    }
+
+    #[test]
+    fn brdf_light_scale() {
+        let mut scene = sphere(|m| Material {
+            texture: Box::new(UniformTexture {
+                kd: 1f64,
+                ks: 0f64,
+                ka: 0f64,
+                color: Color::WHITE,
+            }),
+            model: Model::BlinnPhong,
+            ..m
+        });
+        scene.lights.push(Box::new(PointLight {
+            intensity: Vector::new(255f64, 255f64, 255f64),
+            point: Point::ZERO,
+        }));
+
+        // Lit head-on, the white surface shows the light
+        assert_eq!(center(&scene).values(), (255, 255, 255));
+    }
+
+    #[test]
+    fn transparency() {
+        assert_eq!(center(&sphere(|m| m)), Color::BLACK);
+
+        // The background is seen through both sides of the sphere
+        let scene = sphere(|m| Material {
+            transparency: 1f64,
+            ..m
+        });
+        assert_eq!(center(&scene), Color::WHITE);
+    }
+
+    #[test]
+    fn back_material() {
+        let glow = |emission| {
+            Rc::new(Material {
+                emission,
+                ..Material::new(Box::new(UniformTexture {
+                    kd: 0f64,
+                    ks: 0f64,
+                    ka: 0f64,
+                    color: Color::BLACK,
+                }))
+            })
+        };
+        // Facing away from the camera
+        let scene = |back| {
+            let mut scene = sphere(|m| m);
+            scene.objects = vec![Box::new(Plane {
+                p: Point::new(0f64, 0f64, 3f64),
+                normal: Vector::new(0f64, 0f64, 1f64),
+                material: glow(Color::RED),
+                back,
+                id: String::from("plane"),
+            })];
+            scene
+        };
+
+        assert_eq!(center(&scene(None)), Color::RED.to_linear());
+        assert_eq!(
+            center(&scene(Some(glow(Color::GREEN)))),
+            Color::GREEN.to_linear()
+        );
+    }
+
+    #[test]
+    fn cutout() {
+        let mut image = Image::new(1, 1);
+        image.push(Color::RED);
+        let scene = |alpha: f64| {
+            let texture = ImageTexture::new(&image, Filtering::Nearest, (1f64, 0f64, 0f64));
+            sphere(|m| Material {
+                texture: Box::new(texture.with_alpha(&[alpha])),
+                ..m
+            })
+        };
+
+        // Unlit like the plain sphere
+        assert_eq!(center(&scene(1f64)), Color::BLACK);
+        // Both sides are cut out, the background shows through
+        assert_eq!(center(&scene(0f64)), Color::WHITE);
+    }
+
+    #[test]
+    fn shadows() {
+        let mut image = Image::new(1, 1);
+        image.push(Color::WHITE);
+        // White plane facing the camera, lit from above and behind the camera
+        let scene = |blocker: Option<f64>| {
+            let mut scene = sphere(|m| m);
+            scene.lights.push(Box::new(PointLight {
+                intensity: Vector::new(255f64, 255f64, 255f64),
+                point: Point::new(0f64, 10f64, -7f64),
+            }));
+            scene.objects = vec![Box::new(Plane {
+                p: Point::new(0f64, 0f64, 3f64),
+                normal: Vector::new(0f64, 0f64, -1f64),
+                material: Rc::new(Material::new(Box::new(UniformTexture {
+                    kd: 1f64,
+                    ks: 0f64,
+                    ka: 0f64,
+                    color: Color::WHITE,
+                }))),
+                back: None,
+                id: String::from("surface"),
+            })];
+            // Between the light and the surface, out of sight of the camera
+            if let Some(alpha) = blocker {
+                let texture = ImageTexture::new(&image, Filtering::Nearest, (1f64, 0f64, 0f64));
+                scene.objects.push(Box::new(Plane {
+                    p: Point::new(0f64, 5f64, 0f64),
+                    normal: Vector::new(0f64, -1f64, 0f64),
+                    material: Rc::new(Material::new(Box::new(texture.with_alpha(&[alpha])))),
+                    back: None,
+                    id: String::from("blocker"),
+                }));
+            }
+            scene
+        };
+
+        let lit = center(&scene(None));
+        assert!(lit.luminance() > 0f64);
+        // Cut out texels let the light through
+        assert_eq!(center(&scene(Some(0f64))), lit);
+        assert_eq!(center(&scene(Some(1f64))), Color::BLACK);
+    }
+
+    #[test]
+    fn footprint_grows_with_distance() {
+        let ray = RayDifferential {
+            dx: (Point::ZERO, Vector::new(0.01f64, 0f64, 1f64)),
+            dy: (Point::ZERO, Vector::new(0f64, -0.01f64, 1f64)),
+        };
+        let normal = Vector::new(0f64, 0f64, -1f64);
+
+        for distance in [1f64, 10f64] {
+            let p = Point::new(0f64, 0f64, distance);
+            let (px, py) = ray.transfer(p, normal);
+
+            assert!(((px - p).mag() - 0.01f64 * distance).abs() < 1e-9);
+            assert!(((py - p).mag() - 0.01f64 * distance).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn reflect() {
+        let ray = RayDifferential {
+            dx: (Point::ZERO, Vector::new(0.01f64, 0f64, 1f64)),
+            dy: (Point::ZERO, Vector::new(0f64, 0.01f64, 1f64)),
+        };
+        let normal = Vector::new(0f64, 0f64, -1f64);
+        let p = Point::new(0f64, 0f64, 1f64);
+        let (px, py) = ray.transfer(p, normal);
+
+        let reflected = ray.reflect(px, py, normal);
+        assert_eq!(reflected.dx, (px, Vector::new(0.01f64, 0f64, -1f64)));
+        assert_eq!(reflected.dy.1, Vector::new(0f64, 0.01f64, -1f64));
+    }
+}
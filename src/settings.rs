@@ -0,0 +1,232 @@
+//! Render settings: output size, post-processing and output transform
+use crate::color::Encoding;
+use crate::img::{Format, Image};
+use crate::post::bloom::Bloom;
+use crate::post::chromatic::ChromaticAberration;
+use crate::post::curves::Curves;
+use crate::post::denoise::Denoiser;
+use crate::post::sharpen::Sharpen;
+use crate::post::vignette::Vignette;
+use crate::post::Effect;
+use crate::tonemap::{ToneMapper, ToneMapping};
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fs::File;
+use std::io::BufReader;
+
+/// Effect of the post-processing chain, tagged by `type` in JSON
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum EffectSettings {
+    Bloom(Bloom),
+    Vignette(Vignette),
+    ChromaticAberration(ChromaticAberration),
+    Sharpen(Sharpen),
+    Curves(Curves),
+}
+
+impl EffectSettings {
+    pub fn effect(&self) -> &dyn Effect {
+        match self {
+            EffectSettings::Bloom(e) => e,
+            EffectSettings::Vignette(e) => e,
+            EffectSettings::ChromaticAberration(e) => e,
+            EffectSettings::Sharpen(e) => e,
+            EffectSettings::Curves(e) => e,
+        }
+    }
+}
+
+/// Everything needed to reproduce a render from a scene, missing fields take
+/// their default value
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RenderSettings {
+    pub width: usize,
+    pub height: usize,
+    /// Exposure in stops
+    pub exposure: f64,
+    pub tone_mapping: ToneMapping,
+    pub encoding: Encoding,
+    /// Denoiser run before the effects, disabled when absent
+    pub denoise: Option<Denoiser>,
+    /// Effects applied in order on the linear framebuffer
+    pub effects: Vec<EffectSettings>,
+    /// Path of the rendered image
+    pub output: String,
+    /// Format of `output`, found from its extension when absent
+    pub format: Option<Format>,
+}
+
+impl Default for RenderSettings {
+    fn default() -> Self {
+        RenderSettings {
+            width: 1920 / 2,
+            height: 1080 / 2,
+            exposure: 0f64,
+            tone_mapping: ToneMapping::Clamp,
+            encoding: Encoding::Srgb,
+            denoise: None,
+            effects: Vec::new(),
+            output: String::from("output_0.png"),
+            format: None,
+        }
+    }
+}
+
+impl RenderSettings {
+    pub fn from_file(path: &str) -> Result<RenderSettings, Box<dyn Error>> {
+        let reader = BufReader::new(File::open(path)?);
+        let settings: RenderSettings = serde_json::from_reader(reader)?;
+        if let Some(denoiser) = &settings.denoise {
+            denoiser.validate()?;
+        }
+        for effect in &settings.effects {
+            if let EffectSettings::Vignette(vignette) = effect {
+                vignette.validate()?;
+            }
+        }
+        Ok(settings)
+    }
+
+    /// Denoises `img`, runs the effect chain and sets its output transform
+    pub fn apply(&self, img: &mut Image) -> Result<(), Box<dyn Error>> {
+        if let Some(denoiser) = &self.denoise {
+            denoiser.apply(img)?;
+        }
+
+        for effect in &self.effects {
+            effect.effect().apply(img);
+        }
+
+        img.with_tone_mapper(ToneMapper::new(self.tone_mapping, self.exposure))
+            .with_encoding(self.encoding);
+
+        Ok(())
+    }
+
+    /// Writes `img` to `output` in the format of the settings
+    pub fn save(&self, img: &Image) -> Result<(), Box<dyn Error>> {
+        let format = match self.format {
+            Some(format) => format,
+            None => Format::from_extension(&self.output)?,
+        };
+        img.save_as(&self.output, format)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::color::{Color, Encoding};
+    use crate::img::{Format, Image};
+    use crate::post::bloom::Bloom;
+    use crate::post::vignette::Vignette;
+    use crate::settings::{EffectSettings, RenderSettings};
+    use crate::tonemap::ToneMapping;
+
+    #[test]
+    fn parse() {
+        let json = r#"{
+            "width": 80,
+            "exposure": -1,
+            "tone_mapping": "aces",
+            "denoise": { "iterations": 2 },
+            "output": "render.exr",
+            "effects": [
+                { "type": "bloom", "threshold": 2 },
+                { "type": "vignette" },
+                { "type": "curves", "master": [[0, 0.1], [1, 0.9]] }
+            ]
+        }"#;
+        let settings: RenderSettings = serde_json::from_str(json).unwrap();
+
+        assert_eq!((settings.width, settings.height), (80, 540));
+        assert_eq!(settings.tone_mapping, ToneMapping::Aces);
+        assert_eq!(settings.encoding, Encoding::Srgb);
+        assert_eq!(settings.denoise.unwrap().iterations, 2);
+        assert_eq!(settings.output, "render.exr");
+        assert_eq!(settings.format, None);
+        assert_eq!(settings.effects.len(), 3);
+        assert_eq!(
+            settings.effects[0],
+            EffectSettings::Bloom(Bloom {
+                threshold: 2f64,
+                ..Bloom::default()
+            })
+        );
+        assert_eq!(
+            settings.effects[1],
+            EffectSettings::Vignette(Vignette::default())
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let settings = RenderSettings {
+            effects: vec![EffectSettings::Vignette(Vignette::default())],
+            ..RenderSettings::default()
+        };
+        let json = serde_json::to_string(&settings).unwrap();
+
+        assert_eq!(
+            serde_json::from_str::<RenderSettings>(&json).unwrap(),
+            settings
+        );
+    }
+
+    #[test]
+    fn apply() {
+        let mut img = Image::new(1, 1);
+        img.push(Color::new(128, 128, 128));
+
+        let settings = RenderSettings {
+            exposure: 1f64,
+            encoding: Encoding::Linear,
+            ..RenderSettings::default()
+        };
+        settings.apply(&mut img).unwrap();
+
+        assert_eq!(img.pixels()[0], Color::new(128, 128, 128));
+        assert_eq!(img.tone_mapper().exposure, 1f64);
+    }
+
+    #[test]
+    fn save() {
+        let mut img = Image::new(1, 1);
+        img.push(Color::WHITE);
+
+        // The format wins over the extension
+        let path = std::env::temp_dir().join("raytracer_settings_save.png");
+        let settings = RenderSettings {
+            output: String::from(path.to_str().unwrap()),
+            format: Some(Format::Hdr),
+            ..RenderSettings::default()
+        };
+        settings.save(&img).unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        assert!(bytes.starts_with(b"#?RADIANCE"));
+
+        let settings = RenderSettings {
+            output: String::from("render.tiff"),
+            ..RenderSettings::default()
+        };
+        assert!(settings.save(&img).is_err());
+    }
+
+    #[test]
+    fn invalid_effects() {
+        let path = std::env::temp_dir().join("raytracer_settings_vignette.json");
+        std::fs::write(
+            &path,
+            r#"{ "effects": [{ "type": "vignette", "strength": 2 }] }"#,
+        )
+        .unwrap();
+
+        let error = RenderSettings::from_file(path.to_str().unwrap()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "vignette strength must be between 0 and 1, not 2"
+        );
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,123 @@
+//! Bricks and tiles separated by mortar
+use crate::color::Color;
+use crate::texture::{GetColor, LightCoefficients, Mapping, ShadingContext, TextureTrait};
+use crate::vector::Vector;
+
+/// Rows of bricks along `x`, stacked along `y`
+///
+/// Every other row is shifted by `offset` times the brick length, an offset
+/// of `0` lays tiles.
+pub struct BrickTexture {
+    pub brick: Box<dyn TextureTrait>,
+    pub mortar: Box<dyn TextureTrait>,
+    /// Size of a brick with its mortar, `z` being ignored with `Mapping::Uv`
+    pub size: Vector,
+    /// Thickness of the mortar between two bricks
+    pub mortar_width: f64,
+    pub offset: f64,
+    pub mapping: Mapping,
+}
+
+impl BrickTexture {
+    fn is_mortar(&self, ctx: &ShadingContext) -> bool {
+        let c = self.mapping.coordinates(ctx);
+        let row = (c.y / self.size.y).floor();
+        let shift = row * self.offset;
+
+        // Distance to the closest joint along an axis
+        let joint = |value: f64, size: f64| {
+            let d = value.rem_euclid(size);
+            d.min(size - d) < self.mortar_width / 2f64
+        };
+
+        joint(c.y, self.size.y)
+            || joint(c.x + shift * self.size.x, self.size.x)
+            || (self.mapping == Mapping::Solid && joint(c.z + shift * self.size.z, self.size.z))
+    }
+
+    fn select(&self, ctx: &ShadingContext) -> &dyn TextureTrait {
+        if self.is_mortar(ctx) {
+            self.mortar.as_ref()
+        } else {
+            self.brick.as_ref()
+        }
+    }
+}
+
+impl LightCoefficients for BrickTexture {
+    fn coefficients(&self, ctx: &ShadingContext) -> (f64, f64, f64) {
+        self.select(ctx).coefficients(ctx)
+    }
+}
+
+impl GetColor for BrickTexture {
+    fn color(&self, ctx: &ShadingContext) -> Color {
+        self.select(ctx).color(ctx)
+    }
+}
+
+impl TextureTrait for BrickTexture {}
+
+#[cfg(test)]
+mod tests {
+    use crate::color::Color;
+    use crate::point::Point;
+    use crate::texture::brick::BrickTexture;
+    use crate::texture::uniform::UniformTexture;
+    use crate::texture::{GetColor, Mapping, ShadingContext};
+    use crate::uv::Uv;
+    use crate::vector::Vector;
+
+    fn bricks(offset: f64, mapping: Mapping) -> BrickTexture {
+        let uniform = |color| {
+            Box::new(UniformTexture {
+                kd: 1f64,
+                ks: 0f64,
+                ka: 0f64,
+                color,
+            })
+        };
+
+        BrickTexture {
+            brick: uniform(Color::RED),
+            mortar: uniform(Color::WHITE),
+            size: Vector::new(2f64, 1f64, 1f64),
+            mortar_width: 0.2f64,
+            offset,
+            mapping,
+        }
+    }
+
+    #[test]
+    fn bricks_are_staggered() {
+        let bricks = bricks(0.5f64, Mapping::Solid);
+        let color = |x, y| bricks.color(&ShadingContext::at(Point::new(x, y, 0.25f64)));
+
+        assert_eq!(color(1f64, 0.5f64), Color::RED);
+        assert_eq!(color(2f64, 0.5f64), Color::WHITE);
+        assert_eq!(color(1f64, 1f64), Color::WHITE);
+        // The next row is shifted by half a brick
+        assert_eq!(color(1f64, 1.5f64), Color::WHITE);
+        assert_eq!(color(2f64, 1.5f64), Color::RED);
+        // Joints between rows of bricks in depth
+        assert_eq!(
+            bricks.color(&ShadingContext::at(Point::new(1f64, 0.5f64, 0f64))),
+            Color::WHITE
+        );
+    }
+
+    #[test]
+    fn tiles() {
+        let tiles = bricks(0f64, Mapping::Uv);
+        let color = |u, v| {
+            tiles.color(&ShadingContext {
+                uv: Uv::new(u, v),
+                ..ShadingContext::at(Point::ZERO)
+            })
+        };
+
+        assert_eq!(color(1f64, 0.5f64), Color::RED);
+        assert_eq!(color(1f64, 1.5f64), Color::RED);
+        assert_eq!(color(2f64, 1.5f64), Color::WHITE);
+    }
+}
@@ -0,0 +1,101 @@
+//! Checkerboard of two textures
+use crate::color::Color;
+use crate::texture::{GetColor, LightCoefficients, Mapping, ShadingContext, TextureTrait};
+
+/// Alternates `even` and `odd` every `scale` units along each axis
+///
+/// With `Mapping::Solid` the cells are cubes, with `Mapping::Uv` they are
+/// squares of the surface.
+pub struct CheckerTexture {
+    pub even: Box<dyn TextureTrait>,
+    pub odd: Box<dyn TextureTrait>,
+    /// Side of a cell
+    pub scale: f64,
+    pub mapping: Mapping,
+}
+
+impl CheckerTexture {
+    fn select(&self, ctx: &ShadingContext) -> &dyn TextureTrait {
+        let c = self.mapping.coordinates(ctx) / self.scale;
+        let sum = c.x.floor() + c.y.floor() + c.z.floor();
+
+        if sum.rem_euclid(2f64) == 0f64 {
+            self.even.as_ref()
+        } else {
+            self.odd.as_ref()
+        }
+    }
+}
+
+impl LightCoefficients for CheckerTexture {
+    fn coefficients(&self, ctx: &ShadingContext) -> (f64, f64, f64) {
+        self.select(ctx).coefficients(ctx)
+    }
+}
+
+impl GetColor for CheckerTexture {
+    fn color(&self, ctx: &ShadingContext) -> Color {
+        self.select(ctx).color(ctx)
+    }
+}
+
+impl TextureTrait for CheckerTexture {}
+
+#[cfg(test)]
+mod tests {
+    use crate::color::Color;
+    use crate::point::Point;
+    use crate::texture::checker::CheckerTexture;
+    use crate::texture::uniform::UniformTexture;
+    use crate::texture::{GetColor, LightCoefficients, Mapping, ShadingContext};
+    use crate::uv::Uv;
+
+    fn checker(mapping: Mapping) -> CheckerTexture {
+        CheckerTexture {
+            even: Box::new(UniformTexture {
+                kd: 1f64,
+                ks: 0f64,
+                ka: 0f64,
+                color: Color::WHITE,
+            }),
+            odd: Box::new(UniformTexture {
+                kd: 0.5f64,
+                ks: 1f64,
+                ka: 0f64,
+                color: Color::BLACK,
+            }),
+            scale: 0.5f64,
+            mapping,
+        }
+    }
+
+    #[test]
+    fn solid() {
+        let checker = checker(Mapping::Solid);
+        let color = |x, y, z| checker.color(&ShadingContext::at(Point::new(x, y, z)));
+
+        assert_eq!(color(0.1f64, 0.1f64, 0.1f64), Color::WHITE);
+        assert_eq!(color(0.6f64, 0.1f64, 0.1f64), Color::BLACK);
+        assert_eq!(color(0.6f64, 0.6f64, 0.1f64), Color::WHITE);
+        assert_eq!(color(0.6f64, 0.6f64, 0.6f64), Color::BLACK);
+        // Negative cells keep alternating
+        assert_eq!(color(-0.1f64, 0.1f64, 0.1f64), Color::BLACK);
+
+        let ctx = ShadingContext::at(Point::new(0.6f64, 0.1f64, 0.1f64));
+        assert_eq!(checker.coefficients(&ctx), (0.5f64, 1f64, 0f64));
+    }
+
+    #[test]
+    fn uv() {
+        let checker = checker(Mapping::Uv);
+        let color = |u, v| {
+            checker.color(&ShadingContext {
+                uv: Uv::new(u, v),
+                ..ShadingContext::at(Point::new(0.6f64, 0f64, 0f64))
+            })
+        };
+
+        assert_eq!(color(0.1f64, 0.1f64), Color::WHITE);
+        assert_eq!(color(0.1f64, 0.6f64), Color::BLACK);
+    }
+}
@@ -0,0 +1,84 @@
+//! Clouds from fractional Brownian motion
+use crate::color::Color;
+use crate::noise::perlin::Perlin;
+use crate::noise::{fbm, smoothstep, Octaves};
+use crate::texture::{
+    lerp_color, GetColor, LightCoefficients, Mapping, ShadingContext, TextureTrait,
+};
+
+/// `cloud` where the noise is high enough, `sky` elsewhere
+pub struct CloudTexture {
+    pub kd: f64,
+    pub ks: f64,
+    pub ka: f64,
+
+    pub sky: Color,
+    pub cloud: Color,
+    pub noise: Perlin,
+    /// Frequency of the noise
+    pub scale: f64,
+    /// Roughly the covered fraction of the sky, from `0` to `1`
+    pub cover: f64,
+    pub octaves: Octaves,
+    pub mapping: Mapping,
+}
+
+impl LightCoefficients for CloudTexture {
+    fn coefficients(&self, _ctx: &ShadingContext) -> (f64, f64, f64) {
+        (self.kd, self.ks, self.ka)
+    }
+}
+
+impl GetColor for CloudTexture {
+    fn color(&self, ctx: &ShadingContext) -> Color {
+        let c = self.mapping.coordinates(ctx) * self.scale;
+        // fBm mostly stays within `[-0.5, 0.5]`
+        let density = 0.5f64 + fbm(&self.noise, c, self.octaves);
+        let threshold = 1f64 - self.cover;
+        let t = smoothstep(threshold - 0.1f64, threshold + 0.1f64, density);
+
+        lerp_color(self.sky, self.cloud, t)
+    }
+}
+
+impl TextureTrait for CloudTexture {}
+
+#[cfg(test)]
+mod tests {
+    use crate::color::Color;
+    use crate::noise::perlin::Perlin;
+    use crate::noise::Octaves;
+    use crate::point::Point;
+    use crate::texture::cloud::CloudTexture;
+    use crate::texture::{GetColor, Mapping, ShadingContext};
+
+    fn covered(cover: f64) -> f64 {
+        let clouds = CloudTexture {
+            kd: 1f64,
+            ks: 0f64,
+            ka: 0f64,
+            sky: Color::BLUE,
+            cloud: Color::WHITE,
+            noise: Perlin::new(5),
+            scale: 1f64,
+            cover,
+            octaves: Octaves::default(),
+            mapping: Mapping::Solid,
+        };
+
+        let count = (0..400)
+            .map(|i| Point::new((i % 20) as f64 * 0.37f64, (i / 20) as f64 * 0.37f64, 0.5f64))
+            .filter(|&p| clouds.color(&ShadingContext::at(p)).values().0 > 128)
+            .count();
+        count as f64 / 400f64
+    }
+
+    #[test]
+    fn cover() {
+        assert_eq!(covered(0f64), 0f64);
+        assert_eq!(covered(1f64), 1f64);
+
+        let half = covered(0.5f64);
+        assert!(half > 0.2f64 && half < 0.8f64, "{}", half);
+    }
+}
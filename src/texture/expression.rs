@@ -0,0 +1,86 @@
+//! Texture computed from a math expression
+use crate::color::Color;
+use crate::expression::{Expr, Noises};
+use crate::texture::remap::Ramp;
+use crate::texture::{GetColor, LightCoefficients, ShadingContext, TextureTrait};
+use std::error::Error;
+
+/// Colors of `ramp` at the value of `expr`, or grays from black at `0` to
+/// white at `1` without a ramp
+pub struct ExpressionTexture {
+    pub kd: f64,
+    pub ks: f64,
+    pub ka: f64,
+
+    pub expr: Expr,
+    /// Noises of the `noise`, `fbm`, `turbulence` and `worley` functions
+    pub noises: Noises,
+    pub ramp: Option<Ramp>,
+}
+
+impl ExpressionTexture {
+    /// Parses `source`, noises being seeded by `seed`
+    pub fn new(
+        source: &str,
+        seed: u64,
+        ramp: Option<Vec<(f64, Color)>>,
+        (kd, ks, ka): (f64, f64, f64),
+    ) -> Result<ExpressionTexture, Box<dyn Error>> {
+        Ok(ExpressionTexture {
+            kd,
+            ks,
+            ka,
+            expr: Expr::parse(source).map_err(|e| format!("`{}`: {}", source, e))?,
+            noises: Noises::new(seed),
+            ramp: ramp.map(Ramp::new).transpose()?,
+        })
+    }
+}
+
+impl LightCoefficients for ExpressionTexture {
+    fn coefficients(&self, _ctx: &ShadingContext) -> (f64, f64, f64) {
+        (self.kd, self.ks, self.ka)
+    }
+}
+
+impl GetColor for ExpressionTexture {
+    fn color(&self, ctx: &ShadingContext) -> Color {
+        let t = self.expr.eval(ctx, &self.noises);
+        match &self.ramp {
+            Some(ramp) => ramp.color(t),
+            None => Color::WHITE * t.clamp(0f64, 1f64),
+        }
+    }
+}
+
+impl TextureTrait for ExpressionTexture {}
+
+#[cfg(test)]
+mod tests {
+    use crate::color::Color;
+    use crate::point::Point;
+    use crate::texture::expression::ExpressionTexture;
+    use crate::texture::{GetColor, ShadingContext};
+
+    #[test]
+    fn ramp() {
+        let ramp = vec![(0f64, Color::BLACK), (1f64, Color::RED)];
+        let texture = ExpressionTexture::new("x / 4", 0, Some(ramp), (1f64, 0f64, 0f64)).unwrap();
+        let at = |x: f64| texture.color(&ShadingContext::at(Point::new(x, 0f64, 0f64)));
+
+        assert_eq!(at(-1f64), Color::BLACK);
+        assert_eq!(at(4f64).values(), (255, 0, 0));
+        assert_eq!(at(2f64).values(), (188, 0, 0));
+    }
+
+    #[test]
+    fn gray() {
+        let texture = ExpressionTexture::new("u", 0, None, (1f64, 0f64, 0f64)).unwrap();
+        assert_eq!(
+            texture.color(&ShadingContext::at(Point::ZERO)),
+            Color::BLACK
+        );
+        assert!(ExpressionTexture::new("u +", 0, None, (1f64, 0f64, 0f64)).is_err());
+        assert!(ExpressionTexture::new("u", 0, Some(Vec::new()), (1f64, 0f64, 0f64)).is_err());
+    }
+}
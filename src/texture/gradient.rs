@@ -0,0 +1,125 @@
+//! Gradient between two textures
+use crate::color::Color;
+use crate::point::Point;
+use crate::texture::{
+    lerp_coefficients, lerp_color, GetColor, LightCoefficients, Mapping, ShadingContext,
+    TextureTrait,
+};
+use crate::vector::Vector;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GradientKind {
+    /// Varies along `axis` only
+    #[default]
+    Linear,
+    /// Varies with the distance to `origin`
+    Radial,
+}
+
+/// Blends `start` at `origin` into `end` at `origin + axis`, both being
+/// extended past the ends
+pub struct GradientTexture {
+    pub start: Box<dyn TextureTrait>,
+    pub end: Box<dyn TextureTrait>,
+    pub kind: GradientKind,
+    pub origin: Point,
+    /// Direction of the gradient, its length being the distance `end` is
+    /// reached at
+    pub axis: Vector,
+    pub mapping: Mapping,
+}
+
+impl GradientTexture {
+    /// Position of `ctx` from `0` at `start` to `1` at `end`
+    fn t(&self, ctx: &ShadingContext) -> f64 {
+        let d = self.mapping.coordinates(ctx) - self.origin;
+        let t = match self.kind {
+            GradientKind::Linear => (d * self.axis) / (self.axis * self.axis),
+            GradientKind::Radial => d.mag() / self.axis.mag(),
+        };
+
+        t.clamp(0f64, 1f64)
+    }
+}
+
+impl LightCoefficients for GradientTexture {
+    fn coefficients(&self, ctx: &ShadingContext) -> (f64, f64, f64) {
+        lerp_coefficients(
+            self.start.coefficients(ctx),
+            self.end.coefficients(ctx),
+            self.t(ctx),
+        )
+    }
+}
+
+impl GetColor for GradientTexture {
+    fn color(&self, ctx: &ShadingContext) -> Color {
+        lerp_color(self.start.color(ctx), self.end.color(ctx), self.t(ctx))
+    }
+}
+
+impl TextureTrait for GradientTexture {}
+
+#[cfg(test)]
+mod tests {
+    use crate::color::Color;
+    use crate::point::Point;
+    use crate::texture::gradient::{GradientKind, GradientTexture};
+    use crate::texture::uniform::UniformTexture;
+    use crate::texture::{GetColor, LightCoefficients, Mapping, ShadingContext};
+    use crate::vector::Vector;
+
+    fn gradient(kind: GradientKind) -> GradientTexture {
+        GradientTexture {
+            start: Box::new(UniformTexture {
+                kd: 0f64,
+                ks: 1f64,
+                ka: 0f64,
+                color: Color::BLACK,
+            }),
+            end: Box::new(UniformTexture {
+                kd: 1f64,
+                ks: 0f64,
+                ka: 0f64,
+                color: Color::WHITE,
+            }),
+            kind,
+            origin: Point::new(1f64, 0f64, 0f64),
+            axis: Vector::new(2f64, 0f64, 0f64),
+            mapping: Mapping::Solid,
+        }
+    }
+
+    #[test]
+    fn linear() {
+        let gradient = gradient(GradientKind::Linear);
+        let ctx = |x, y| ShadingContext::at(Point::new(x, y, 0f64));
+
+        assert_eq!(gradient.color(&ctx(0f64, 0f64)).values(), (0, 0, 0));
+        assert_eq!(gradient.color(&ctx(5f64, 0f64)).values(), (255, 255, 255));
+        // Halfway in linear light
+        assert_eq!(gradient.color(&ctx(2f64, 3f64)).values(), (188, 188, 188));
+        assert_eq!(
+            gradient.coefficients(&ctx(2f64, 0f64)),
+            (0.5f64, 0.5f64, 0f64)
+        );
+    }
+
+    #[test]
+    fn radial() {
+        let gradient = gradient(GradientKind::Radial);
+        let ctx = |x, y| ShadingContext::at(Point::new(x, y, 0f64));
+
+        assert_eq!(
+            gradient.coefficients(&ctx(1f64, 1f64)),
+            (0.5f64, 0.5f64, 0f64)
+        );
+        assert_eq!(
+            gradient.coefficients(&ctx(1f64, -1f64)),
+            (0.5f64, 0.5f64, 0f64)
+        );
+        assert_eq!(gradient.color(&ctx(-2f64, 0f64)).values(), (255, 255, 255));
+    }
+}
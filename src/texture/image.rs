@@ -0,0 +1,252 @@
+//! Texture sampling an image file with texture coordinates
+use crate::color::{Color, Encoding};
+use crate::img::Image;
+use crate::texture::mipmap::MipMap;
+use crate::texture::{GetColor, LightCoefficients, ShadingContext, TextureTrait};
+use crate::uv::{Footprint, Uv};
+use crate::vector::Vector;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+
+/// How texels are combined when a sample falls between them
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Filtering {
+    /// Closest texel
+    Nearest,
+    /// Weighted average of the 4 closest texels
+    #[default]
+    Bilinear,
+    /// Bilinear lookups of the two mip levels matching the footprint
+    Trilinear,
+    /// Several trilinear lookups along the longest axis of the footprint
+    Anisotropic,
+}
+
+/// Image repeated over the texture coordinates, `v` going up from the
+/// bottom row
+pub struct ImageTexture {
+    pub kd: f64,
+    pub ks: f64,
+    pub ka: f64,
+
+    /// Linear texels and their mip levels
+    pub mipmap: MipMap,
+    /// Coverage of the texels, in every channel, for cutouts
+    pub alpha: Option<MipMap>,
+    pub filtering: Filtering,
+}
+
+impl ImageTexture {
+    /// Builds the mip pyramid of the linear pixels of `image`
+    pub fn new(image: &Image, filtering: Filtering, (kd, ks, ka): (f64, f64, f64)) -> ImageTexture {
+        let texels = image.pixels().iter().map(|c| c.to_vec()).collect();
+
+        ImageTexture {
+            kd,
+            ks,
+            ka,
+            mipmap: MipMap::new(image.width(), image.height(), texels),
+            alpha: None,
+            filtering,
+        }
+    }
+
+    /// Adds the coverage of every texel, row by row like the image
+    pub fn with_alpha(mut self, alpha: &[f64]) -> ImageTexture {
+        let base = self.mipmap.base();
+        let texels = alpha.iter().map(|&a| Vector::ONE * a).collect();
+        self.alpha = Some(MipMap::new(base.width, base.height, texels));
+        self
+    }
+
+    /// Reads any file supported by `Image::load`, 8-bit files being sRGB
+    pub fn load(
+        filename: &str,
+        filtering: Filtering,
+        coefficients: (f64, f64, f64),
+    ) -> Result<ImageTexture, Box<dyn Error>> {
+        let image = Image::load(filename, Encoding::Srgb)?;
+        if image.pixels().len() != image.width() * image.height() {
+            return Err(format!("{}: incomplete image", filename).into());
+        }
+
+        let texture = ImageTexture::new(&image, filtering, coefficients);
+        Ok(match Image::load_alpha(filename)? {
+            Some(alpha) => texture.with_alpha(&alpha),
+            None => texture,
+        })
+    }
+
+    /// Lookup of `mipmap` with the filtering of the texture
+    fn filter(&self, mipmap: &MipMap, uv: Uv, footprint: Footprint) -> Vector {
+        match self.filtering {
+            Filtering::Nearest => mipmap.base().nearest(uv),
+            Filtering::Bilinear => mipmap.base().bilinear(uv),
+            Filtering::Trilinear => mipmap.trilinear(uv, footprint),
+            Filtering::Anisotropic => mipmap.anisotropic(uv, footprint),
+        }
+    }
+
+    /// Filtered linear color at `uv`, nearest and bilinear filtering ignore
+    /// the footprint
+    pub fn sample(&self, uv: Uv, footprint: Footprint) -> Color {
+        Color::from(self.filter(&self.mipmap, uv, footprint))
+    }
+}
+
+impl LightCoefficients for ImageTexture {
+    fn coefficients(&self, _ctx: &ShadingContext) -> (f64, f64, f64) {
+        (self.kd, self.ks, self.ka)
+    }
+}
+
+impl GetColor for ImageTexture {
+    fn color(&self, ctx: &ShadingContext) -> Color {
+        // Colors are returned in sRGB like every other texture
+        self.sample(ctx.uv, ctx.footprint).to_srgb()
+    }
+
+    fn alpha(&self, ctx: &ShadingContext) -> f64 {
+        self.alpha
+            .as_ref()
+            .map_or(1f64, |alpha| self.filter(alpha, ctx.uv, ctx.footprint).x)
+    }
+
+    fn has_alpha(&self) -> bool {
+        self.alpha.is_some()
+    }
+}
+
+impl TextureTrait for ImageTexture {}
+
+#[cfg(test)]
+mod tests {
+    use crate::color::Color;
+    use crate::img::Image;
+    use crate::point::Point;
+    use crate::texture::image::{Filtering, ImageTexture};
+    use crate::texture::{GetColor, ShadingContext};
+    use crate::uv::{Footprint, Uv};
+
+    /// Black and white columns over a red and blue row
+    fn texture(filtering: Filtering) -> ImageTexture {
+        let mut image = Image::new(2, 2);
+        for c in [Color::BLACK, Color::WHITE, Color::RED, Color::BLUE] {
+            image.push(c);
+        }
+
+        ImageTexture::new(&image, filtering, (1f64, 0f64, 0f64))
+    }
+
+    #[test]
+    fn nearest() {
+        let texture = texture(Filtering::Nearest);
+
+        assert_eq!(
+            texture.sample(Uv::new(0.1f64, 0.9f64), Footprint::default()),
+            Color::BLACK
+        );
+        assert_eq!(
+            texture.sample(Uv::new(0.9f64, 0.9f64), Footprint::default()),
+            Color::WHITE
+        );
+        assert_eq!(
+            texture.sample(Uv::new(0.1f64, 0.1f64), Footprint::default()),
+            Color::RED
+        );
+        // Coordinates repeat
+        assert_eq!(
+            texture.sample(Uv::new(-0.1f64, 2.1f64), Footprint::default()),
+            Color::BLUE
+        );
+    }
+
+    #[test]
+    fn bilinear() {
+        let texture = texture(Filtering::Bilinear);
+
+        assert_eq!(
+            texture.sample(Uv::new(0.25f64, 0.75f64), Footprint::default()),
+            Color::BLACK
+        );
+        assert_eq!(
+            texture
+                .sample(Uv::new(0.5f64, 0.75f64), Footprint::default())
+                .values(),
+            (128, 128, 128)
+        );
+        // Halfway between the four texels
+        assert_eq!(
+            texture
+                .sample(Uv::new(0.5f64, 0.5f64), Footprint::default())
+                .values(),
+            (128, 64, 128)
+        );
+        // Wraps around the left edge
+        assert_eq!(
+            texture
+                .sample(Uv::new(0f64, 0.75f64), Footprint::default())
+                .values(),
+            (128, 128, 128)
+        );
+    }
+
+    #[test]
+    fn color_is_srgb() {
+        let mut image = Image::new(1, 1);
+        image.push(Color::new(10, 128, 250).to_linear());
+        let texture = ImageTexture::new(&image, Filtering::Nearest, (1f64, 0f64, 0f64));
+
+        assert_eq!(
+            texture.color(&ShadingContext::at(Point::ZERO)).values(),
+            (10, 128, 250)
+        );
+    }
+
+    #[test]
+    fn load() {
+        let path = std::env::temp_dir().join("raytracer_image_texture.png");
+        let path = path.to_str().unwrap();
+
+        let mut image = Image::new(1, 2);
+        image.push(Color::RED.to_linear());
+        image.push(Color::GREEN.to_linear());
+        image.save_png(path).unwrap();
+
+        let texture = ImageTexture::load(path, Filtering::Nearest, (1f64, 0f64, 0f64)).unwrap();
+        assert_eq!(
+            texture
+                .color(&ShadingContext {
+                    uv: Uv::new(0.75f64, 0f64),
+                    ..ShadingContext::at(Point::ZERO)
+                })
+                .values(),
+            (0, 255, 0)
+        );
+        assert!(ImageTexture::load("missing.png", Filtering::Nearest, (1f64, 0f64, 0f64)).is_err());
+    }
+
+    #[test]
+    fn alpha() {
+        let opaque = texture(Filtering::Bilinear);
+        let ctx = ShadingContext {
+            uv: Uv::new(0.5f64, 0.75f64),
+            ..ShadingContext::at(Point::ZERO)
+        };
+        assert!(!opaque.has_alpha());
+        assert_eq!(opaque.alpha(&ctx), 1f64);
+
+        // Transparent left column, filtered like the colors
+        let cutout = opaque.with_alpha(&[0f64, 1f64, 0f64, 1f64]);
+        assert!(cutout.has_alpha());
+        assert_eq!(cutout.alpha(&ctx), 0.5f64);
+        assert_eq!(
+            cutout.alpha(&ShadingContext {
+                uv: Uv::new(0.25f64, 0.75f64),
+                ..ctx
+            }),
+            0f64
+        );
+    }
+}
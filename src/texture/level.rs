@@ -0,0 +1,69 @@
+//! Texture coloring objects by subdivision level
+use crate::color::Color;
+use crate::texture::{GetColor, LightCoefficients, ShadingContext, TextureTrait};
+use std::error::Error;
+
+/// Picks `colors[level]`, cycling when the level is past the last color
+///
+/// Colors Menger sub cubes by recursion level or turtle branches by depth.
+pub struct LevelTexture {
+    pub kd: f64,
+    pub ks: f64,
+    pub ka: f64,
+
+    /// Never empty, see `new`
+    colors: Vec<Color>,
+}
+
+impl LevelTexture {
+    pub fn new(
+        colors: Vec<Color>,
+        (kd, ks, ka): (f64, f64, f64),
+    ) -> Result<LevelTexture, Box<dyn Error>> {
+        if colors.is_empty() {
+            return Err("a level texture needs at least one color".into());
+        }
+
+        Ok(LevelTexture { kd, ks, ka, colors })
+    }
+}
+
+impl LightCoefficients for LevelTexture {
+    fn coefficients(&self, _ctx: &ShadingContext) -> (f64, f64, f64) {
+        (self.kd, self.ks, self.ka)
+    }
+}
+
+impl GetColor for LevelTexture {
+    fn color(&self, ctx: &ShadingContext) -> Color {
+        self.colors[ctx.level % self.colors.len()]
+    }
+}
+
+impl TextureTrait for LevelTexture {}
+
+#[cfg(test)]
+mod tests {
+    use crate::color::Color;
+    use crate::point::Point;
+    use crate::texture::level::LevelTexture;
+    use crate::texture::{GetColor, ShadingContext};
+
+    #[test]
+    fn cycles() {
+        let texture =
+            LevelTexture::new(vec![Color::RED, Color::GREEN], (1f64, 0f64, 0f64)).unwrap();
+        let color = |level| {
+            texture.color(&ShadingContext {
+                level,
+                ..ShadingContext::at(Point::ZERO)
+            })
+        };
+
+        assert_eq!(color(0), Color::RED);
+        assert_eq!(color(1), Color::GREEN);
+        assert_eq!(color(2), Color::RED);
+
+        assert!(LevelTexture::new(Vec::new(), (1f64, 0f64, 0f64)).is_err());
+    }
+}
@@ -0,0 +1,83 @@
+//! Marble veins from turbulent noise
+use crate::color::Color;
+use crate::noise::perlin::Perlin;
+use crate::noise::{turbulence, Octaves};
+use crate::texture::{
+    lerp_color, GetColor, LightCoefficients, Mapping, ShadingContext, TextureTrait,
+};
+use std::f64::consts::PI;
+
+/// Sine bands along `x` bent by turbulence
+pub struct MarbleTexture {
+    pub kd: f64,
+    pub ks: f64,
+    pub ka: f64,
+
+    pub base: Color,
+    pub vein: Color,
+    pub noise: Perlin,
+    /// Frequency of the noise, veins being `1 / scale` apart
+    pub scale: f64,
+    /// How far veins are bent by the turbulence
+    pub distortion: f64,
+    pub octaves: Octaves,
+    pub mapping: Mapping,
+}
+
+impl LightCoefficients for MarbleTexture {
+    fn coefficients(&self, _ctx: &ShadingContext) -> (f64, f64, f64) {
+        (self.kd, self.ks, self.ka)
+    }
+}
+
+impl GetColor for MarbleTexture {
+    fn color(&self, ctx: &ShadingContext) -> Color {
+        let c = self.mapping.coordinates(ctx) * self.scale;
+        let phase = c.x + self.distortion * turbulence(&self.noise, c, self.octaves);
+        let t = 0.5f64 + 0.5f64 * (phase * PI).sin();
+
+        lerp_color(self.base, self.vein, t)
+    }
+}
+
+impl TextureTrait for MarbleTexture {}
+
+#[cfg(test)]
+mod tests {
+    use crate::color::Color;
+    use crate::noise::perlin::Perlin;
+    use crate::noise::Octaves;
+    use crate::point::Point;
+    use crate::texture::marble::MarbleTexture;
+    use crate::texture::{GetColor, Mapping, ShadingContext};
+
+    #[test]
+    fn veins() {
+        let marble = MarbleTexture {
+            kd: 1f64,
+            ks: 0f64,
+            ka: 0f64,
+            base: Color::WHITE,
+            vein: Color::BLACK,
+            noise: Perlin::new(0),
+            scale: 2f64,
+            distortion: 0f64,
+            octaves: Octaves::default(),
+            mapping: Mapping::Solid,
+        };
+        let color = |x| marble.color(&ShadingContext::at(Point::new(x, 0.3f64, 0.6f64)));
+
+        // Without distortion, bands only depend on `x`
+        assert_eq!(color(0.25f64).values(), (0, 0, 0));
+        assert_eq!(color(0.75f64).values(), (255, 255, 255));
+
+        let marble = MarbleTexture {
+            distortion: 4f64,
+            ..marble
+        };
+        let a = marble.color(&ShadingContext::at(Point::new(0.25f64, 0.3f64, 0.6f64)));
+        let b = marble.color(&ShadingContext::at(Point::new(0.25f64, 0.3f64, 0.6001f64)));
+        assert_ne!(a, Color::BLACK);
+        assert!((a.to_vec() - b.to_vec()).mag() < 5f64);
+    }
+}
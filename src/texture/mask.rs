@@ -0,0 +1,85 @@
+//! Hard edged selection of two textures by a mask texture
+use crate::color::Color;
+use crate::texture::{GetColor, LightCoefficients, ShadingContext, TextureTrait};
+
+/// `inside` where the luminance of `mask` is above `threshold`, `outside`
+/// elsewhere
+pub struct MaskTexture {
+    pub inside: Box<dyn TextureTrait>,
+    pub outside: Box<dyn TextureTrait>,
+    pub mask: Box<dyn TextureTrait>,
+    pub threshold: f64,
+}
+
+impl MaskTexture {
+    fn select(&self, ctx: &ShadingContext) -> &dyn TextureTrait {
+        if self.mask.color(ctx).luminance() > self.threshold {
+            self.inside.as_ref()
+        } else {
+            self.outside.as_ref()
+        }
+    }
+}
+
+impl LightCoefficients for MaskTexture {
+    fn coefficients(&self, ctx: &ShadingContext) -> (f64, f64, f64) {
+        self.select(ctx).coefficients(ctx)
+    }
+}
+
+impl GetColor for MaskTexture {
+    fn color(&self, ctx: &ShadingContext) -> Color {
+        self.select(ctx).color(ctx)
+    }
+
+    fn alpha(&self, ctx: &ShadingContext) -> f64 {
+        self.select(ctx).alpha(ctx)
+    }
+
+    fn has_alpha(&self) -> bool {
+        self.inside.has_alpha() || self.outside.has_alpha()
+    }
+}
+
+impl TextureTrait for MaskTexture {}
+
+#[cfg(test)]
+mod tests {
+    use crate::color::Color;
+    use crate::point::Point;
+    use crate::texture::mask::MaskTexture;
+    use crate::texture::stripes::StripesTexture;
+    use crate::texture::uniform::UniformTexture;
+    use crate::texture::{GetColor, LightCoefficients, Mapping, ShadingContext, TextureTrait};
+    use crate::vector::Vector;
+
+    fn uniform(color: Color, ks: f64) -> Box<dyn TextureTrait> {
+        Box::new(UniformTexture {
+            kd: 1f64,
+            ks,
+            ka: 0f64,
+            color,
+        })
+    }
+
+    #[test]
+    fn select() {
+        let mask = MaskTexture {
+            inside: uniform(Color::RED, 1f64),
+            outside: uniform(Color::GREEN, 0f64),
+            mask: Box::new(StripesTexture {
+                first: uniform(Color::WHITE, 0f64),
+                second: uniform(Color::new(100, 100, 100), 0f64),
+                axis: Vector::new(1f64, 0f64, 0f64),
+                scale: 1f64,
+                mapping: Mapping::Solid,
+            }),
+            threshold: 0.5f64,
+        };
+        let ctx = |x| ShadingContext::at(Point::new(x, 0f64, 0f64));
+
+        assert_eq!(mask.color(&ctx(0.5f64)), Color::RED);
+        assert_eq!(mask.coefficients(&ctx(0.5f64)).1, 1f64);
+        assert_eq!(mask.color(&ctx(1.5f64)), Color::GREEN);
+    }
+}
@@ -0,0 +1,250 @@
+//! Mip pyramids of image textures
+//!
+//! Every level halves the size of the previous one down to a single texel,
+//! lookups pick the level whose texels match the screen footprint so that
+//! minified textures are averaged instead of aliasing.
+use crate::uv::{Footprint, Uv};
+use crate::vector::Vector;
+
+/// Most samples taken along the major axis of an anisotropic footprint
+pub const MAX_ANISOTROPY: usize = 16;
+
+/// Single level of a `MipMap`, texels are linear and rows go down
+#[derive(Debug, Clone, PartialEq)]
+pub struct MipLevel {
+    pub width: usize,
+    pub height: usize,
+    pub texels: Vec<Vector>,
+}
+
+impl MipLevel {
+    /// Texel at `x`, `y`, coordinates wrapping around the edges
+    fn texel(&self, x: isize, y: isize) -> Vector {
+        let (width, height) = (self.width as isize, self.height as isize);
+        let (x, y) = (x.rem_euclid(width), y.rem_euclid(height));
+        self.texels[(y * width + x) as usize]
+    }
+
+    /// Continuous texel coordinates of `uv`, `v` going up from the bottom row
+    fn position(&self, uv: Uv) -> (f64, f64) {
+        (
+            uv.u.rem_euclid(1f64) * self.width as f64,
+            (1f64 - uv.v.rem_euclid(1f64)) * self.height as f64,
+        )
+    }
+
+    pub fn nearest(&self, uv: Uv) -> Vector {
+        let (x, y) = self.position(uv);
+        self.texel(x.floor() as isize, y.floor() as isize)
+    }
+
+    /// Weighted average of the 4 closest texels
+    pub fn bilinear(&self, uv: Uv) -> Vector {
+        let (x, y) = self.position(uv);
+        // Texel centers are at half coordinates
+        let (x, y) = (x - 0.5f64, y - 0.5f64);
+        let (x0, y0) = (x.floor(), y.floor());
+        let (tx, ty) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as isize, y0 as isize);
+
+        let top = self.texel(x0, y0) * (1f64 - tx) + self.texel(x0 + 1, y0) * tx;
+        let bottom = self.texel(x0, y0 + 1) * (1f64 - tx) + self.texel(x0 + 1, y0 + 1) * tx;
+        top * (1f64 - ty) + bottom * ty
+    }
+
+    /// Next level, each texel averaging a 2x2 block, odd edges being repeated
+    fn downsample(&self) -> MipLevel {
+        let width = self.width.div_ceil(2);
+        let height = self.height.div_ceil(2);
+        let at = |x: usize, y: usize| {
+            self.texels[y.min(self.height - 1) * self.width + x.min(self.width - 1)]
+        };
+
+        let texels = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| {
+                (at(2 * x, 2 * y)
+                    + at(2 * x + 1, 2 * y)
+                    + at(2 * x, 2 * y + 1)
+                    + at(2 * x + 1, 2 * y + 1))
+                    / 4f64
+            })
+            .collect();
+
+        MipLevel {
+            width,
+            height,
+            texels,
+        }
+    }
+}
+
+/// Mip pyramid, the first level being the full resolution image
+#[derive(Debug, Clone, PartialEq)]
+pub struct MipMap {
+    levels: Vec<MipLevel>,
+}
+
+impl MipMap {
+    /// Builds every level from `texels`, which has `width * height` values
+    pub fn new(width: usize, height: usize, texels: Vec<Vector>) -> MipMap {
+        let mut levels = vec![MipLevel {
+            width,
+            height,
+            texels,
+        }];
+
+        while let Some(last) = levels.last().filter(|l| l.width > 1 || l.height > 1) {
+            let next = last.downsample();
+            levels.push(next);
+        }
+
+        MipMap { levels }
+    }
+
+    #[allow(dead_code)]
+    pub fn levels(&self) -> &[MipLevel] {
+        &self.levels
+    }
+
+    /// Full resolution level
+    pub fn base(&self) -> &MipLevel {
+        &self.levels[0]
+    }
+
+    /// Length in base level texels of a texture coordinate difference
+    fn texel_length(&self, d: Uv) -> f64 {
+        (d.u * self.base().width as f64).hypot(d.v * self.base().height as f64)
+    }
+
+    /// Bilinear samples of the two levels around `lod`, linearly blended
+    fn sample_lod(&self, uv: Uv, lod: f64) -> Vector {
+        let lod = lod.clamp(0f64, (self.levels.len() - 1) as f64);
+        let level = lod.floor() as usize;
+        let f = lod - level as f64;
+
+        let fine = self.levels[level].bilinear(uv);
+        if f == 0f64 {
+            return fine;
+        }
+        fine * (1f64 - f) + self.levels[level + 1].bilinear(uv) * f
+    }
+
+    /// Trilinear lookup, the level being chosen from the longest axis of the
+    /// footprint
+    pub fn trilinear(&self, uv: Uv, footprint: Footprint) -> Vector {
+        let width = self
+            .texel_length(footprint.dx)
+            .max(self.texel_length(footprint.dy));
+        self.sample_lod(uv, width.log2())
+    }
+
+    /// Anisotropic lookup: up to `MAX_ANISOTROPY` trilinear samples spread
+    /// along the major axis of the footprint, at the level of its minor axis
+    pub fn anisotropic(&self, uv: Uv, footprint: Footprint) -> Vector {
+        let (lx, ly) = (
+            self.texel_length(footprint.dx),
+            self.texel_length(footprint.dy),
+        );
+        let (major, major_length, minor_length) = if lx >= ly {
+            (footprint.dx, lx, ly)
+        } else {
+            (footprint.dy, ly, lx)
+        };
+
+        let ratio = major_length / minor_length.max(f64::EPSILON);
+        let samples = (ratio.ceil() as usize).clamp(1, MAX_ANISOTROPY);
+        let lod = (major_length / samples as f64).log2();
+
+        let sum = (0..samples)
+            .map(|i| {
+                let offset = (i as f64 + 0.5f64) / samples as f64 - 0.5f64;
+                self.sample_lod(uv + major * offset, lod)
+            })
+            .fold(Vector::ZERO, |acc, v| acc + v);
+        sum / samples as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::texture::mipmap::MipMap;
+    use crate::uv::{Footprint, Uv};
+    use crate::vector::Vector;
+
+    /// Black and white columns of `size` texels
+    fn stripes(size: usize) -> MipMap {
+        let texels = (0..size * size)
+            .map(|i| {
+                if i % 2 == 0 {
+                    Vector::ZERO
+                } else {
+                    Vector::ONE
+                }
+            })
+            .collect();
+        MipMap::new(size, size, texels)
+    }
+
+    #[test]
+    fn levels() {
+        let map = MipMap::new(5, 2, vec![Vector::ONE; 10]);
+        let sizes = map
+            .levels()
+            .iter()
+            .map(|l| (l.width, l.height))
+            .collect::<Vec<_>>();
+
+        assert_eq!(sizes, vec![(5, 2), (3, 1), (2, 1), (1, 1)]);
+        assert!(map
+            .levels()
+            .iter()
+            .all(|l| l.texels.len() == l.width * l.height));
+    }
+
+    #[test]
+    fn last_level_is_average() {
+        let map = stripes(8);
+        assert_eq!(
+            map.levels().last().unwrap().texels,
+            vec![Vector::ONE * 0.5f64]
+        );
+    }
+
+    #[test]
+    fn trilinear() {
+        let map = stripes(8);
+        let uv = Uv::new(1f64 / 16f64, 0.5f64);
+
+        // No footprint is a bilinear lookup of the base level
+        assert_eq!(map.trilinear(uv, Footprint::default()), Vector::ZERO);
+
+        // 4 texels wide, stripes are averaged
+        let footprint = Footprint {
+            dx: Uv::new(0.5f64, 0f64),
+            dy: Uv::new(0f64, 0.5f64),
+        };
+        assert_eq!(map.trilinear(uv, footprint), Vector::ONE * 0.5f64);
+    }
+
+    #[test]
+    fn anisotropic_keeps_minor_axis_sharp() {
+        let map = stripes(8);
+        let uv = Uv::new(1f64 / 16f64, 0.5f64);
+        // A quarter texel across the stripes, 4 texels along them
+        let along = Footprint {
+            dx: Uv::new(1f64 / 32f64, 0f64),
+            dy: Uv::new(0f64, 0.5f64),
+        };
+
+        assert!(map.trilinear(uv, along).x > 0.4f64);
+        assert_eq!(map.anisotropic(uv, along), Vector::ZERO);
+
+        // Across the stripes, every sample lands on a different column
+        let across = Footprint {
+            dx: Uv::new(0.5f64, 0f64),
+            dy: Uv::new(0f64, 1f64 / 32f64),
+        };
+        assert!((map.anisotropic(uv, across).x - 0.5f64).abs() < 0.1f64);
+    }
+}
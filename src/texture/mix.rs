@@ -0,0 +1,84 @@
+//! Blend of two textures by a factor texture
+use crate::color::Color;
+use crate::texture::{
+    lerp_coefficients, lerp_color, GetColor, LightCoefficients, ShadingContext, TextureTrait,
+};
+
+/// `a` where `factor` is black, `b` where it is white, blended in between
+///
+/// The factor is the luminance of the declared color of `factor`.
+pub struct MixTexture {
+    pub a: Box<dyn TextureTrait>,
+    pub b: Box<dyn TextureTrait>,
+    pub factor: Box<dyn TextureTrait>,
+}
+
+impl MixTexture {
+    fn t(&self, ctx: &ShadingContext) -> f64 {
+        self.factor.color(ctx).luminance().clamp(0f64, 1f64)
+    }
+}
+
+impl LightCoefficients for MixTexture {
+    fn coefficients(&self, ctx: &ShadingContext) -> (f64, f64, f64) {
+        lerp_coefficients(
+            self.a.coefficients(ctx),
+            self.b.coefficients(ctx),
+            self.t(ctx),
+        )
+    }
+}
+
+impl GetColor for MixTexture {
+    fn color(&self, ctx: &ShadingContext) -> Color {
+        lerp_color(self.a.color(ctx), self.b.color(ctx), self.t(ctx))
+    }
+
+    fn alpha(&self, ctx: &ShadingContext) -> f64 {
+        let t = self.t(ctx);
+        self.a.alpha(ctx) * (1f64 - t) + self.b.alpha(ctx) * t
+    }
+
+    fn has_alpha(&self) -> bool {
+        self.a.has_alpha() || self.b.has_alpha()
+    }
+}
+
+impl TextureTrait for MixTexture {}
+
+#[cfg(test)]
+mod tests {
+    use crate::color::Color;
+    use crate::point::Point;
+    use crate::texture::mix::MixTexture;
+    use crate::texture::uniform::UniformTexture;
+    use crate::texture::{GetColor, LightCoefficients, ShadingContext, TextureTrait};
+    use crate::vector::Vector;
+
+    fn uniform(color: Color, kd: f64) -> Box<dyn TextureTrait> {
+        Box::new(UniformTexture {
+            kd,
+            ks: 0f64,
+            ka: 0f64,
+            color,
+        })
+    }
+
+    #[test]
+    fn factor() {
+        let ctx = ShadingContext::at(Point::ZERO);
+        let mix = |factor| MixTexture {
+            a: uniform(Color::RED, 0f64),
+            b: uniform(Color::BLUE, 1f64),
+            factor: uniform(factor, 0f64),
+        };
+
+        assert_eq!(mix(Color::BLACK).color(&ctx).values(), (255, 0, 0));
+        assert_eq!(mix(Color::WHITE).color(&ctx).values(), (0, 0, 255));
+        assert!((mix(Color::WHITE).coefficients(&ctx).0 - 1f64).abs() < 1e-9);
+
+        let half = mix(Color::from(Vector::ONE * 127.5f64));
+        assert!((half.coefficients(&ctx).0 - 0.5f64).abs() < 1e-9);
+        assert_eq!(half.color(&ctx).values(), (188, 0, 188));
+    }
+}
@@ -1,31 +1,134 @@
 //! Texture container
 use crate::point::Point;
+use crate::uv::{Footprint, Uv};
+use crate::vector::Vector;
 use crate::Color;
+use serde::{Deserialize, Serialize};
 
+pub mod brick;
+pub mod checker;
+pub mod cloud;
+pub mod expression;
+pub mod gradient;
+pub mod image;
+pub mod level;
+pub mod marble;
+pub mod mask;
+pub mod mipmap;
+pub mod mix;
 pub mod random;
+pub mod remap;
+pub mod stripes;
+pub mod transform;
+pub mod triplanar;
 pub mod uniform;
+pub mod wood;
+pub mod worley;
+
+/// Alpha below which a surface is cut out
+pub const ALPHA_CUTOFF: f64 = 0.5;
+
+/// What textures know about the point being shaded
+#[derive(Debug, Copy, Clone)]
+pub struct ShadingContext<'a> {
+    pub point: Point,
+    /// Normal facing the camera
+    pub normal: Vector,
+    pub uv: Uv,
+    /// Texture area covered by the pixel
+    pub footprint: Footprint,
+    /// Id of the object that was hit
+    #[allow(dead_code)]
+    pub id: &'a str,
+    /// Number of reflections before this hit, `0` for camera rays
+    #[allow(dead_code)]
+    pub depth: usize,
+    /// Subdivision level of the hit part of the object, such as the Menger
+    /// sub cube recursion or the turtle branch depth
+    pub level: usize,
+}
+
+impl ShadingContext<'_> {
+    /// Context of a camera ray hitting `point`, with no other information
+    pub fn at(point: Point) -> ShadingContext<'static> {
+        ShadingContext {
+            point,
+            normal: Vector::ZERO,
+            uv: Uv::default(),
+            footprint: Footprint::default(),
+            id: "",
+            depth: 0,
+            level: 0,
+        }
+    }
+}
+
+/// Coordinates procedural patterns are evaluated in
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Mapping {
+    /// World space position, the pattern is carved through objects
+    #[default]
+    Solid,
+    /// Texture coordinates, `u` and `v` being `x` and `y`
+    Uv,
+}
+
+impl Mapping {
+    pub fn coordinates(&self, ctx: &ShadingContext) -> Vector {
+        match self {
+            Mapping::Solid => ctx.point,
+            Mapping::Uv => Vector::new(ctx.uv.u, ctx.uv.v, 0f64),
+        }
+    }
+}
+
+/// Interpolates `a` and `b` by `t`, colors being mixed in linear space
+pub fn lerp_color(a: Color, b: Color, t: f64) -> Color {
+    (a.to_linear() * (1f64 - t) + b.to_linear() * t).to_srgb()
+}
+
+/// Interpolates the coefficients of `a` and `b` by `t`
+pub fn lerp_coefficients(a: (f64, f64, f64), b: (f64, f64, f64), t: f64) -> (f64, f64, f64) {
+    let lerp = |a: f64, b: f64| a * (1f64 - t) + b * t;
+    (lerp(a.0, b.0), lerp(a.1, b.1), lerp(a.2, b.2))
+}
 
 pub trait LightCoefficients {
-    /// Returns lighting coefficients at `p` point
-    fn coefficients(&self, point: Point) -> (f64, f64, f64);
+    /// Returns the diffuse, specular and ambient coefficients
+    fn coefficients(&self, ctx: &ShadingContext) -> (f64, f64, f64);
 }
 
 pub trait GetColor {
-    fn color(&self, p: Point) -> Color;
+    /// Returns the color declared in sRGB
+    fn color(&self, ctx: &ShadingContext) -> Color;
+
+    /// Coverage in `[0, 1]`, rays go through the surface where it is below
+    /// `ALPHA_CUTOFF`
+    fn alpha(&self, _ctx: &ShadingContext) -> f64 {
+        1f64
+    }
+
+    /// Whether `alpha` may be below one, sparing opaque textures the shading
+    /// context of every intersection
+    fn has_alpha(&self) -> bool {
+        false
+    }
 }
 
 pub trait TextureTrait: GetColor + LightCoefficients {}
 
+#[allow(dead_code)]
 pub struct Texture {}
 
 impl LightCoefficients for Texture {
-    fn coefficients(&self, _point: Point) -> (f64, f64, f64) {
+    fn coefficients(&self, _ctx: &ShadingContext) -> (f64, f64, f64) {
         unimplemented!()
     }
 }
 
 impl GetColor for Texture {
-    fn color(&self, _p: Point) -> Color {
+    fn color(&self, _ctx: &ShadingContext) -> Color {
         unimplemented!()
     }
 }
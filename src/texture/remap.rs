@@ -0,0 +1,131 @@
+//! Color remapping through a gradient ramp
+use crate::color::Color;
+use crate::texture::{lerp_color, GetColor, LightCoefficients, ShadingContext, TextureTrait};
+use std::error::Error;
+
+/// Gradient of `(position, color)` stops sorted by position, colors before the
+/// first and after the last stop being extended
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ramp {
+    stops: Vec<(f64, Color)>,
+}
+
+impl Ramp {
+    /// Sorts `stops` by position, returns an error if there are none or if a
+    /// position is not finite
+    pub fn new(mut stops: Vec<(f64, Color)>) -> Result<Ramp, Box<dyn Error>> {
+        if stops.is_empty() {
+            return Err("a ramp needs at least one stop".into());
+        }
+        if let Some((position, _)) = stops.iter().find(|(position, _)| !position.is_finite()) {
+            return Err(format!("ramp stop positions must be finite, not {}", position).into());
+        }
+
+        stops.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+        Ok(Ramp { stops })
+    }
+
+    /// Color of the ramp at `t`
+    pub fn color(&self, t: f64) -> Color {
+        let first = self.stops[0];
+        if t <= first.0 {
+            return first.1;
+        }
+
+        for window in self.stops.windows(2) {
+            let ((p1, c1), (p2, c2)) = (window[0], window[1]);
+            if t <= p2 {
+                return lerp_color(c1, c2, (t - p1) / (p2 - p1));
+            }
+        }
+
+        self.stops[self.stops.len() - 1].1
+    }
+}
+
+/// Replaces the color of `texture` by the color of `ramp` at its luminance
+///
+/// Coefficients are the ones of `texture`.
+pub struct RemapTexture {
+    pub texture: Box<dyn TextureTrait>,
+    ramp: Ramp,
+}
+
+impl RemapTexture {
+    /// Returns an error if `ramp` is not a valid `Ramp`
+    pub fn new(
+        texture: Box<dyn TextureTrait>,
+        ramp: Vec<(f64, Color)>,
+    ) -> Result<RemapTexture, Box<dyn Error>> {
+        Ok(RemapTexture {
+            texture,
+            ramp: Ramp::new(ramp)?,
+        })
+    }
+
+    /// Color of the ramp at `t`
+    pub fn ramp(&self, t: f64) -> Color {
+        self.ramp.color(t)
+    }
+}
+
+impl LightCoefficients for RemapTexture {
+    fn coefficients(&self, ctx: &ShadingContext) -> (f64, f64, f64) {
+        self.texture.coefficients(ctx)
+    }
+}
+
+impl GetColor for RemapTexture {
+    fn color(&self, ctx: &ShadingContext) -> Color {
+        self.ramp(self.texture.color(ctx).luminance())
+    }
+}
+
+impl TextureTrait for RemapTexture {}
+
+#[cfg(test)]
+mod tests {
+    use crate::color::Color;
+    use crate::point::Point;
+    use crate::texture::remap::{Ramp, RemapTexture};
+    use crate::texture::uniform::UniformTexture;
+    use crate::texture::{GetColor, ShadingContext};
+
+    #[test]
+    fn ramp() {
+        // Stops are sorted by position
+        let remap = RemapTexture::new(
+            Box::new(UniformTexture {
+                kd: 1f64,
+                ks: 0f64,
+                ka: 0f64,
+                color: Color::WHITE,
+            }),
+            vec![
+                (0.6f64, Color::RED),
+                (0.2f64, Color::BLACK),
+                (0.8f64, Color::BLUE),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(remap.ramp(0f64), Color::BLACK);
+        assert_eq!(remap.ramp(0.6f64).values(), (255, 0, 0));
+        assert_eq!(remap.ramp(0.7f64).values(), (188, 0, 188));
+        assert_eq!(remap.ramp(0.9f64), Color::BLUE);
+        assert_eq!(
+            remap.color(&ShadingContext::at(Point::ZERO)).values(),
+            (0, 0, 255)
+        );
+    }
+
+    #[test]
+    fn invalid_ramps() {
+        assert!(Ramp::new(Vec::new()).is_err());
+        assert!(Ramp::new(vec![(f64::NAN, Color::RED)]).is_err());
+        assert_eq!(
+            Ramp::new(vec![(1f64, Color::RED), (0f64, Color::BLUE)]).unwrap(),
+            Ramp::new(vec![(0f64, Color::BLUE), (1f64, Color::RED)]).unwrap()
+        );
+    }
+}
@@ -0,0 +1,77 @@
+//! Parallel stripes of two textures
+use crate::color::Color;
+use crate::texture::{GetColor, LightCoefficients, Mapping, ShadingContext, TextureTrait};
+use crate::vector::Vector;
+
+/// Alternates `first` and `second` in bands of `scale` units across `axis`
+pub struct StripesTexture {
+    pub first: Box<dyn TextureTrait>,
+    pub second: Box<dyn TextureTrait>,
+    /// Direction the bands follow one another along
+    pub axis: Vector,
+    /// Width of a band
+    pub scale: f64,
+    pub mapping: Mapping,
+}
+
+impl StripesTexture {
+    fn select(&self, ctx: &ShadingContext) -> &dyn TextureTrait {
+        let d = self.mapping.coordinates(ctx) * self.axis.normalize() / self.scale;
+
+        if d.floor().rem_euclid(2f64) == 0f64 {
+            self.first.as_ref()
+        } else {
+            self.second.as_ref()
+        }
+    }
+}
+
+impl LightCoefficients for StripesTexture {
+    fn coefficients(&self, ctx: &ShadingContext) -> (f64, f64, f64) {
+        self.select(ctx).coefficients(ctx)
+    }
+}
+
+impl GetColor for StripesTexture {
+    fn color(&self, ctx: &ShadingContext) -> Color {
+        self.select(ctx).color(ctx)
+    }
+}
+
+impl TextureTrait for StripesTexture {}
+
+#[cfg(test)]
+mod tests {
+    use crate::color::Color;
+    use crate::point::Point;
+    use crate::texture::stripes::StripesTexture;
+    use crate::texture::uniform::UniformTexture;
+    use crate::texture::{GetColor, Mapping, ShadingContext};
+    use crate::vector::Vector;
+
+    #[test]
+    fn bands() {
+        let uniform = |color| {
+            Box::new(UniformTexture {
+                kd: 1f64,
+                ks: 0f64,
+                ka: 0f64,
+                color,
+            })
+        };
+        let stripes = StripesTexture {
+            first: uniform(Color::RED),
+            second: uniform(Color::BLUE),
+            axis: Vector::new(0f64, 2f64, 0f64),
+            scale: 1f64,
+            mapping: Mapping::Solid,
+        };
+        let color = |x, y| stripes.color(&ShadingContext::at(Point::new(x, y, 0f64)));
+
+        assert_eq!(color(0f64, 0.5f64), Color::RED);
+        assert_eq!(color(0f64, 1.5f64), Color::BLUE);
+        assert_eq!(color(0f64, -0.5f64), Color::BLUE);
+        // Constant along the bands
+        assert_eq!(color(7.3f64, 1.5f64), Color::BLUE);
+    }
+}
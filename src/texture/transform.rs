@@ -0,0 +1,149 @@
+//! Transformation of texture space
+use crate::color::Color;
+use crate::texture::{GetColor, LightCoefficients, ShadingContext, TextureTrait};
+use crate::uv::{Footprint, Uv};
+use crate::vector::Vector;
+
+/// Scales, then rotates, then translates `texture` over objects
+///
+/// Texture coordinates are transformed in 2D with the `x` and `y` components
+/// of `scale` and `translation`, the rotation being of `angle` around `z`.
+pub struct TransformTexture {
+    pub texture: Box<dyn TextureTrait>,
+    pub scale: Vector,
+    pub axis: Vector,
+    /// Rotation around `axis`, in radians
+    pub angle: f64,
+    pub translation: Vector,
+}
+
+impl TransformTexture {
+    pub fn new(texture: Box<dyn TextureTrait>) -> TransformTexture {
+        TransformTexture {
+            texture,
+            scale: Vector::ONE,
+            axis: Vector::new(0f64, 0f64, 1f64),
+            angle: 0f64,
+            translation: Vector::ZERO,
+        }
+    }
+
+    /// Point of texture space seen at `p`
+    pub fn point(&self, p: Vector) -> Vector {
+        (p - self.translation)
+            .rotate_around(&self.axis, -self.angle)
+            .div(self.scale)
+    }
+
+    /// Texture space difference of a texture coordinates difference
+    fn uv_linear(&self, d: Uv) -> Uv {
+        let (sin, cos) = (-self.angle).sin_cos();
+        Uv::new(
+            (d.u * cos - d.v * sin) / self.scale.x,
+            (d.u * sin + d.v * cos) / self.scale.y,
+        )
+    }
+
+    /// Texture coordinates of texture space seen at `uv`
+    pub fn uv(&self, uv: Uv) -> Uv {
+        self.uv_linear(uv - Uv::new(self.translation.x, self.translation.y))
+    }
+
+    fn context<'a>(&self, ctx: &ShadingContext<'a>) -> ShadingContext<'a> {
+        ShadingContext {
+            point: self.point(ctx.point),
+            uv: self.uv(ctx.uv),
+            footprint: Footprint {
+                dx: self.uv_linear(ctx.footprint.dx),
+                dy: self.uv_linear(ctx.footprint.dy),
+            },
+            ..*ctx
+        }
+    }
+}
+
+impl LightCoefficients for TransformTexture {
+    fn coefficients(&self, ctx: &ShadingContext) -> (f64, f64, f64) {
+        self.texture.coefficients(&self.context(ctx))
+    }
+}
+
+impl GetColor for TransformTexture {
+    fn color(&self, ctx: &ShadingContext) -> Color {
+        self.texture.color(&self.context(ctx))
+    }
+
+    fn alpha(&self, ctx: &ShadingContext) -> f64 {
+        self.texture.alpha(&self.context(ctx))
+    }
+
+    fn has_alpha(&self) -> bool {
+        self.texture.has_alpha()
+    }
+}
+
+impl TextureTrait for TransformTexture {}
+
+#[cfg(test)]
+mod tests {
+    use crate::color::Color;
+    use crate::point::Point;
+    use crate::texture::checker::CheckerTexture;
+    use crate::texture::transform::TransformTexture;
+    use crate::texture::uniform::UniformTexture;
+    use crate::texture::{GetColor, Mapping, ShadingContext, TextureTrait};
+    use crate::uv::Uv;
+    use crate::vector::Vector;
+    use std::f64::consts::FRAC_PI_2;
+
+    fn uniform(color: Color) -> Box<dyn TextureTrait> {
+        Box::new(UniformTexture {
+            kd: 1f64,
+            ks: 0f64,
+            ka: 0f64,
+            color,
+        })
+    }
+
+    fn near(a: Vector, b: Vector) -> bool {
+        (a - b).mag() < 1e-9
+    }
+
+    #[test]
+    fn point() {
+        let transform = TransformTexture {
+            scale: Vector::new(2f64, 1f64, 1f64),
+            angle: FRAC_PI_2,
+            translation: Vector::new(1f64, 0f64, 0f64),
+            ..TransformTexture::new(uniform(Color::BLACK))
+        };
+
+        // (1, 0, 0) is scaled to (2, 0, 0), rotated to (0, 2, 0) and moved
+        assert!(near(
+            transform.point(Point::new(1f64, 2f64, 0f64)),
+            Point::new(1f64, 0f64, 0f64)
+        ));
+
+        let uv = transform.uv(Uv::new(1f64, 2f64));
+        assert!((uv.u - 1f64).abs() < 1e-9 && uv.v.abs() < 1e-9);
+    }
+
+    #[test]
+    fn scaled_checker() {
+        let checker = CheckerTexture {
+            even: uniform(Color::WHITE),
+            odd: uniform(Color::BLACK),
+            scale: 1f64,
+            mapping: Mapping::Solid,
+        };
+        let transform = TransformTexture {
+            scale: Vector::ONE * 4f64,
+            ..TransformTexture::new(Box::new(checker))
+        };
+        let color = |x| transform.color(&ShadingContext::at(Point::new(x, 0.5f64, 0.5f64)));
+
+        assert_eq!(color(0.5f64), Color::WHITE);
+        assert_eq!(color(3.5f64), Color::WHITE);
+        assert_eq!(color(4.5f64), Color::BLACK);
+    }
+}
@@ -0,0 +1,160 @@
+//! Texture projected along the three axes
+use crate::color::Color;
+use crate::texture::{GetColor, LightCoefficients, ShadingContext, TextureTrait};
+use crate::uv::{Footprint, Uv};
+use crate::vector::Vector;
+
+/// Projections of `texture` along `x`, `y` and `z`, blended by how much the
+/// surface faces each axis
+///
+/// Projected texture coordinates are world positions divided by `scale`,
+/// oriented like `RectangleInner::face_uv` so that faces are not mirrored.
+/// They have no footprint, images are sampled at full resolution.
+pub struct TriplanarTexture {
+    pub texture: Box<dyn TextureTrait>,
+    /// Size of one texture unit
+    pub scale: f64,
+    /// Exponent of the blend weights, larger values give narrower seams
+    pub sharpness: f64,
+}
+
+impl TriplanarTexture {
+    /// Contexts of the three projections with their normalized weights,
+    /// projections of negligible weight being skipped
+    fn projections<'a>(&self, ctx: &ShadingContext<'a>) -> Vec<(ShadingContext<'a>, f64)> {
+        let n = ctx.normal;
+        let p = ctx.point / self.scale;
+        let weights = Vector::new(n.x.abs(), n.y.abs(), n.z.abs()).powf(self.sharpness);
+        let total = weights.sum();
+        if total <= 0f64 {
+            return vec![(*ctx, 1f64)];
+        }
+
+        let flip = |value: f64, positive: bool| if positive { -value } else { value };
+        [
+            (Uv::new(flip(p.z, n.x > 0f64), p.y), weights.x),
+            (Uv::new(p.x, flip(p.z, n.y > 0f64)), weights.y),
+            (Uv::new(flip(p.x, n.z > 0f64), p.y), weights.z),
+        ]
+        .into_iter()
+        .filter(|&(_, w)| w / total > 1e-4)
+        .map(|(uv, w)| {
+            let ctx = ShadingContext {
+                uv,
+                footprint: Footprint::default(),
+                ..*ctx
+            };
+            (ctx, w / total)
+        })
+        .collect()
+    }
+}
+
+impl LightCoefficients for TriplanarTexture {
+    fn coefficients(&self, ctx: &ShadingContext) -> (f64, f64, f64) {
+        self.projections(ctx)
+            .iter()
+            .fold((0f64, 0f64, 0f64), |(kd, ks, ka), (ctx, w)| {
+                let c = self.texture.coefficients(ctx);
+                (kd + c.0 * w, ks + c.1 * w, ka + c.2 * w)
+            })
+    }
+}
+
+impl GetColor for TriplanarTexture {
+    fn color(&self, ctx: &ShadingContext) -> Color {
+        // Blended in linear space like `lerp_color`
+        self.projections(ctx)
+            .iter()
+            .map(|(ctx, w)| self.texture.color(ctx).to_linear() * *w)
+            .fold(Color::BLACK, |acc, c| acc + c)
+            .to_srgb()
+    }
+
+    fn alpha(&self, ctx: &ShadingContext) -> f64 {
+        self.projections(ctx)
+            .iter()
+            .map(|(ctx, w)| self.texture.alpha(ctx) * w)
+            .sum()
+    }
+
+    fn has_alpha(&self) -> bool {
+        self.texture.has_alpha()
+    }
+}
+
+impl TextureTrait for TriplanarTexture {}
+
+#[cfg(test)]
+mod tests {
+    use crate::color::Color;
+    use crate::point::Point;
+    use crate::texture::checker::CheckerTexture;
+    use crate::texture::triplanar::TriplanarTexture;
+    use crate::texture::uniform::UniformTexture;
+    use crate::texture::{GetColor, Mapping, ShadingContext};
+    use crate::vector::Vector;
+
+    /// Texture coordinates checkerboard, white on the `[0, 1)` square
+    fn triplanar(sharpness: f64) -> TriplanarTexture {
+        let uniform = |color| {
+            Box::new(UniformTexture {
+                kd: 1f64,
+                ks: 0f64,
+                ka: 0f64,
+                color,
+            })
+        };
+
+        TriplanarTexture {
+            texture: Box::new(CheckerTexture {
+                even: uniform(Color::WHITE),
+                odd: uniform(Color::BLACK),
+                scale: 1f64,
+                mapping: Mapping::Uv,
+            }),
+            scale: 2f64,
+            sharpness,
+        }
+    }
+
+    fn color(texture: &TriplanarTexture, point: Point, normal: Vector) -> Color {
+        texture.color(&ShadingContext {
+            normal: normal.normalize(),
+            ..ShadingContext::at(point)
+        })
+    }
+
+    #[test]
+    fn axis_aligned() {
+        let texture = triplanar(4f64);
+        // Seen at (0.5, 2.5) along `z` and at (0.5, 1.5) along `y`
+        let p = Point::new(1f64, 5f64, 3f64);
+
+        assert_eq!(
+            color(&texture, p, Vector::new(0f64, 0f64, -1f64)).values(),
+            Color::WHITE.values()
+        );
+        assert_eq!(
+            color(&texture, p, Vector::new(0f64, -1f64, 0f64)).values(),
+            Color::BLACK.values()
+        );
+    }
+
+    #[test]
+    fn blend() {
+        let p = Point::new(1f64, 5f64, 3f64);
+        let diagonal = Vector::new(0f64, -1f64, -1f64);
+
+        // Halfway between white and black in linear space
+        let soft = color(&triplanar(1f64), p, diagonal);
+        assert_eq!(soft.values(), (188, 188, 188));
+
+        // Sharp blends pick the dominant axis
+        let slanted = Vector::new(0f64, -1f64, -0.8f64);
+        assert_eq!(
+            color(&triplanar(64f64), p, slanted).values(),
+            Color::BLACK.values()
+        );
+    }
+}
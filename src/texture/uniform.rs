@@ -1,6 +1,5 @@
 use crate::color::Color;
-use crate::point::Point;
-use crate::texture::{GetColor, LightCoefficients, TextureTrait};
+use crate::texture::{GetColor, LightCoefficients, ShadingContext, TextureTrait};
 
 pub struct UniformTexture {
     pub kd: f64, // diffusion
@@ -11,13 +10,13 @@ pub struct UniformTexture {
 }
 
 impl LightCoefficients for UniformTexture {
-    fn coefficients(&self, _point: Point) -> (f64, f64, f64) {
+    fn coefficients(&self, _ctx: &ShadingContext) -> (f64, f64, f64) {
         (self.kd, self.ks, self.ka)
     }
 }
 
 impl GetColor for UniformTexture {
-    fn color(&self, _point: Point) -> Color {
+    fn color(&self, _ctx: &ShadingContext) -> Color {
         self.color
     }
 }
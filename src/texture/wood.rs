@@ -0,0 +1,75 @@
+//! Wood growth rings distorted by noise
+use crate::color::Color;
+use crate::noise::perlin::Perlin;
+use crate::noise::{fbm, Octaves};
+use crate::texture::{
+    lerp_color, GetColor, LightCoefficients, Mapping, ShadingContext, TextureTrait,
+};
+use std::f64::consts::PI;
+
+/// Concentric rings around the `y` axis
+pub struct WoodTexture {
+    pub kd: f64,
+    pub ks: f64,
+    pub ka: f64,
+
+    pub light: Color,
+    pub dark: Color,
+    pub noise: Perlin,
+    /// Rings per unit
+    pub scale: f64,
+    /// How far rings are moved by the noise, in rings
+    pub distortion: f64,
+    pub octaves: Octaves,
+    pub mapping: Mapping,
+}
+
+impl LightCoefficients for WoodTexture {
+    fn coefficients(&self, _ctx: &ShadingContext) -> (f64, f64, f64) {
+        (self.kd, self.ks, self.ka)
+    }
+}
+
+impl GetColor for WoodTexture {
+    fn color(&self, ctx: &ShadingContext) -> Color {
+        let c = self.mapping.coordinates(ctx) * self.scale;
+        let rings = c.x.hypot(c.z) + self.distortion * fbm(&self.noise, c, self.octaves);
+        let t = 0.5f64 - 0.5f64 * (rings * 2f64 * PI).cos();
+
+        lerp_color(self.light, self.dark, t)
+    }
+}
+
+impl TextureTrait for WoodTexture {}
+
+#[cfg(test)]
+mod tests {
+    use crate::color::Color;
+    use crate::noise::perlin::Perlin;
+    use crate::noise::Octaves;
+    use crate::point::Point;
+    use crate::texture::wood::WoodTexture;
+    use crate::texture::{GetColor, Mapping, ShadingContext};
+
+    #[test]
+    fn rings() {
+        let wood = WoodTexture {
+            kd: 1f64,
+            ks: 0f64,
+            ka: 0f64,
+            light: Color::WHITE,
+            dark: Color::BLACK,
+            noise: Perlin::new(0),
+            scale: 1f64,
+            distortion: 0f64,
+            octaves: Octaves::default(),
+            mapping: Mapping::Solid,
+        };
+        let color = |x, y, z| wood.color(&ShadingContext::at(Point::new(x, y, z)));
+
+        assert_eq!(color(0f64, 0f64, 1f64).values(), (255, 255, 255));
+        assert_eq!(color(0.5f64, 0f64, 0f64).values(), (0, 0, 0));
+        // Rings are the same all around and along the trunk
+        assert_eq!(color(0f64, 3f64, -0.5f64), color(0.5f64, 0f64, 0f64));
+    }
+}
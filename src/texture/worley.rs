@@ -0,0 +1,123 @@
+//! Cellular texture from Worley noise
+use crate::color::Color;
+use crate::noise::worley::{DistanceMode, Worley};
+use crate::palette::Palette;
+use crate::texture::{GetColor, LightCoefficients, Mapping, ShadingContext, TextureTrait};
+use std::error::Error;
+
+/// Cells of random colors picked from `palette`, shaded by a Worley distance
+pub struct WorleyTexture {
+    pub kd: f64,
+    pub ks: f64,
+    pub ka: f64,
+
+    pub worley: Worley,
+    pub mode: DistanceMode,
+    /// Cells per unit
+    pub density: f64,
+    /// How much the distance darkens the cell colors, `0` giving flat cells
+    pub contrast: f64,
+    /// Never empty, see `with_palette`
+    palette: Vec<Color>,
+    pub mapping: Mapping,
+}
+
+impl WorleyTexture {
+    /// Flat cells colored with 360 hues of the pastel palette
+    pub fn new(worley: Worley, density: f64, (kd, ks, ka): (f64, f64, f64)) -> WorleyTexture {
+        WorleyTexture {
+            kd,
+            ks,
+            ka,
+            worley,
+            mode: DistanceMode::F1,
+            density,
+            contrast: 0f64,
+            palette: Palette::Pastel.colors(360),
+            mapping: Mapping::Solid,
+        }
+    }
+
+    /// Sets the colors of the cells, which cannot be empty
+    pub fn with_palette(mut self, palette: Vec<Color>) -> Result<WorleyTexture, Box<dyn Error>> {
+        if palette.is_empty() {
+            return Err("a worley texture needs at least one color".into());
+        }
+
+        self.palette = palette;
+        Ok(self)
+    }
+}
+
+impl LightCoefficients for WorleyTexture {
+    fn coefficients(&self, _ctx: &ShadingContext) -> (f64, f64, f64) {
+        (self.kd, self.ks, self.ka)
+    }
+}
+
+impl GetColor for WorleyTexture {
+    fn color(&self, ctx: &ShadingContext) -> Color {
+        let cells = self
+            .worley
+            .cells(self.mapping.coordinates(ctx) * self.density);
+
+        let index = self.worley.hash(cells.cell) % self.palette.len() as u64;
+        let shade = 1f64 - self.contrast + self.contrast * cells.value(self.mode).clamp(0f64, 1f64);
+
+        self.palette[index as usize] * shade
+    }
+}
+
+impl TextureTrait for WorleyTexture {}
+
+#[cfg(test)]
+mod tests {
+    use crate::color::Color;
+    use crate::noise::worley::{DistanceMode, Metric, Worley};
+    use crate::palette::Palette;
+    use crate::point::Point;
+    use crate::texture::worley::WorleyTexture;
+    use crate::texture::{GetColor, ShadingContext};
+
+    #[test]
+    fn flat_cells() {
+        let texture =
+            WorleyTexture::new(Worley::new(2, Metric::Euclidean), 2f64, (1f64, 0f64, 0f64));
+        let feature = texture.worley.feature((1, 1, 1)) / 2f64;
+        let color = |p| texture.color(&ShadingContext::at(p));
+
+        assert!(Palette::Pastel.colors(360).contains(&color(feature)));
+        // The closest feature point does not change nearby
+        assert_eq!(color(feature), color(feature + 1e-3));
+    }
+
+    #[test]
+    fn palette_and_borders() {
+        let texture = WorleyTexture {
+            mode: DistanceMode::F2MinusF1,
+            contrast: 1f64,
+            ..WorleyTexture::new(Worley::new(2, Metric::Chebyshev), 1f64, (1f64, 0f64, 0f64))
+                .with_palette(vec![Color::RED])
+                .unwrap()
+        };
+
+        for i in 0..50 {
+            let p = Point::new(i as f64 * 0.21f64, 0.5f64, 0.5f64);
+            let (_, g, b) = texture.color(&ShadingContext::at(p)).values();
+            assert_eq!((g, b), (0, 0));
+        }
+
+        // Points between two feature points are on a border
+        let (a, b) = (
+            texture.worley.feature((0, 0, 0)),
+            texture.worley.feature((1, 0, 0)),
+        );
+        let border = texture.color(&ShadingContext::at((a + b) / 2f64));
+        let center = texture.color(&ShadingContext::at(a));
+        assert!(border.values().0 < center.values().0);
+
+        let texture =
+            WorleyTexture::new(Worley::new(2, Metric::Chebyshev), 1f64, (1f64, 0f64, 0f64));
+        assert!(texture.with_palette(Vec::new()).is_err());
+    }
+}
@@ -0,0 +1,116 @@
+//! Tone mapping of the linear framebuffer to 8-bit output
+use crate::color::Color;
+use crate::vector::Vector;
+use serde::{Deserialize, Serialize};
+
+/// Operator compressing unbounded linear values into the displayable range
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToneMapping {
+    /// Hard clip at white
+    Clamp,
+    /// `x / (1 + x)`, never reaches white but keeps every highlight
+    Reinhard,
+    /// Narkowicz fit of the ACES filmic curve
+    Aces,
+}
+
+/// Exposure and tone mapping applied when writing an `Image`
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ToneMapper {
+    pub operator: ToneMapping,
+    /// Exposure in stops: values are scaled by `2^exposure` before the operator
+    pub exposure: f64,
+}
+
+impl ToneMapper {
+    pub const fn new(operator: ToneMapping, exposure: f64) -> ToneMapper {
+        ToneMapper { operator, exposure }
+    }
+
+    /// Maps a single channel normalized so that `1` is white
+    fn map_channel(&self, x: f64) -> f64 {
+        let x = x.max(0f64);
+
+        match self.operator {
+            ToneMapping::Clamp => x.min(1f64),
+            ToneMapping::Reinhard => x / (1f64 + x),
+            ToneMapping::Aces => {
+                let (a, b, c, d, e) = (2.51f64, 0.03f64, 2.43f64, 0.59f64, 0.14f64);
+                ((x * (a * x + b)) / (x * (c * x + d) + e)).clamp(0f64, 1f64)
+            }
+        }
+    }
+
+    /// Returns the displayable color, each channel in `[0, 255]`
+    pub fn map(&self, c: Color) -> Color {
+        let scale = 2f64.powf(self.exposure) / 255f64;
+        let v = c.to_vec() * scale;
+
+        Color::from(
+            Vector::new(
+                self.map_channel(v.x),
+                self.map_channel(v.y),
+                self.map_channel(v.z),
+            ) * 255f64,
+        )
+    }
+
+    /// Quantizes the tone mapped color to 8 bits per channel
+    #[allow(dead_code)]
+    pub fn quantize(&self, c: Color) -> (u8, u8, u8) {
+        self.map(c).values()
+    }
+}
+
+impl Default for ToneMapper {
+    fn default() -> Self {
+        ToneMapper::new(ToneMapping::Clamp, 0f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::color::Color;
+    use crate::tonemap::{ToneMapper, ToneMapping};
+    use crate::vector::Vector;
+
+    #[test]
+    fn clamp_keeps_ldr() {
+        let mapper = ToneMapper::default();
+        assert_eq!(mapper.quantize(Color::new(12, 128, 255)), (12, 128, 255));
+    }
+
+    #[test]
+    fn clamp_clips_highlights() {
+        let mapper = ToneMapper::default();
+        let c = Color::from(Vector::new(1000f64, -20f64, 300f64));
+        assert_eq!(mapper.quantize(c), (255, 0, 255));
+    }
+
+    #[test]
+    fn exposure() {
+        let mapper = ToneMapper::new(ToneMapping::Clamp, -1f64);
+        let c = Color::from(Vector::new(400f64, 200f64, 100f64));
+        assert_eq!(mapper.quantize(c), (200, 100, 50));
+    }
+
+    #[test]
+    fn reinhard_preserves_ordering() {
+        let mapper = ToneMapper::new(ToneMapping::Reinhard, 0f64);
+        let c = Color::from(Vector::new(255f64, 2550f64, 25500f64));
+        let (r, g, b) = mapper.quantize(c);
+
+        assert_eq!(r, 128);
+        assert!(r < g && g < b);
+    }
+
+    #[test]
+    fn aces_saturates() {
+        let mapper = ToneMapper::new(ToneMapping::Aces, 0f64);
+        assert_eq!(mapper.quantize(Color::BLACK), (0, 0, 0));
+
+        let c = Color::from(Vector::new(1e6f64, 1e6f64, 1e6f64));
+        assert_eq!(mapper.quantize(c), (255, 255, 255));
+    }
+}
@@ -0,0 +1,46 @@
+//! Texture coordinates
+use std::ops::{Add, Mul, Sub};
+
+/// Texture coordinates, textures repeat outside of `[0, 1]`
+#[derive(Debug, PartialOrd, PartialEq, Copy, Clone, Default)]
+pub struct Uv {
+    pub u: f64,
+    pub v: f64,
+}
+
+impl Uv {
+    pub const fn new(u: f64, v: f64) -> Uv {
+        Uv { u, v }
+    }
+}
+
+impl Add for Uv {
+    type Output = Uv;
+    fn add(self, rhs: Uv) -> Self::Output {
+        Uv::new(self.u + rhs.u, self.v + rhs.v)
+    }
+}
+
+impl Sub for Uv {
+    type Output = Uv;
+    fn sub(self, rhs: Uv) -> Self::Output {
+        Uv::new(self.u - rhs.u, self.v - rhs.v)
+    }
+}
+
+impl Mul<f64> for Uv {
+    type Output = Uv;
+    fn mul(self, rhs: f64) -> Self::Output {
+        Uv::new(self.u * rhs, self.v * rhs)
+    }
+}
+
+/// Texture coordinate differences across one pixel of the screen, zero for a
+/// point sample
+#[derive(Debug, PartialEq, Copy, Clone, Default)]
+pub struct Footprint {
+    /// Difference towards the next pixel to the right
+    pub dx: Uv,
+    /// Difference towards the next pixel below
+    pub dy: Uv,
+}